@@ -0,0 +1,50 @@
+use timely::container::{ContainerBuilder, PushInto};
+
+use differential_dataflow::trace::implementations::chunker::{RadixChunker, VecChunker};
+
+type Update = ((u64, u64), u64, isize);
+
+/// Pseudo-random updates over `keys` distinct integer keys.
+fn updates(count: usize, keys: u64) -> Vec<Update> {
+    let mut state = 0x2545F4914F6CDD1Du64;
+    (0 .. count).map(|_| {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (((state >> 20) % keys, (state >> 10) % 4), (state >> 5) % 4, 1)
+    }).collect()
+}
+
+/// Pushes `data` through `chunker` in batches of 1024, returning the chunks formed.
+fn chunk<C>(mut chunker: C, data: &[Update]) -> Vec<Vec<Update>>
+where
+    C: ContainerBuilder<Container = Vec<Update>> + for<'a> PushInto<&'a mut Vec<Update>>,
+{
+    let mut chunks = Vec::new();
+    for batch in data.chunks(1024) {
+        chunker.push_into(&mut batch.to_vec());
+        while let Some(chunk) = chunker.extract() {
+            chunks.push(std::mem::take(chunk));
+        }
+    }
+    while let Some(chunk) = chunker.finish() {
+        chunks.push(std::mem::take(chunk));
+    }
+    chunks
+}
+
+fn main() {
+
+    let count: usize = std::env::args().nth(1).map(|x| x.parse().unwrap()).unwrap_or(10_000_000);
+    let keys: u64 = std::env::args().nth(2).map(|x| x.parse().unwrap()).unwrap_or(1 << 20);
+
+    let data = updates(count, keys);
+
+    let timer = std::time::Instant::now();
+    let vec = chunk(VecChunker::default(), &data);
+    println!("VecChunker:\t{:?}", timer.elapsed());
+
+    let timer = std::time::Instant::now();
+    let radix = chunk(RadixChunker::default(), &data);
+    println!("RadixChunker:\t{:?}", timer.elapsed());
+
+    assert_eq!(vec, radix);
+}
@@ -48,6 +48,13 @@ fn main() {
                     keys.join_core(&data, |_k, &(), &()| Option::<()>::None)
                         .probe_with(&mut probe);
                 },
+                "rhh_key" => {
+                    use differential_dataflow::trace::implementations::rhh::{HashWrapper, VecKeyBatcher, VecKeyBuilder, VecKeySpine};
+                    let data = data.map(|x| HashWrapper { inner: x }).arrange::<VecKeyBatcher<_,_,_>, VecKeyBuilder<_,_,_>, VecKeySpine<_,_,_>>();
+                    let keys = keys.map(|x| HashWrapper { inner: x }).arrange::<VecKeyBatcher<_,_,_>, VecKeyBuilder<_,_,_>, VecKeySpine<_,_,_>>();
+                    keys.join_core(&data, |_k, &(), &()| Option::<()>::None)
+                        .probe_with(&mut probe);
+                },
                 _ => {
                     println!("unrecognized mode: {:?}", mode)
                 }
@@ -1,6 +1,7 @@
 //! Bi-directional Dijkstra distance labeling.
 
 use std::hash::Hash;
+use std::ops::Add;
 
 use timely::order::Product;
 use timely::dataflow::*;
@@ -121,3 +122,95 @@ where
         reached.leave()
     })
 }
+
+/// Returns the subset of `goals` that can reach each other in weighted `edges`, with the least total weight.
+///
+/// Edges are `(src, (dst, weight))` records, and weights must be non-negative, with `W::default()`
+/// the weight of an empty path. As for `bidijkstra`, each search proceeds both forward from the source
+/// and in reverse from the target. Once the searches for a goal meet, they only continue along paths
+/// lighter than the lightest path found, which suffices to find the lightest path.
+pub fn bidijkstra_weighted<G, N, W>(edges: &Collection<G, (N,(N,W))>, goals: &Collection<G, (N,N)>) -> Collection<G, ((N,N), W)>
+where
+    G: Scope<Timestamp: Lattice+Ord>,
+    N: ExchangeData+Hash,
+    W: ExchangeData+Add<Output=W>+Default,
+{
+    use crate::operators::arrange::arrangement::ArrangeByKey;
+    let forward = edges.arrange_by_key();
+    let reverse = edges.map(|(x,(y,w))| (y,(x,w))).arrange_by_key();
+    bidijkstra_weighted_arranged(&forward, &reverse, goals)
+}
+
+/// Bi-directional weighted Dijkstra search using arranged forward and reverse edge collections.
+pub fn bidijkstra_weighted_arranged<G, N, W, Tr>(
+    forward: &Arranged<G, Tr>,
+    reverse: &Arranged<G, Tr>,
+    goals: &Collection<G, (N,N)>
+) -> Collection<G, ((N,N), W)>
+where
+    G: Scope<Timestamp=Tr::Time>,
+    N: ExchangeData+Hash,
+    W: ExchangeData+Add<Output=W>+Default,
+    Tr: for<'a> TraceReader<Key<'a>=&'a N, Val<'a>=&'a (N,W), Diff=isize>+Clone+'static,
+{
+    forward
+        .stream
+        .scope().iterative::<u64,_,_>(|inner| {
+
+        let forward_edges = forward.enter(inner);
+        let reverse_edges = reverse.enter(inner);
+
+        // forward and reverse (node, (root, dist))
+        let forward = Variable::new_from(goals.map(|(x,_)| (x.clone(),(x.clone(),W::default()))).enter(inner), Product::new(Default::default(), 1));
+        let reverse = Variable::new_from(goals.map(|(_,y)| (y.clone(),(y.clone(),W::default()))).enter(inner), Product::new(Default::default(), 1));
+
+        let goals = goals.enter(inner);
+
+        // The lightest path found so far for each goal.
+        let reached =
+        forward
+            .join_map(&reverse, |_, (src,d1), (dst,d2)| ((src.clone(), dst.clone()), d1.clone() + d2.clone()))
+            .reduce(|_key, s, t| t.push((s[0].0.clone(), 1)))
+            .semijoin(&goals);
+
+        // The weight each goal's searches may still explore below, where `None` is unbounded.
+        let bounds =
+        goals
+            .map(|goal| (goal, None))
+            .concat(&reached.map(|(goal, _)| (goal, None)).negate())
+            .concat(&reached.map(|(goal, dist)| (goal, Some(dist))));
+
+        // Searches from a root continue while any of its goals has a greater bound, with `None` the least `Option`.
+        let forward_bounds = bounds.map(|((x,_y), bound)| (x, bound)).reduce(|_key, s, t| t.push((if s[0].0.is_none() { None } else { s[s.len()-1].0.clone() }, 1)));
+        let forward_next =
+        forward
+            .map(|(med, (src, dist))| (src, (med, dist)))
+            .join_map(&forward_bounds, |src, (med, dist), bound| (med.clone(), (src.clone(), dist.clone()), bound.clone()))
+            .filter(|(_med, (_src, dist), bound)| bound.as_ref().map(|bound| dist < bound).unwrap_or(true))
+            .map(|(med, (src, dist), _bound)| (med, (src, dist)))
+            .join_core(&forward_edges, |_med, (src, dist), (next, weight)| Some((next.clone(), (src.clone(), dist.clone() + weight.clone()))))
+            .concat(&forward)
+            .map(|(next, (src, dist))| ((next, src), dist))
+            .reduce(|_key, s, t| t.push((s[0].0.clone(), 1)))
+            .map(|((next, src), dist)| (next, (src, dist)));
+
+        forward.set(&forward_next);
+
+        let reverse_bounds = bounds.map(|((_x,y), bound)| (y, bound)).reduce(|_key, s, t| t.push((if s[0].0.is_none() { None } else { s[s.len()-1].0.clone() }, 1)));
+        let reverse_next =
+        reverse
+            .map(|(med, (rev, dist))| (rev, (med, dist)))
+            .join_map(&reverse_bounds, |rev, (med, dist), bound| (med.clone(), (rev.clone(), dist.clone()), bound.clone()))
+            .filter(|(_med, (_rev, dist), bound)| bound.as_ref().map(|bound| dist < bound).unwrap_or(true))
+            .map(|(med, (rev, dist), _bound)| (med, (rev, dist)))
+            .join_core(&reverse_edges, |_med, (rev, dist), (next, weight)| Some((next.clone(), (rev.clone(), dist.clone() + weight.clone()))))
+            .concat(&reverse)
+            .map(|(next, (rev, dist))| ((next, rev), dist))
+            .reduce(|_key, s, t| t.push((s[0].0.clone(), 1)))
+            .map(|((next,rev), dist)| (next, (rev, dist)));
+
+        reverse.set(&reverse_next);
+
+        reached.leave()
+    })
+}
@@ -0,0 +1,108 @@
+//! k-core decomposition of undirected graphs.
+//!
+//! The core number of a node is the largest `k` such that the node belongs to a subgraph in which
+//! every node has degree at least `k`, its k-core. Rather than peeling nodes of least degree one at
+//! a time, core numbers are computed by repeatedly replacing each node's estimate by the h-index of
+//! its neighbors' estimates, the largest `h` such that at least `h` neighbors have estimates of at
+//! least `h`. Starting from degrees, the estimates only decrease, and settle at the core numbers.
+//! The degeneracy of the graph, the largest core number, is available, but degeneracy orderings are not.
+
+use std::hash::Hash;
+
+use timely::dataflow::*;
+
+use crate::{Collection, ExchangeData};
+use crate::operators::*;
+use crate::lattice::Lattice;
+
+/// Returns pairs (node, core) of the core number of each node with an incident edge.
+///
+/// Edges are treated as undirected, and self-loops and repeated edges are ignored.
+pub fn core_numbers<G, N>(edges: &Collection<G, (N,N)>) -> Collection<G, (N, usize)>
+where
+    G: Scope<Timestamp: Lattice+Ord>,
+    N: ExchangeData+Hash,
+{
+    use crate::operators::arrange::arrangement::ArrangeByKey;
+    let symmetric =
+    edges
+        .flat_map(|(x,y)| if x != y { vec![(x.clone(),y.clone()), (y,x)] } else { vec![] })
+        .distinct()
+        .arrange_by_key();
+    core_numbers_arranged(&symmetric)
+}
+
+/// Returns the edges of `edges` whose endpoints both have core number at least `k`.
+///
+/// This computes the core numbers of `edges`; use `k_core_from_cores` to reuse core numbers that have
+/// already been computed.
+pub fn k_core<G, N>(edges: &Collection<G, (N,N)>, k: usize) -> Collection<G, (N,N)>
+where
+    G: Scope<Timestamp: Lattice+Ord>,
+    N: ExchangeData+Hash,
+{
+    k_core_from_cores(edges, &core_numbers(edges), k)
+}
+
+/// Returns the edges of `edges` whose endpoints both have core number at least `k`, from the core numbers `cores`.
+pub fn k_core_from_cores<G, N>(edges: &Collection<G, (N,N)>, cores: &Collection<G, (N, usize)>, k: usize) -> Collection<G, (N,N)>
+where
+    G: Scope<Timestamp: Lattice+Ord>,
+    N: ExchangeData+Hash,
+{
+    let nodes = cores.filter(move |(_node, core)| *core >= k).map(|(node, _core)| node);
+    edges
+        .semijoin(&nodes)
+        .map(|(x,y)| (y,x))
+        .semijoin(&nodes)
+        .map(|(y,x)| (x,y))
+}
+
+/// Returns the degeneracy of the graph, the largest of the core numbers `cores`.
+///
+/// Only the degeneracy is returned, and not a degeneracy ordering. Ordering nodes by core number does
+/// not suffice, as a node may have more neighbors of equal core number than its core number, and an
+/// ordering would instead need the order in which nodes are peeled, which is not maintained here.
+pub fn degeneracy<G, N>(cores: &Collection<G, (N, usize)>) -> Collection<G, usize>
+where
+    G: Scope<Timestamp: Lattice+Ord>,
+    N: ExchangeData,
+{
+    cores
+        .map(|(_node, core)| ((), core))
+        .reduce(|_, s, t| t.push((*s[s.len()-1].0, 1)))
+        .map(|((), core)| core)
+}
+
+use crate::trace::TraceReader;
+use crate::operators::arrange::Arranged;
+
+/// Returns pairs (node, core) of the core number of each node with an incident edge, using arranged edges.
+///
+/// The arrangement must contain each undirected edge once in each direction, and no self-loops.
+pub fn core_numbers_arranged<G, N, Tr>(edges: &Arranged<G, Tr>) -> Collection<G, (N, usize)>
+where
+    G: Scope<Timestamp=Tr::Time>,
+    N: ExchangeData+Hash,
+    Tr: for<'a> TraceReader<Key<'a>=&'a N, Val<'a>=&'a N, Diff=isize>+Clone+'static,
+{
+    // initialize each node's estimate to its degree
+    let degrees = edges.as_collection(|src, _dst| src.clone()).count().map(|(node, degree)| (node, degree as usize));
+
+    // repeatedly replace each estimate by the h-index of the neighbors' estimates
+    degrees.iterate(|inner| {
+
+        let edges = edges.enter(&inner.scope());
+
+        inner.join_core(&edges, |_node, core, neighbor| Some((neighbor.clone(), *core)))
+             .reduce(|_node, s, t| {
+                 let mut h_index = 0;
+                 let mut at_least = 0;
+                 for (core, count) in s.iter().rev() {
+                     at_least += *count as usize;
+                     h_index = std::cmp::max(h_index, std::cmp::min(**core, at_least));
+                 }
+                 t.push((h_index, 1));
+             })
+    })
+}
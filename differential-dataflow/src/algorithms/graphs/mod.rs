@@ -4,4 +4,8 @@ pub mod scc;
 pub mod sequential;
 pub mod bijkstra;
 pub mod bfs;
+pub mod sssp;
+pub mod pagerank;
+pub mod triangles;
+pub mod kcore;
 pub mod propagate;
\ No newline at end of file
@@ -0,0 +1,147 @@
+//! Global and personalized PageRank.
+//!
+//! Ranks are maintained as data, rather than as the multiplicities of nodes, so that they can be
+//! joined against and can take fractional values. Each iteration moves the rank of each node along
+//! its out-edges in equal shares, scaled down by a damping factor, and blends in a reset rank at a
+//! set of seed nodes. For global PageRank the seeds are all nodes, and for personalized PageRank
+//! they are supplied by the caller.
+//!
+//! As floating point ranks converge only in the limit, a node's rank is only updated when it moves
+//! by more than a tolerance, and the computation may additionally be limited to a number of
+//! iterations. Without such a limit the tolerance must be positive, and a very small tolerance may
+//! take many iterations to reach. Ranks are not normalized: each seed receives one minus the damping
+//! factor in each iteration, and so the ranks sum to at most the number of seeds. Rank that reaches
+//! nodes without out-edges is lost.
+
+use std::hash::{Hash, Hasher};
+
+use serde::{Deserialize, Serialize};
+use timely::order::Product;
+use timely::dataflow::*;
+use timely::dataflow::operators::Filter;
+
+use crate::{AsCollection, Collection, ExchangeData};
+use crate::operators::*;
+use crate::lattice::Lattice;
+use crate::operators::iterate::Variable;
+
+/// A rank, ordered and compared by `f64::total_cmp` so that it can be used as data.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct Rank(pub f64);
+
+impl PartialEq for Rank {
+    fn eq(&self, other: &Self) -> bool { self.cmp(other) == std::cmp::Ordering::Equal }
+}
+impl Eq for Rank { }
+impl PartialOrd for Rank {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> { Some(self.cmp(other)) }
+}
+impl Ord for Rank {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering { self.0.total_cmp(&other.0) }
+}
+impl Hash for Rank {
+    fn hash<H: Hasher>(&self, state: &mut H) { self.0.to_bits().hash(state) }
+}
+
+/// Returns pairs (node, rank) of the PageRank of each node with an incident edge.
+///
+/// The `damping` factor is the fraction of each node's rank that moves along its out-edges in each
+/// iteration. Ranks are updated only when they move by more than `tolerance`, and if `iterations`
+/// is supplied, the ranks are those after at most that many iterations.
+///
+/// # Panics
+///
+/// Panics if `iterations` is `None` and `tolerance` is not positive, as rounding may then prevent
+/// the ranks from ever settling.
+pub fn pagerank<G, N>(edges: &Collection<G, (N,N)>, damping: f64, tolerance: f64, iterations: Option<u64>) -> Collection<G, (N, Rank)>
+where
+    G: Scope<Timestamp: Lattice+Ord>,
+    N: ExchangeData+Hash,
+{
+    let nodes = edges.flat_map(|(x,y)| Some(x).into_iter().chain(Some(y)));
+    personalized_pagerank(edges, &nodes, damping, tolerance, iterations)
+}
+
+/// Returns pairs (node, rank) of the PageRank of each node reachable from `seeds`, resetting to `seeds`.
+///
+/// This is `pagerank`, but with the reset rank blended in only at the nodes of `seeds`.
+///
+/// # Panics
+///
+/// Panics if `iterations` is `None` and `tolerance` is not positive.
+pub fn personalized_pagerank<G, N>(edges: &Collection<G, (N,N)>, seeds: &Collection<G, N>, damping: f64, tolerance: f64, iterations: Option<u64>) -> Collection<G, (N, Rank)>
+where
+    G: Scope<Timestamp: Lattice+Ord>,
+    N: ExchangeData+Hash,
+{
+    use crate::operators::arrange::arrangement::ArrangeByKey;
+    let edges = edges.arrange_by_key();
+    pagerank_arranged(&edges, seeds, damping, tolerance, iterations)
+}
+
+use crate::trace::TraceReader;
+use crate::operators::arrange::Arranged;
+
+/// Returns pairs (node, rank) of the PageRank of each node reachable from `seeds`, using arranged edges.
+///
+/// # Panics
+///
+/// Panics if `iterations` is `None` and `tolerance` is not positive.
+pub fn pagerank_arranged<G, N, Tr>(edges: &Arranged<G, Tr>, seeds: &Collection<G, N>, damping: f64, tolerance: f64, iterations: Option<u64>) -> Collection<G, (N, Rank)>
+where
+    G: Scope<Timestamp=Tr::Time>,
+    N: ExchangeData+Hash,
+    Tr: for<'a> TraceReader<Key<'a>=&'a N, Val<'a>=&'a N, Diff=isize>+Clone+'static,
+{
+    assert!(iterations.is_some() || tolerance > 0.0, "pagerank requires a positive tolerance or an iteration limit, found tolerance {}", tolerance);
+
+    // out-degrees of each node, and the reset rank of each seed.
+    let degrees = edges.as_collection(|src, _dst| src.clone()).count();
+    let reset = seeds.distinct().map(move |node| (node, Rank(1.0 - damping)));
+
+    edges.stream.scope().iterative::<u64,_,_>(|inner| {
+
+        let edges = edges.enter(inner);
+        let degrees = degrees.enter(inner);
+        let reset = reset.enter(inner);
+
+        let ranks = Variable::new_from(reset.clone(), Product::new(Default::default(), 1));
+
+        // Divide each node's damped rank among its out-edges.
+        let pushed =
+        ranks
+            .join_map(&degrees, move |node, rank, degree| (node.clone(), Rank(damping * rank.0 / *degree as f64)))
+            .join_core(&edges, |_src, share, dst| Some((dst.clone(), *share)));
+
+        // Sum the shares received and the reset rank, retaining the previous rank if within `tolerance`.
+        let mut next =
+        ranks
+            .map(|(node, rank)| (node, (false, rank)))
+            .concat(&pushed.concat(&reset).map(|(node, rank)| (node, (true, rank))))
+            .reduce(move |_node, input, output| {
+                let mut previous = None;
+                let mut total = 0.0;
+                for ((received, rank), count) in input.iter() {
+                    if *received { total += rank.0 * *count as f64; }
+                    else { previous = Some(rank.0); }
+                }
+                let rank = match previous {
+                    Some(previous) if (total - previous).abs() <= tolerance => previous,
+                    _ => total,
+                };
+                if rank > 0.0 {
+                    output.push((Rank(rank), 1));
+                }
+            });
+
+        if let Some(iterations) = iterations {
+            next =
+            next.inner
+                .filter(move |(_data, time, _diff)| time.inner < iterations)
+                .as_collection();
+        }
+
+        ranks.set(&next);
+        next.leave()
+    })
+}
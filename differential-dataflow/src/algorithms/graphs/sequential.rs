@@ -1,4 +1,8 @@
 //! Sequential (non-concurrent) graph algorithms.
+//!
+//! These algorithms visit nodes in order of their identifiers, as if by a sequential program, using
+//! `sequence`. Each is maintained incrementally, and the outputs are those the sequential algorithm
+//! would produce for the current graph.
 
 use std::hash::Hash;
 
@@ -9,17 +13,24 @@ use crate::lattice::Lattice;
 use crate::operators::*;
 use crate::hashable::Hashable;
 
-fn _color<G, N>(edges: &Collection<G, (N,N)>) -> Collection<G,(N,Option<u32>)>
+/// Returns pairs (node, color) of a greedy coloring of the undirected graph `edges`.
+///
+/// Nodes are colored in order of node identifiers, each with the least positive integer not used
+/// by any of its neighbors with lower identifiers. Adjacent nodes have distinct colors, and nodes
+/// without neighbors have color one. Self-loops are ignored.
+pub fn color<G, N>(edges: &Collection<G, (N,N)>) -> Collection<G,(N,u32)>
 where
-    G: Scope<Timestamp: Lattice+Ord>,
+    G: Scope<Timestamp: Lattice+Hash+Ord>,
     N: ExchangeData+Hash,
 {
+    let nodes = endpoints(edges);
+    let edges = symmetrize(edges);
+
     // need some bogus initial values.
-    let start = edges.map(|(x,_y)| (x,u32::max_value()))
-                     .distinct();
+    let start = nodes.map(|x| (x,u32::MAX));
 
     // repeatedly apply color-picking logic.
-    sequence(&start, edges, |_node, vals| {
+    sequence(&start, &edges, |_node, vals| {
 
         // look for the first absent positive integer.
         // start at 1 in case we ever use NonZero<u32>.
@@ -28,6 +39,94 @@ where
             .find(|&i| vals.get(i as usize - 1).map(|x| *x.0) != Some(i))
             .unwrap()
     })
+    .flat_map(|(node, color)| color.map(|color| (node, color)))
+    .concat(&isolated(&nodes, &edges).map(|x| (x, 1)))
+}
+
+/// Returns a maximal independent set of the undirected graph `edges`.
+///
+/// Nodes are considered in order of node identifiers, and each is added to the set if none of its
+/// neighbors with lower identifiers were. No two nodes in the set are adjacent, and each node not
+/// in the set has a neighbor in the set. Self-loops are ignored.
+pub fn maximal_independent_set<G, N>(edges: &Collection<G, (N,N)>) -> Collection<G, N>
+where
+    G: Scope<Timestamp: Lattice+Hash+Ord>,
+    N: ExchangeData+Hash,
+{
+    independent_set(&endpoints(edges), &symmetrize(edges))
+}
+
+/// Returns a maximal matching of the undirected graph `edges`, as edges `(x,y)` with `x < y`.
+///
+/// Edges are considered in order, and each is added to the matching if none of the edges sharing
+/// an endpoint with it and preceding it were. This is a maximal independent set in the line graph
+/// of `edges`, whose size is quadratic in the degrees of nodes. No two edges in the matching share
+/// an endpoint, and each edge not in the matching shares an endpoint with an edge in the matching.
+/// Self-loops are ignored.
+pub fn maximal_matching<G, N>(edges: &Collection<G, (N,N)>) -> Collection<G, (N,N)>
+where
+    G: Scope<Timestamp: Lattice+Hash+Ord>,
+    N: ExchangeData+Hash,
+{
+    let undirected =
+    edges
+        .flat_map(|(x,y)| if x < y { Some((x,y)) } else if y < x { Some((y,x)) } else { None })
+        .distinct();
+
+    // pairs of distinct edges with a common endpoint.
+    let incident = undirected.flat_map(|(x,y)| vec![(x.clone(),(x.clone(),y.clone())), (y.clone(),(x,y))]);
+    let adjacent =
+    incident
+        .join_map(&incident, |_node, edge1, edge2| (edge1.clone(), edge2.clone()))
+        .filter(|(edge1, edge2)| edge1 != edge2)
+        .distinct();
+
+    independent_set(&undirected, &adjacent)
+}
+
+/// Greedy maximal independent set of `nodes`, for symmetric `edges` without self-loops.
+fn independent_set<G, N>(nodes: &Collection<G, N>, edges: &Collection<G, (N,N)>) -> Collection<G, N>
+where
+    G: Scope<Timestamp: Lattice+Hash+Ord>,
+    N: ExchangeData+Hash,
+{
+    // higher neighbors present their initial state, which must not exclude a node.
+    let start = nodes.map(|x| (x,false));
+
+    sequence(&start, edges, |_node, vals| !vals.iter().any(|x| *x.0))
+        .flat_map(|(node, include)| if include == Some(true) { Some(node) } else { None })
+        .concat(&isolated(nodes, edges))
+}
+
+/// The distinct endpoints of `edges`.
+fn endpoints<G, N>(edges: &Collection<G, (N,N)>) -> Collection<G, N>
+where
+    G: Scope<Timestamp: Lattice+Ord>,
+    N: ExchangeData+Hash,
+{
+    edges
+        .flat_map(|(x,y)| Some(x).into_iter().chain(Some(y)))
+        .distinct()
+}
+
+/// The distinct edges of `edges` in both directions, excluding self-loops.
+fn symmetrize<G, N>(edges: &Collection<G, (N,N)>) -> Collection<G, (N,N)>
+where
+    G: Scope<Timestamp: Lattice+Ord>,
+    N: ExchangeData+Hash,
+{
+    edges
+        .flat_map(|(x,y)| if x != y { vec![(x.clone(),y.clone()), (y,x)] } else { vec![] })
+        .distinct()
+}
+
+/// The nodes of `nodes` without neighbors in symmetric `edges`, which `sequence` does not produce.
+fn isolated<G, N>(nodes: &Collection<G, N>, edges: &Collection<G, (N,N)>) -> Collection<G, N>
+where
+    G: Scope<Timestamp: Lattice+Ord>,
+    N: ExchangeData+Hash,
+{
+    nodes.antijoin(&edges.map(|(x,_y)| x).distinct())
 }
 
 /// Applies `logic` to nodes sequentially, in order of node identifiers.
@@ -0,0 +1,53 @@
+//! Weighted single-source shortest paths.
+
+use std::hash::Hash;
+use std::ops::Add;
+
+use timely::dataflow::*;
+
+use crate::{Collection, ExchangeData};
+use crate::operators::*;
+use crate::lattice::Lattice;
+
+/// Returns pairs (node, dist) indicating the least total weight of a path to each node from a root.
+///
+/// Edges are `(src, (dst, weight))` records, and weights must be non-negative, with `W::default()`
+/// the weight of an empty path. Multiple edges between the same nodes are permitted, and the least
+/// weight among them is used. As for `bfs`, distances are maintained as edges are added and removed,
+/// which allows weights to change by removing an edge and adding it with its new weight.
+pub fn sssp<G, N, W>(edges: &Collection<G, (N,(N,W))>, roots: &Collection<G, N>) -> Collection<G, (N,W)>
+where
+    G: Scope<Timestamp: Lattice+Ord>,
+    N: ExchangeData+Hash,
+    W: ExchangeData+Add<Output=W>+Default,
+{
+    use crate::operators::arrange::arrangement::ArrangeByKey;
+    let edges = edges.arrange_by_key();
+    sssp_arranged(&edges, roots)
+}
+
+use crate::trace::TraceReader;
+use crate::operators::arrange::Arranged;
+
+/// Returns pairs (node, dist) indicating the least total weight of a path to each node from a root.
+pub fn sssp_arranged<G, N, W, Tr>(edges: &Arranged<G, Tr>, roots: &Collection<G, N>) -> Collection<G, (N, W)>
+where
+    G: Scope<Timestamp=Tr::Time>,
+    N: ExchangeData+Hash,
+    W: ExchangeData+Add<Output=W>+Default,
+    Tr: for<'a> TraceReader<Key<'a>=&'a N, Val<'a>=&'a (N,W), Diff=isize>+Clone+'static,
+{
+    // initialize roots as reaching themselves at distance zero
+    let nodes = roots.map(|x| (x, W::default()));
+
+    // repeatedly update minimal distances each node can be reached from each root
+    nodes.iterate(|inner| {
+
+        let edges = edges.enter(&inner.scope());
+        let nodes = nodes.enter(&inner.scope());
+
+        inner.join_core(&edges, |_k,l,(d,w)| Some((d.clone(), l.clone() + w.clone())))
+             .concat(&nodes)
+             .reduce(|_, s, t| t.push((s[0].0.clone(), 1)))
+     })
+}
@@ -0,0 +1,208 @@
+//! Triangle listing and counting in undirected graphs.
+//!
+//! Edges are treated as undirected, and self-loops and repeated edges are ignored. Each edge is
+//! oriented from the endpoint of lower degree to the endpoint of higher degree, breaking ties by
+//! node, so that each node has at most `O(m^{1/2})` out-edges. A triangle `(a, b, c)` then has
+//! oriented edges `(a, b)`, `(a, c)`, and `(b, c)`.
+//!
+//! Triangles are maintained as a delta query: each change to an oriented edge proposes extensions
+//! from the arranged edges and validates them against the arranged edges, in the style of the
+//! `dogsdogsdogs` half joins. Only the edges are arranged; the proposed wedges are held only until
+//! the arrangements are complete through their times, and are then validated and discarded. As
+//! edges change, a change in the degree of a node may re-orient each of its edges, and so re-emits
+//! `O(degree)` edges and the wedges they propose, even when no triangles change.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use timely::order::TotalOrder;
+use timely::progress::{Antichain, Timestamp};
+use timely::dataflow::*;
+use timely::dataflow::channels::pact::{Exchange, Pipeline};
+use timely::dataflow::operators::Operator;
+
+use crate::{Collection, AsCollection, Data, ExchangeData, Hashable};
+use crate::operators::*;
+use crate::lattice::Lattice;
+use crate::operators::arrange::{Arranged, TraceAgent};
+use crate::operators::arrange::arrangement::ArrangeByKey;
+use crate::trace::{Cursor, TraceReader};
+use crate::trace::implementations::ValSpine;
+
+/// Returns each triangle `(a, b, c)` of the undirected graph `edges` once, with `a < b < c`.
+///
+/// The updates to each triangle are produced at the times of the edge changes that complete them,
+/// which relies on the timestamps being totally ordered.
+pub fn triangles<G, N>(edges: &Collection<G, (N,N)>) -> Collection<G, (N,N,N)>
+where
+    G: Scope<Timestamp: TotalOrder+Lattice+Ord>,
+    N: ExchangeData+Hash,
+{
+    let undirected =
+    edges
+        .flat_map(|(x,y)| if x < y { Some((x,y)) } else if y < x { Some((y,x)) } else { None })
+        .distinct();
+
+    // Orient each edge from its endpoint of lesser `(degree, node)` to its endpoint of greater.
+    let degrees = undirected.flat_map(|(x,y)| Some(x).into_iter().chain(Some(y))).count();
+    let oriented =
+    undirected
+        .join_map(&degrees, |x, y, dx| (y.clone(), (x.clone(), *dx)))
+        .join_map(&degrees, |y, (x, dx), dy| if (dx, x) < (dy, y) { (x.clone(), y.clone()) } else { (y.clone(), x.clone()) });
+
+    // Out-edges and in-edges of each node.
+    let forward = oriented.arrange_by_key();
+    let reverse = oriented.map(|(x,y)| (y,x)).arrange_by_key();
+
+    // The edges `(a, b)`, `(a, c)`, and `(b, c)` of a triangle play the roles of three relations, and each
+    // change to `oriented` is a change to each of them. A change to one relation is matched against updates
+    // to earlier relations at times up to its own, and against updates to later relations at strictly earlier
+    // times, so that each combination of updates to the three relations is produced exactly once.
+
+    //   dQ/dE1 := dE1(a,b), E2(a,c), E3(b,c)
+    let changes1 = half_lookup(&oriented, &forward, false, |(a,_b)| (a.clone(), None), |(a,b), c| (a.clone(), b.clone(), c.clone()));
+    let changes1 = half_lookup(&changes1, &forward, false, |(_a,b,c)| (b.clone(), Some(c.clone())), |abc, _c| abc.clone());
+
+    //   dQ/dE2 := dE2(a,c), E1(a,b), E3(b,c)
+    let changes2 = half_lookup(&oriented, &forward, true, |(a,_c)| (a.clone(), None), |(a,c), b| (a.clone(), b.clone(), c.clone()));
+    let changes2 = half_lookup(&changes2, &forward, false, |(_a,b,c)| (b.clone(), Some(c.clone())), |abc, _c| abc.clone());
+
+    //   dQ/dE3 := dE3(b,c), E1(a,b), E2(a,c)
+    let changes3 = half_lookup(&oriented, &reverse, true, |(b,_c)| (b.clone(), None), |(b,c), a| (a.clone(), b.clone(), c.clone()));
+    let changes3 = half_lookup(&changes3, &forward, true, |(a,_b,c)| (a.clone(), Some(c.clone())), |abc, _c| abc.clone());
+
+    changes1
+        .concat(&changes2)
+        .concat(&changes3)
+        .map(|(a, b, c)| {
+            let mut triangle = [a, b, c];
+            triangle.sort();
+            let [a, b, c] = triangle;
+            (a, b, c)
+        })
+}
+
+/// Matches each record of `stream` against the values of its key in `arrangement`, without arranging `stream`.
+///
+/// The key of each record and, to validate rather than to propose, the single value to match are given by
+/// `lookup`. A record at time `t` is matched against the arranged updates at times strictly less than `t`,
+/// or less or equal to `t` if `inclusive` is set, and the results are produced at time `t`. Records are held
+/// only until the arrangement is complete through their times.
+fn half_lookup<G, N, D, L, F, O>(
+    stream: &Collection<G, D>,
+    arrangement: &Arranged<G, TraceAgent<ValSpine<N, N, G::Timestamp, isize>>>,
+    inclusive: bool,
+    lookup: L,
+    mut result: F,
+) -> Collection<G, O>
+where
+    G: Scope<Timestamp: TotalOrder+Lattice+Ord>,
+    N: ExchangeData+Hashable,
+    D: ExchangeData,
+    O: Data,
+    L: Fn(&D)->(N, Option<N>)+Clone+'static,
+    F: FnMut(&D, &N)->O+'static,
+{
+    // Records are only looked up once the arrangement is complete through their times.
+    let mut trace = arrangement.trace.clone();
+    trace.set_physical_compaction(Antichain::new().borrow());
+    let mut trace_option = Some(trace);
+
+    let exchange_lookup = lookup.clone();
+    let exchange = Exchange::new(move |update: &(D,G::Timestamp,isize)| exchange_lookup(&update.0).0.hashed().into());
+
+    // Records awaiting the completion of the arrangement, by capability.
+    let mut stash = HashMap::new();
+    // A lower bound on the times of stashed and future records.
+    let mut lower = Antichain::from_elem(<G::Timestamp as Timestamp>::minimum());
+
+    stream.inner.binary_frontier(&arrangement.stream, exchange, Pipeline, "HalfLookup", move |_,_| move |input1, input2, output| {
+
+        input1.for_each(|capability, data| {
+            stash.entry(capability.retain())
+                 .or_insert(Vec::new())
+                 .append(data);
+        });
+
+        // Drain input batches; we read the trace instead, but want the input frontier.
+        input2.for_each(|_, _| { });
+
+        if let Some(ref mut trace) = trace_option {
+            for (capability, records) in stash.iter_mut() {
+                if !input2.frontier().less_equal(capability.time()) {
+
+                    let mut session = output.session(capability);
+
+                    // Sort records for in-order cursor traversal.
+                    records.sort_by(|x, y| lookup(&x.0).cmp(&lookup(&y.0)));
+
+                    let (mut cursor, storage) = trace.cursor();
+                    for (data, time, diff) in records.iter_mut() {
+                        if !input2.frontier().less_equal(time) {
+                            let (key, val) = lookup(data);
+                            cursor.seek_key(&storage, &key);
+                            if cursor.get_key(&storage) == Some(&key) {
+                                if let Some(val) = val.as_ref() {
+                                    cursor.seek_val(&storage, val);
+                                }
+                                while let Some(found) = cursor.get_val(&storage) {
+                                    if val.as_ref().map_or(false, |val| val != found) { break; }
+                                    let mut count = 0;
+                                    cursor.map_times(&storage, |t, d| {
+                                        if *t < *time || (inclusive && *t == *time) { count += *d; }
+                                    });
+                                    if count != 0 {
+                                        session.give((result(data, found), time.clone(), *diff * count));
+                                    }
+                                    cursor.step_val(&storage);
+                                }
+                                cursor.rewind_vals(&storage);
+                            }
+                            *diff = 0;
+                        }
+                    }
+
+                    records.retain(|(_, _, diff)| *diff != 0);
+                }
+            }
+        }
+
+        // Drop fully processed capabilities.
+        stash.retain(|_, records| !records.is_empty());
+
+        // Compact the trace only through the previous lower bound, which is strictly less than the times of all
+        // remaining records, so that arranged times less than a record's time are not advanced to equal it.
+        let mut current = Antichain::new();
+        for time in input1.frontier().frontier().iter() {
+            current.insert(time.clone());
+        }
+        for capability in stash.keys() {
+            current.insert(capability.time().clone());
+        }
+        if current != lower {
+            if let Some(trace) = trace_option.as_mut() {
+                trace.set_logical_compaction(lower.borrow());
+            }
+            lower = current;
+        }
+
+        if lower.is_empty() {
+            trace_option = None;
+        }
+    })
+    .as_collection()
+}
+
+/// Returns pairs (node, count) of the number of triangles of `triangles` containing each node.
+///
+/// Nodes in no triangles are absent. Together with the degree of each node, the counts determine
+/// its local clustering coefficient, `2 * count / (degree * (degree - 1))`.
+pub fn triangle_counts<G, N>(triangles: &Collection<G, (N,N,N)>) -> Collection<G, (N, isize)>
+where
+    G: Scope<Timestamp: Lattice+Ord>,
+    N: ExchangeData+Hash,
+{
+    triangles
+        .flat_map(|(a,b,c)| vec![a, b, c])
+        .count()
+}
@@ -5,9 +5,9 @@
 //! about the collection that once true stay true, such as the exact changes data undergo
 //! at each time, and the number of distinct updates at each time.
 //!
-//! The methods are parameterized by implementors of byte sources and byte sinks. For
-//! example implementations of these traits, consult the commented text at the end of
-//! this file.
+//! The methods are parameterized by implementors of byte sources and byte sinks. The `file`
+//! and `tcp` modules provide implementations backed by append-only log files and by socket
+//! connections, and further examples can be found in the commented text at the end of this file.
 
 use std::time::Duration;
 use serde::{Deserialize, Serialize};
@@ -49,6 +49,30 @@ pub trait Writer<T> {
     fn done(&self) -> bool;
 }
 
+/// The number of bytes in the length prefix of each encoded message.
+const PREFIX: usize = std::mem::size_of::<u64>();
+
+/// Appends to `buffer` the `bincode` encoding of `item`, prefixed by its length.
+fn encode<S: Serialize>(item: &S, buffer: &mut Vec<u8>) {
+    let start = buffer.len();
+    buffer.extend_from_slice(&[0u8; PREFIX]);
+    bincode::serialize_into(&mut *buffer, item).expect("failed to serialize message");
+    let length = (buffer.len() - start - PREFIX) as u64;
+    buffer[start .. start + PREFIX].copy_from_slice(&length.to_le_bytes());
+}
+
+/// Iterates over the complete length-prefixed frames in `bytes`, as pairs of end offset and payload.
+fn frames(bytes: &[u8]) -> impl Iterator<Item = (usize, &[u8])> {
+    let mut offset = 0;
+    std::iter::from_fn(move || {
+        let prefix = bytes.get(offset .. offset + PREFIX)?;
+        let length = u64::from_le_bytes(prefix.try_into().unwrap()) as usize;
+        let payload = bytes.get(offset + PREFIX .. (offset + PREFIX).checked_add(length)?)?;
+        offset += PREFIX + length;
+        Some((offset, payload))
+    })
+}
+
 /// A deduplicating, re-ordering iterator.
 pub mod iterator {
 
@@ -718,6 +742,556 @@ pub mod sink {
     }
 }
 
+/// Capture and replay through append-only log files.
+///
+/// Each worker writing a captured collection appends length-prefixed `bincode` encodings of
+/// `Message`s to its own segment file, named `worker-{index}.log`, in a shared directory.
+/// Readers tail every `.log` segment in the directory, including segments that appear later,
+/// and present the messages they find to `source::build`. As the protocol tolerates both
+/// duplication and reordering, segments may be copied, re-written from the start by a restarted
+/// writer, or read in any order, without affecting the replayed collection.
+pub mod file {
+
+    use std::collections::{BTreeMap, VecDeque};
+    use std::fs::{File, OpenOptions};
+    use std::hash::Hash;
+    use std::io::{Read, Seek, SeekFrom, Write};
+    use std::marker::PhantomData;
+    use std::path::{Path, PathBuf};
+    use std::rc::Rc;
+    use std::cell::RefCell;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use serde::{Deserialize, Serialize};
+    use serde::de::DeserializeOwned;
+    use timely::dataflow::{Scope, Stream};
+    use timely::progress::Timestamp;
+    use timely::scheduling::SyncActivator;
+
+    use crate::{lattice::Lattice, ExchangeData, Hashable};
+    use super::{Message, Writer, encode, frames};
+
+    /// Records the updates in `stream` to segment files in `directory`.
+    ///
+    /// The returned token keeps the writers open; once dropped, no further messages are written.
+    /// As with `sink::build`, the stream must already be consolidated.
+    pub fn sink<G, D, T, R>(stream: &Stream<G, (D, T, R)>, directory: &Path) -> std::io::Result<Box<dyn std::any::Any>>
+    where
+        G: Scope<Timestamp = T>,
+        D: ExchangeData + Hash + Serialize + for<'a> Deserialize<'a>,
+        T: ExchangeData + Hash + Serialize + for<'a> Deserialize<'a> + Timestamp + Lattice,
+        R: ExchangeData + Hash + Serialize + for<'a> Deserialize<'a>,
+    {
+        std::fs::create_dir_all(directory)?;
+        let path = directory.join(format!("worker-{}.log", stream.scope().index()));
+        let writer = Rc::new(RefCell::new(FileWriter::<D, T, R>::open(&path)?));
+        let sink_hash = directory.to_string_lossy().hashed();
+        super::sink::build(stream, sink_hash, Rc::downgrade(&writer), Rc::downgrade(&writer));
+        Ok(Box::new(writer))
+    }
+
+    /// Replays the updates recorded in segment files in `directory`.
+    ///
+    /// Segments are divided among the workers of `scope`, and each is re-read every `interval`
+    /// for newly appended messages. The source continues until the recorded collection is
+    /// complete, or the returned token is dropped.
+    pub fn source<G, D, T, R>(scope: G, directory: &Path, interval: Duration) -> (Box<dyn std::any::Any + Send + Sync>, Stream<G, (D, T, R)>)
+    where
+        G: Scope<Timestamp = T>,
+        D: ExchangeData + Hash + DeserializeOwned,
+        T: ExchangeData + Hash + DeserializeOwned + Timestamp + Lattice,
+        R: ExchangeData + Hash + DeserializeOwned,
+    {
+        let (index, peers) = (scope.index(), scope.peers());
+        let directory = directory.to_owned();
+        super::source::build(scope, move |activator| {
+            FileSource::new(directory, activator, interval)
+                .with_filter(move |segment| (segment.to_string_lossy().hashed() as usize) % peers == index)
+        })
+    }
+
+    /// An append-only log of messages, implementing `Writer`.
+    ///
+    /// Each message is written and synchronized before `poll` returns. Should a write fail, the
+    /// log is returned to its prior length and the write is retried after a delay.
+    pub struct FileWriter<D, T, R> {
+        /// The open segment file.
+        file: File,
+        /// The length of the file through the last complete message.
+        length: u64,
+        /// Re-used buffer for encoding messages.
+        buffer: Vec<u8>,
+        phantom: PhantomData<(D, T, R)>,
+    }
+
+    impl<D, T, R> FileWriter<D, T, R> {
+        /// Opens the segment at `path` for appending, creating it if needed.
+        ///
+        /// Any incomplete message at the end of an existing segment, as left by an interrupted
+        /// writer, is removed so that further messages can be read back.
+        pub fn open(path: &Path) -> std::io::Result<Self> {
+            let mut file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path)?;
+            let mut bytes = Vec::new();
+            file.read_to_end(&mut bytes)?;
+            let length = frames(&bytes).map(|(offset, _)| offset).last().unwrap_or(0) as u64;
+            if length < bytes.len() as u64 {
+                file.set_len(length)?;
+            }
+            file.seek(SeekFrom::Start(length))?;
+            Ok(FileWriter { file, length, buffer: Vec::new(), phantom: PhantomData })
+        }
+
+        fn append(&mut self) -> std::io::Result<()> {
+            self.file.write_all(&self.buffer)?;
+            self.file.sync_data()
+        }
+    }
+
+    impl<D, T, R> Writer<Message<D, T, R>> for FileWriter<D, T, R>
+    where
+        D: Serialize,
+        T: Serialize,
+        R: Serialize,
+    {
+        fn poll(&mut self, item: &Message<D, T, R>) -> Option<Duration> {
+            self.buffer.clear();
+            encode(item, &mut self.buffer);
+            match self.append() {
+                Ok(()) => {
+                    self.length += self.buffer.len() as u64;
+                    None
+                }
+                Err(_) => {
+                    // Discard any partial write, so that the retried message follows the last complete one.
+                    let _ = self.file.set_len(self.length);
+                    let _ = self.file.seek(SeekFrom::Start(self.length));
+                    Some(Duration::from_secs(1))
+                }
+            }
+        }
+        fn done(&self) -> bool { true }
+    }
+
+    /// Reads messages from the segment files in a directory, as they are appended.
+    ///
+    /// The iterator yields messages until it has caught up with all segments, and then returns
+    /// `None`. A background thread re-activates the source every `interval`, at which point the
+    /// iterator may again yield messages. Incomplete messages at the end of a segment are left to
+    /// be read once their writer completes them.
+    pub struct FileSource<D, T, R> {
+        /// The directory containing segment files.
+        directory: PathBuf,
+        /// Selects the segments this source reads.
+        filter: Box<dyn Fn(&Path) -> bool>,
+        /// For each segment, the offset through which it has been read.
+        offsets: BTreeMap<PathBuf, u64>,
+        /// Messages read but not yet yielded.
+        pending: VecDeque<Message<D, T, R>>,
+        /// Keeps the re-activation thread running.
+        _alive: Arc<()>,
+    }
+
+    impl<D, T, R> FileSource<D, T, R> {
+        /// Creates a source reading all segments in `directory`, re-activated every `interval`.
+        pub fn new(directory: PathBuf, activator: SyncActivator, interval: Duration) -> Self {
+            let alive = Arc::new(());
+            let weak = Arc::downgrade(&alive);
+            std::thread::spawn(move || {
+                while weak.upgrade().is_some() {
+                    std::thread::sleep(interval);
+                    if activator.activate().is_err() { break; }
+                }
+            });
+            FileSource {
+                directory,
+                filter: Box::new(|_| true),
+                offsets: BTreeMap::new(),
+                pending: VecDeque::new(),
+                _alive: alive,
+            }
+        }
+
+        /// Restricts the source to segments whose file names satisfy `filter`.
+        pub fn with_filter<F: Fn(&Path) -> bool + 'static>(mut self, filter: F) -> Self {
+            self.filter = Box::new(filter);
+            self
+        }
+    }
+
+    impl<D: DeserializeOwned, T: DeserializeOwned, R: DeserializeOwned> FileSource<D, T, R> {
+        /// Reads any newly appended messages from the directory's segments.
+        fn read_segments(&mut self) -> std::io::Result<()> {
+            for entry in std::fs::read_dir(&self.directory)? {
+                let path = entry?.path();
+                if path.extension().map(|e| e == "log") != Some(true) { continue; }
+                if !path.file_name().map(|name| (self.filter)(Path::new(name))).unwrap_or(false) { continue; }
+
+                let offset = self.offsets.entry(path.clone()).or_insert(0);
+                let mut file = File::open(&path)?;
+                // A segment shorter than our offset has been re-written, and is read again from the start.
+                if file.metadata()?.len() < *offset { *offset = 0; }
+                file.seek(SeekFrom::Start(*offset))?;
+                let mut bytes = Vec::new();
+                file.read_to_end(&mut bytes)?;
+
+                let mut consumed = 0;
+                for (end, payload) in frames(&bytes) {
+                    match bincode::deserialize(payload) {
+                        Ok(message) => self.pending.push_back(message),
+                        Err(_) => break,
+                    }
+                    consumed = end;
+                }
+                *offset += consumed as u64;
+            }
+            Ok(())
+        }
+    }
+
+    impl<D: DeserializeOwned, T: DeserializeOwned, R: DeserializeOwned> Iterator for FileSource<D, T, R> {
+        type Item = Message<D, T, R>;
+        fn next(&mut self) -> Option<Self::Item> {
+            if self.pending.is_empty() {
+                // Errors, for example a directory not yet created, are retried on the next activation.
+                let _ = self.read_segments();
+            }
+            self.pending.pop_front()
+        }
+    }
+}
+
+/// Capture and replay over TCP connections.
+///
+/// Each worker writing a captured collection connects to a listening reader, and sends it
+/// length-prefixed `bincode` encodings of `Message`s. The reader acknowledges the messages it
+/// receives by replying with the number of bytes of complete messages it has received on the
+/// connection, and writers retain only the messages not yet acknowledged. Should the connection
+/// fail, the writer reconnects and retransmits these messages, relying on the protocol's tolerance
+/// for duplication. The reader accepts any number of connections, including reconnections,
+/// and presents the messages it receives to `source::build`.
+///
+/// Writers use memory proportional to the messages in flight, rather than to the volume of the
+/// captured collection. A reader that restarts does not receive the messages acknowledged before
+/// it restarted, and so the replaying dataflow should run for as long as the writers do.
+pub mod tcp {
+
+    use std::collections::VecDeque;
+    use std::hash::Hash;
+    use std::io::{ErrorKind, Read, Write};
+    use std::marker::PhantomData;
+    use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+    use std::rc::Rc;
+    use std::cell::RefCell;
+    use std::sync::{Arc, Weak};
+    use std::sync::mpsc::{channel, Receiver, Sender};
+    use std::time::Duration;
+
+    use serde::{Deserialize, Serialize};
+    use serde::de::DeserializeOwned;
+    use timely::dataflow::{Scope, Stream};
+    use timely::progress::Timestamp;
+    use timely::scheduling::SyncActivator;
+
+    use crate::{lattice::Lattice, ExchangeData, Hashable};
+    use super::{Message, Progress, Writer, encode, frames};
+
+    /// How long writers wait before reconnecting, and reader threads between checks for shutdown.
+    const RETRY: Duration = Duration::from_millis(100);
+
+    /// How long writers and readers wait for a blocked write before abandoning the connection.
+    const TIMEOUT: Duration = Duration::from_secs(1);
+
+    /// The number of bytes in each acknowledgement, a count of bytes received.
+    const ACK: usize = std::mem::size_of::<u64>();
+
+    /// Records the updates in `stream` by sending them to the reader listening at `address`.
+    ///
+    /// The returned token keeps the writers running; once dropped, no further messages are sent.
+    /// As with `sink::build`, the stream must already be consolidated.
+    pub fn sink<G, D, T, R>(stream: &Stream<G, (D, T, R)>, address: &str) -> Box<dyn std::any::Any>
+    where
+        G: Scope<Timestamp = T>,
+        D: ExchangeData + Hash + Serialize + for<'a> Deserialize<'a>,
+        T: ExchangeData + Hash + Serialize + for<'a> Deserialize<'a> + Timestamp + Lattice,
+        R: ExchangeData + Hash + Serialize + for<'a> Deserialize<'a>,
+    {
+        let writer = Rc::new(RefCell::new(TcpWriter::<D, T, R>::new(address)));
+        let sink_hash = address.hashed();
+        super::sink::build(stream, sink_hash, Rc::downgrade(&writer), Rc::downgrade(&writer));
+        Box::new(writer)
+    }
+
+    /// Replays the updates received by `listener`, if supplied.
+    ///
+    /// Typically one worker supplies a listener and the others supply `None`; the received
+    /// messages are distributed among all workers. The source continues until the recorded
+    /// collection is complete, or the returned token is dropped. An error is returned if the
+    /// listener cannot be configured.
+    pub fn source<G, D, T, R>(scope: G, listener: Option<TcpListener>) -> std::io::Result<(Box<dyn std::any::Any + Send + Sync>, Stream<G, (D, T, R)>)>
+    where
+        G: Scope<Timestamp = T>,
+        D: ExchangeData + Hash + DeserializeOwned,
+        T: ExchangeData + Hash + DeserializeOwned + Timestamp + Lattice,
+        R: ExchangeData + Hash + DeserializeOwned,
+    {
+        if let Some(listener) = listener.as_ref() {
+            listener.set_nonblocking(true)?;
+        }
+        Ok(super::source::build(scope, move |activator| {
+            listener
+                .map(|listener| TcpSource::start(listener, activator))
+                .into_iter()
+                .flatten()
+        }))
+    }
+
+    /// A message retained for retransmission until the reader acknowledges it.
+    enum Unacknowledged<T> {
+        /// The encoding of an update message.
+        Updates(Vec<u8>),
+        /// A progress statement, merging those sent that chain.
+        Progress(Progress<T>),
+    }
+
+    /// Sends messages to a reader over TCP, implementing `Writer`.
+    ///
+    /// Each message is sent before `poll` returns, and retained until the reader acknowledges it.
+    /// If the connection cannot be established, fails, or a write does not complete within
+    /// `TIMEOUT`, `poll` asks to be retried; the next connection first retransmits all messages not
+    /// yet acknowledged. Progress statements that chain, each starting where the previous ended,
+    /// are retained and retransmitted as a single statement spanning all of them.
+    pub struct TcpWriter<D, T, R> {
+        /// The address of the reader.
+        address: String,
+        /// The current connection, if any.
+        stream: Option<TcpStream>,
+        /// Messages not yet acknowledged, in the order sent, for retransmission on reconnection.
+        history: VecDeque<Unacknowledged<T>>,
+        /// For each message of `history`, the number of bytes sent on the current connection through its last sending.
+        sent: VecDeque<u64>,
+        /// The number of bytes sent on the current connection.
+        written: u64,
+        /// Received bytes of acknowledgements not yet complete.
+        acks: Vec<u8>,
+        phantom: PhantomData<(D, R)>,
+    }
+
+    impl<D, T, R> TcpWriter<D, T, R> {
+        /// Creates a writer for the reader at `address`, which will connect on first use.
+        pub fn new<A: Into<String>>(address: A) -> Self {
+            TcpWriter {
+                address: address.into(),
+                stream: None,
+                history: VecDeque::new(),
+                sent: VecDeque::new(),
+                written: 0,
+                acks: Vec::new(),
+                phantom: PhantomData,
+            }
+        }
+
+        /// Reads any acknowledgements available on the connection, and forgets acknowledged messages.
+        fn acknowledge(&mut self) -> std::io::Result<()> {
+            let stream = self.stream.as_mut().ok_or(ErrorKind::NotConnected)?;
+            stream.set_nonblocking(true)?;
+            let mut chunk = [0u8; 1024];
+            let result = loop {
+                match stream.read(&mut chunk) {
+                    Ok(0) => break Err(ErrorKind::UnexpectedEof.into()),
+                    Ok(count) => self.acks.extend_from_slice(&chunk[.. count]),
+                    Err(error) if error.kind() == ErrorKind::WouldBlock => break Ok(()),
+                    Err(error) if error.kind() == ErrorKind::Interrupted => { },
+                    Err(error) => break Err(error),
+                }
+            };
+
+            // Only the most recent acknowledgement matters, as each covers all bytes before it.
+            // Acknowledgements received before the connection failed are still honored.
+            let complete = self.acks.len() - self.acks.len() % ACK;
+            if complete > 0 {
+                let acknowledged = u64::from_le_bytes(self.acks[complete - ACK .. complete].try_into().unwrap());
+                self.acks.drain(.. complete);
+                while self.sent.front().is_some_and(|sent| *sent <= acknowledged) {
+                    self.sent.pop_front();
+                    self.history.pop_front();
+                }
+            }
+
+            result?;
+            stream.set_nonblocking(false)
+        }
+    }
+
+    impl<D, T, R> TcpWriter<D, T, R>
+    where
+        D: Serialize,
+        T: Serialize + Clone,
+        R: Serialize,
+    {
+        /// Ensures a connection, establishing one and retransmitting unacknowledged messages if needed.
+        fn connect(&mut self) -> std::io::Result<()> {
+            if self.stream.is_none() {
+                let address = self.address.to_socket_addrs()?.next().ok_or(ErrorKind::AddrNotAvailable)?;
+                let mut stream = TcpStream::connect_timeout(&address, RETRY)?;
+                stream.set_nodelay(true)?;
+                stream.set_write_timeout(Some(TIMEOUT))?;
+                self.sent.clear();
+                self.acks.clear();
+                let mut buffer = Vec::new();
+                for message in self.history.iter() {
+                    match message {
+                        Unacknowledged::Updates(bytes) => buffer.extend_from_slice(bytes),
+                        Unacknowledged::Progress(progress) => encode(&Message::<D, T, R>::Progress(progress.clone()), &mut buffer),
+                    }
+                    self.sent.push_back(buffer.len() as u64);
+                }
+                stream.write_all(&buffer)?;
+                self.written = buffer.len() as u64;
+                self.stream = Some(stream);
+            }
+            Ok(())
+        }
+    }
+
+    impl<D, T, R> Writer<Message<D, T, R>> for TcpWriter<D, T, R>
+    where
+        D: Serialize,
+        T: Serialize + Clone + PartialEq,
+        R: Serialize,
+    {
+        fn poll(&mut self, item: &Message<D, T, R>) -> Option<Duration> {
+            let mut buffer = Vec::new();
+            encode(item, &mut buffer);
+            let sent = self.connect().is_ok()
+                && self.acknowledge().is_ok()
+                && self.stream.as_mut().map(|stream| stream.write_all(&buffer).is_ok()) == Some(true);
+            if !sent {
+                // Forget the connection; the reader discards any partially sent message.
+                self.stream = None;
+                return Some(RETRY);
+            }
+            self.written += buffer.len() as u64;
+            match item {
+                Message::Updates(_) => {
+                    self.history.push_back(Unacknowledged::Updates(buffer));
+                    self.sent.push_back(self.written);
+                }
+                Message::Progress(progress) => {
+                    match self.history.back_mut() {
+                        Some(Unacknowledged::Progress(last)) if last.upper == progress.lower => {
+                            last.upper.clone_from(&progress.upper);
+                            last.counts.extend(progress.counts.iter().cloned());
+                            // The merged statement is acknowledged only once this message is.
+                            *self.sent.back_mut().unwrap() = self.written;
+                        }
+                        _ => {
+                            self.history.push_back(Unacknowledged::Progress(progress.clone()));
+                            self.sent.push_back(self.written);
+                        }
+                    }
+                }
+            }
+            None
+        }
+        fn done(&self) -> bool { true }
+    }
+
+    /// Receives messages from any number of TCP connections.
+    ///
+    /// Background threads accept connections and decode messages, re-activating the source as
+    /// messages arrive, and acknowledge the messages they decode. The iterator yields received
+    /// messages, and returns `None` when none are available. The threads stop once the source
+    /// is dropped.
+    pub struct TcpSource<D, T, R> {
+        /// Decoded messages from all connections.
+        receiver: Receiver<Message<D, T, R>>,
+        /// Keeps the background threads running.
+        _alive: Arc<()>,
+    }
+
+    impl<D, T, R> TcpSource<D, T, R>
+    where
+        D: DeserializeOwned + Send + 'static,
+        T: DeserializeOwned + Send + 'static,
+        R: DeserializeOwned + Send + 'static,
+    {
+        /// Creates a source receiving connections from `listener`.
+        pub fn new(listener: TcpListener, activator: SyncActivator) -> std::io::Result<Self> {
+            listener.set_nonblocking(true)?;
+            Ok(Self::start(listener, activator))
+        }
+
+        /// Creates a source receiving connections from `listener`, which must be non-blocking.
+        fn start(listener: TcpListener, activator: SyncActivator) -> Self {
+            let activator = Arc::new(activator);
+            let (sender, receiver) = channel();
+            let alive = Arc::new(());
+            let weak = Arc::downgrade(&alive);
+            std::thread::spawn(move || {
+                while weak.upgrade().is_some() {
+                    match listener.accept() {
+                        Ok((stream, _)) => {
+                            let (sender, activator, weak) = (sender.clone(), activator.clone(), weak.clone());
+                            std::thread::spawn(move || receive(stream, sender, activator, weak));
+                        }
+                        Err(error) if error.kind() == ErrorKind::WouldBlock => std::thread::sleep(RETRY),
+                        Err(_) => break,
+                    }
+                }
+            });
+            TcpSource { receiver, _alive: alive }
+        }
+    }
+
+    impl<D, T, R> Iterator for TcpSource<D, T, R> {
+        type Item = Message<D, T, R>;
+        fn next(&mut self) -> Option<Self::Item> {
+            self.receiver.try_recv().ok()
+        }
+    }
+
+    /// Decodes messages from `stream` until it closes, fails, or the source is dropped.
+    ///
+    /// After each read that completes messages, replies with the number of bytes of complete
+    /// messages received on the connection. A connection presenting a message that cannot be
+    /// decoded is dropped, and any partial message is discarded; the writer will reconnect and
+    /// retransmit.
+    fn receive<D, T, R>(mut stream: TcpStream, sender: Sender<Message<D, T, R>>, activator: Arc<SyncActivator>, alive: Weak<()>)
+    where
+        D: DeserializeOwned,
+        T: DeserializeOwned,
+        R: DeserializeOwned,
+    {
+        if stream.set_nonblocking(false).is_err() || stream.set_read_timeout(Some(RETRY)).is_err() || stream.set_write_timeout(Some(TIMEOUT)).is_err() { return; }
+        let mut buffer = Vec::new();
+        let mut chunk = vec![0u8; 1 << 16];
+        let mut received = 0u64;
+        while alive.upgrade().is_some() {
+            match stream.read(&mut chunk) {
+                Ok(0) => return,
+                Ok(count) => {
+                    buffer.extend_from_slice(&chunk[.. count]);
+                    let mut consumed = 0;
+                    for (end, payload) in frames(&buffer) {
+                        let Ok(message) = bincode::deserialize(payload) else { return; };
+                        if sender.send(message).is_err() { return; }
+                        consumed = end;
+                    }
+                    buffer.drain(.. consumed);
+                    if consumed > 0 {
+                        received += consumed as u64;
+                        if activator.activate().is_err() { return; }
+                        if stream.write_all(&received.to_le_bytes()).is_err() { return; }
+                    }
+                }
+                Err(error) if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut | ErrorKind::Interrupted) => { },
+                Err(_) => return,
+            }
+        }
+    }
+}
+
 // pub mod kafka {
 
 //     use serde::{Serialize, Deserialize};
@@ -291,6 +291,67 @@ impl<G: Scope, D: Clone+'static, R: Clone+'static> Collection<G, D, R> {
             .flat_map(move |(data, time, delta)| logic(data).into_iter().map(move |x| (x, time.clone(), delta.clone())))
             .as_collection()
     }
+    /// Creates a pair of collections by applying a fallible function to each input element.
+    ///
+    /// The first collection contains the results of successful applications, and the second collection
+    /// contains the errors of the failed applications. As errors are data, they are retracted when the
+    /// input elements that produced them are retracted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     let data = scope.new_collection_from(-2 .. 3).1;
+    ///
+    ///     let (oks, errs) = data.map_fallible(|x| u32::try_from(x).map_err(|_| x));
+    ///
+    ///     oks.assert_eq(&scope.new_collection_from(0 .. 3u32).1);
+    ///     errs.assert_eq(&scope.new_collection_from(-2 .. 0).1);
+    /// });
+    /// ```
+    pub fn map_fallible<D2, E, L>(&self, logic: L) -> (Collection<G, D2, R>, Collection<G, E, R>)
+    where
+        D2: Data,
+        E: Data,
+        L: FnMut(D) -> Result<D2, E> + 'static,
+    {
+        self.map(logic)
+            .ok_err()
+    }
+    /// Creates a pair of collections by applying a function producing fallible results to each input element.
+    ///
+    /// The first collection contains the successful results, and the second collection contains the errors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     let data = scope.new_collection_from(0 .. 4).1;
+    ///
+    ///     // odd values are errors
+    ///     let (oks, errs) = data.flat_map_fallible(|x| (0 .. x).map(|y| if y % 2 == 0 { Ok(y) } else { Err(y) }));
+    ///
+    ///     oks.assert_eq(&scope.new_collection_from(vec![0, 0, 0, 2]).1);
+    ///     errs.assert_eq(&scope.new_collection_from(vec![1, 1]).1);
+    /// });
+    /// ```
+    pub fn flat_map_fallible<D2, E, I, L>(&self, logic: L) -> (Collection<G, D2, R>, Collection<G, E, R>)
+    where
+        G::Timestamp: Clone,
+        D2: Data,
+        E: Data,
+        I: IntoIterator<Item=Result<D2, E>>,
+        L: FnMut(D) -> I + 'static,
+    {
+        self.flat_map(logic)
+            .ok_err()
+    }
     /// Creates a new collection containing those input records satisfying the supplied predicate.
     ///
     /// # Examples
@@ -567,6 +628,48 @@ impl<G: Scope, D: Clone+'static, R: Clone+'static> Collection<G, D, R> {
         self.consolidate()
             .inspect(|x| panic!("Assertion failed: non-empty collection: {:?}", x));
     }
+
+    /// Subscribes to the updates of the collection, returning a handle from which they can be pulled.
+    ///
+    /// The handle yields each completed time with the consolidated updates at that time, once the frontier
+    /// of the collection has passed the time, and can also produce the accumulated contents of the collection
+    /// at completed times. Each worker observes only the updates that flow through it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    ///
+    /// ::timely::execute_directly(|worker| {
+    ///
+    ///     let (mut input, mut subscribe) = worker.dataflow::<u64,_,_>(|scope| {
+    ///         let (input, data) = scope.new_collection::<u64, isize>();
+    ///         (input, data.map(|x| x * 2).subscribe())
+    ///     });
+    ///
+    ///     input.insert(1);
+    ///     input.insert(2);
+    ///     input.advance_to(1);
+    ///     input.flush();
+    ///     while subscribe.frontier().less_equal(&0) { worker.step(); }
+    ///     assert_eq!(subscribe.next(), Some((0, vec![(2, 1), (4, 1)])));
+    ///
+    ///     input.remove(1);
+    ///     input.advance_to(2);
+    ///     input.flush();
+    ///     while subscribe.frontier().less_equal(&1) { worker.step(); }
+    ///     assert_eq!(subscribe.next(), Some((1, vec![(2, -1)])));
+    ///     assert_eq!(subscribe.next(), None);
+    ///     assert_eq!(subscribe.snapshot(), vec![(4, 1)]);
+    /// });
+    /// ```
+    pub fn subscribe(&self) -> crate::subscribe::SubscribeHandle<G::Timestamp, D, R>
+    where
+        D: crate::Data,
+        R: Semigroup,
+    {
+        crate::subscribe::subscribe(self)
+    }
 }
 
 use timely::dataflow::scopes::ScopeParent;
@@ -605,6 +708,30 @@ where
     }
 }
 
+/// Methods for collections of fallible results.
+impl<G: Scope, D: Data, E: Data, R: Clone+'static> Collection<G, Result<D, E>, R> {
+    /// Splits a collection of results into a collection of successes and a collection of errors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     let (oks, errs) = scope.new_collection_from(vec![Ok(1), Err('a')]).1.ok_err();
+    ///
+    ///     oks.assert_eq(&scope.new_collection_from(vec![1]).1);
+    ///     errs.assert_eq(&scope.new_collection_from(vec!['a']).1);
+    /// });
+    /// ```
+    pub fn ok_err(&self) -> (Collection<G, D, R>, Collection<G, E, R>) {
+        let oks = self.flat_map(Result::ok);
+        let errs = self.flat_map(Result::err);
+        (oks, errs)
+    }
+}
+
 /// Methods requiring a region as the scope.
 impl<G: Scope, D, R, C: Container+Data> Collection<Child<'_, G, G::Timestamp>, D, R, C>
 {
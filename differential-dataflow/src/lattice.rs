@@ -334,6 +334,47 @@ pub fn antichain_meet<T: Lattice+Clone>(one: &[T], other: &[T]) -> Antichain<T>
     upper
 }
 
+/// Extends `times` with the joins of its elements, leaving it sorted, deduplicated, and closed under join.
+///
+/// Accumulations of updates at the times of `times` can only change at the joins of those times, and
+/// visiting the result in order visits each time after all times less than it in the partial order.
+///
+/// # Examples
+///
+/// ```
+/// # use timely::order::Product;
+/// # use differential_dataflow::lattice::close_under_join;
+/// # fn main() {
+///
+/// let mut times = vec![Product::new(3, 4), Product::new(5, 2), Product::new(0, 7)];
+/// close_under_join(&mut times);
+/// assert_eq!(times, vec![
+///     Product::new(0, 7),
+///     Product::new(3, 4),
+///     Product::new(3, 7),
+///     Product::new(5, 2),
+///     Product::new(5, 4),
+///     Product::new(5, 7),
+/// ]);
+/// # }
+/// ```
+pub fn close_under_join<T: Lattice+Ord+Clone>(times: &mut Vec<T>) {
+    times.sort();
+    times.dedup();
+    // Times incomparable in the partial order are earlier in the sort order, so only those pairs are joined.
+    loop {
+        let mut joins = Vec::new();
+        for (index, time1) in times.iter().enumerate() {
+            joins.extend(times[index + 1 ..].iter().filter(|time2| !time1.less_equal(time2)).map(|time2| time1.join(time2)));
+        }
+        let len = times.len();
+        times.extend(joins);
+        times.sort();
+        times.dedup();
+        if times.len() == len { break; }
+    }
+}
+
 impl<T: Lattice+Clone> Lattice for Antichain<T> {
     fn join(&self, other: &Self) -> Self {
         let mut upper = Antichain::new();
@@ -3,14 +3,18 @@
 use std::rc::{Rc, Weak};
 use std::cell::RefCell;
 use std::collections::VecDeque;
+use std::ops::Bound;
 
+use timely::PartialOrder;
 use timely::dataflow::Scope;
 use timely::dataflow::operators::generic::{OperatorInfo, source};
 use timely::progress::Timestamp;
 use timely::progress::{Antichain, frontier::AntichainRef};
 use timely::dataflow::operators::CapabilitySet;
 
-use crate::trace::{Trace, TraceReader, BatchReader};
+use crate::trace::{Trace, TraceReader, BatchReader, Cursor};
+use crate::trace::implementations::containers::BatchContainer;
+use crate::difference::{IsZero, Semigroup};
 use crate::trace::wrappers::rc::TraceBox;
 
 use timely::scheduling::Activator;
@@ -157,6 +161,121 @@ impl<Tr: TraceReader> TraceAgent<Tr> {
     pub fn trace_box_unstable(&self) -> Rc<RefCell<TraceBox<Tr>>> {
         Rc::clone(&self.trace)
     }
+
+    /// Reads the accumulated values associated with `key` at `time`, outside of any dataflow.
+    ///
+    /// The method returns `None` if the values cannot be determined, which is the case if `time` is not
+    /// greater or equal to the logical compaction frontier of this agent, or if `time` is not yet complete
+    /// in the trace. The agent is itself the read hold that keeps the trace from compacting past `time`,
+    /// and an agent used to serve lookups should advance its logical compaction frontier as the times of
+    /// interest advance, so that the trace can compact its history.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use timely::dataflow::operators::Probe;
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::arrange::ArrangeByKey;
+    ///
+    /// ::timely::execute_directly(|worker| {
+    ///
+    ///     let (mut input, mut trace, probe) = worker.dataflow::<u64,_,_>(|scope| {
+    ///         let (input, data) = scope.new_collection::<(u64, char), isize>();
+    ///         let arranged = data.arrange_by_key();
+    ///         (input, arranged.trace, arranged.stream.probe())
+    ///     });
+    ///
+    ///     input.insert((0, 'a'));
+    ///     input.insert((0, 'b'));
+    ///     input.insert((1, 'c'));
+    ///     input.advance_to(1);
+    ///     input.flush();
+    ///     while probe.less_than(input.time()) { worker.step(); }
+    ///
+    ///     assert_eq!(trace.lookup(&0, &0), Some(vec![('a', 1), ('b', 1)]));
+    ///     // Time `1` is not yet complete.
+    ///     assert_eq!(trace.lookup(&0, &1), None);
+    /// });
+    /// ```
+    pub fn lookup(&mut self, key: &Tr::KeyOwn, time: &Tr::Time) -> Option<Vec<(Tr::ValOwn, Tr::Diff)>> {
+        let updates = self.lookup_range(Bound::Included(key), Bound::Included(key), time)?;
+        Some(updates.into_iter().map(|((_key, val), diff)| (val, diff)).collect())
+    }
+
+    /// Reads the accumulated `(key, val)` pairs at `time` for keys within the supplied bounds.
+    ///
+    /// Unbounded bounds read the snapshot of the full collection at `time`. The results are ordered by key
+    /// and then by value, and the method returns `None` under the same conditions as `lookup`.
+    pub fn lookup_range(&mut self, lower: Bound<&Tr::KeyOwn>, upper: Bound<&Tr::KeyOwn>, time: &Tr::Time) -> Option<Vec<((Tr::KeyOwn, Tr::ValOwn), Tr::Diff)>> {
+
+        // Accumulations are only correct at times beyond our logical compaction frontier.
+        if !self.logical_compaction.less_equal(time) {
+            return None;
+        }
+
+        // Accumulations are only final at times not beyond the upper frontier of the trace.
+        let mut through = Antichain::from_elem(<Tr::Time as Timestamp>::minimum());
+        self.map_batches(|batch| through.clone_from(batch.upper()));
+        if through.less_equal(time) || !PartialOrder::less_equal(&self.physical_compaction.borrow(), &through.borrow()) {
+            return None;
+        }
+
+        let (mut cursor, storage) = self.cursor_through(through.borrow())?;
+
+        // Stash the bounds in a container, to compare them with the keys of the cursor.
+        let mut bounds = Tr::KeyContainer::with_capacity(2);
+        let mut stash = |bound: Bound<&Tr::KeyOwn>| match bound {
+            Bound::Included(key) => { bounds.push_own(key); Bound::Included(bounds.len() - 1) },
+            Bound::Excluded(key) => { bounds.push_own(key); Bound::Excluded(bounds.len() - 1) },
+            Bound::Unbounded => Bound::Unbounded,
+        };
+        let lower = stash(lower);
+        let upper = stash(upper);
+
+        if let Bound::Included(index) | Bound::Excluded(index) = lower {
+            cursor.seek_key(&storage, bounds.index(index));
+        }
+
+        let mut result = Vec::new();
+        let mut scratch = <Tr::Time as Timestamp>::minimum();
+        while let Some(key) = cursor.get_key(&storage) {
+            let before_upper = match upper {
+                Bound::Included(index) => key <= bounds.index(index),
+                Bound::Excluded(index) => key < bounds.index(index),
+                Bound::Unbounded => true,
+            };
+            if !before_upper {
+                break;
+            }
+            // The seek may land on an excluded lower bound, which we skip.
+            let after_lower = match lower {
+                Bound::Excluded(index) => key > bounds.index(index),
+                _ => true,
+            };
+            if after_lower {
+                while let Some(val) = cursor.get_val(&storage) {
+                    let mut sum: Option<Tr::Diff> = None;
+                    cursor.map_times(&storage, |t, d| {
+                        Tr::clone_time_onto(t, &mut scratch);
+                        if scratch.less_equal(time) {
+                            let d = Tr::owned_diff(d);
+                            match sum.as_mut() {
+                                Some(sum) => sum.plus_equals(&d),
+                                None => sum = Some(d),
+                            }
+                        }
+                    });
+                    if let Some(sum) = sum.filter(|sum| !sum.is_zero()) {
+                        result.push(((Tr::owned_key(key), Tr::owned_val(val)), sum));
+                    }
+                    cursor.step_val(&storage);
+                }
+            }
+            cursor.step_key(&storage);
+        }
+
+        Some(result)
+    }
 }
 
 impl<Tr: TraceReader+'static> TraceAgent<Tr> {
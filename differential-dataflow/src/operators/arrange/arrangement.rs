@@ -227,6 +227,55 @@ where
         })
         .as_collection()
     }
+
+    /// Retains distinctions between times for a window of history determined by `policy`.
+    ///
+    /// As the frontier of the arrangement advances, the logical compaction of the trace is held back to the
+    /// times `policy` produces from the elements of the frontier, for example the frontier less `N` time units.
+    /// Readers can then use `import_frontier_core` to query the arrangement as of any time greater or equal to
+    /// these times, without any one reader holding back compaction indefinitely. The hold is a clone of the trace
+    /// handle owned by an operator, and it is released once the stream of the arrangement completes.
+    ///
+    /// The policy should produce times less or equal to its argument. The hold cannot recover distinctions that
+    /// the trace has already compacted away, and so it should be established when the arrangement is formed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::arrange::ArrangeBySelf;
+    ///
+    /// ::timely::example(|scope| {
+    ///     // retain distinctions for the last ten rounds.
+    ///     scope.new_collection_from(0 .. 10u64).1
+    ///          .arrange_by_self()
+    ///          .retain_history(|time| time.saturating_sub(10));
+    /// });
+    /// ```
+    pub fn retain_history<F>(&self, mut policy: F)
+    where
+        Tr: 'static,
+        F: FnMut(&Tr::Time)->Tr::Time+'static,
+    {
+        let mut hold = Some(self.trace.clone());
+        let mut frontier = Antichain::from_elem(<Tr::Time as Timestamp>::minimum());
+        self.stream.sink(Pipeline, "RetainHistory", move |input| {
+            input.for_each(|_time, _data| { });
+            if frontier.borrow() != input.frontier().frontier() {
+                frontier.clear();
+                frontier.extend(input.frontier().frontier().iter().cloned());
+                if frontier.is_empty() {
+                    // Release the hold once the arrangement will change no further.
+                    hold = None;
+                }
+                else if let Some(hold) = hold.as_mut() {
+                    let retained = frontier.elements().iter().map(&mut policy).collect::<Antichain<_>>();
+                    hold.set_logical_compaction(retained.borrow());
+                    hold.set_physical_compaction(frontier.borrow());
+                }
+            }
+        });
+    }
 }
 
 
@@ -273,6 +322,30 @@ where
         )
             .as_collection()
     }
+
+    /// A direct implementation of the `OuterJoinCore::left_join_core` method.
+    pub fn left_join_core<T2,I,L>(&self, other: &Arranged<G,T2>, mut result: L) -> Collection<G,I::Item,T1::Diff>
+    where
+        T2: for<'a> TraceReader<Key<'a>=T1::Key<'a>, Time=T1::Time, Diff=T1::Diff>+Clone+'static,
+        T1::Diff: Abelian+Multiply<Output=T1::Diff>+From<i8>,
+        I: IntoIterator<Item: Data>,
+        L: FnMut(T1::Key<'_>,T1::Val<'_>,Option<T2::Val<'_>>)->I+'static,
+    {
+        use crate::operators::outer_join::outer_join_traces;
+        outer_join_traces(self, other, true, false, move |k, v1, v2| v1.map(|v1| result(k, v1, v2)).into_iter().flatten())
+    }
+
+    /// A direct implementation of the `OuterJoinCore::full_outer_join_core` method.
+    pub fn full_outer_join_core<T2,I,L>(&self, other: &Arranged<G,T2>, result: L) -> Collection<G,I::Item,T1::Diff>
+    where
+        T2: for<'a> TraceReader<Key<'a>=T1::Key<'a>, Time=T1::Time, Diff=T1::Diff>+Clone+'static,
+        T1::Diff: Abelian+Multiply<Output=T1::Diff>+From<i8>,
+        I: IntoIterator<Item: Data>,
+        L: FnMut(T1::Key<'_>,Option<T1::Val<'_>>,Option<T2::Val<'_>>)->I+'static,
+    {
+        use crate::operators::outer_join::outer_join_traces;
+        outer_join_traces(self, other, true, true, result)
+    }
 }
 
 // Direct reduce implementations.
@@ -394,6 +467,26 @@ where
 /// It uses the supplied parallelization contract to distribute the data, which does not need to
 /// be consistently by key (though this is the most common).
 pub fn arrange_core<G, P, Ba, Bu, Tr>(stream: &StreamCore<G, Ba::Input>, pact: P, name: &str) -> Arranged<G, TraceAgent<Tr>>
+where
+    G: Scope<Timestamp: Lattice>,
+    P: ParallelizationContract<G::Timestamp, Ba::Input>,
+    Ba: Batcher<Time=G::Timestamp,Input: Container + Clone + 'static> + 'static,
+    Bu: Builder<Time=G::Timestamp, Input=Ba::Output, Output = Tr::Batch>,
+    Tr: Trace<Time=G::Timestamp>+'static,
+{
+    arrange_core_from::<G, P, Ba, Bu, Tr>(stream, pact, name, Vec::new())
+}
+
+/// Arranges a stream of updates as `arrange_core`, starting from a sequence of existing batches.
+///
+/// The batches must be contiguous, starting from the minimal frontier, as those read back by
+/// `snapshot::read`. They are installed in the trace before any input is accepted, and are sent
+/// along the output stream at the minimal time, so that downstream operators observe them as
+/// they would have had the arrangement formed them itself.
+///
+/// All updates on `stream` must be at times in advance of the upper bound of the last batch.
+/// Input frontiers that do not yet reach this bound are ignored.
+pub fn arrange_core_from<G, P, Ba, Bu, Tr>(stream: &StreamCore<G, Ba::Input>, pact: P, name: &str, batches: Vec<Tr::Batch>) -> Arranged<G, TraceAgent<Tr>>
 where
     G: Scope<Timestamp: Lattice>,
     P: ParallelizationContract<G::Timestamp, Ba::Input>,
@@ -422,7 +515,7 @@ where
     let reader_ref = &mut reader;
     let scope = stream.scope();
 
-    let stream = stream.unary_frontier(pact, name, move |_capability, info| {
+    let stream = stream.unary_frontier(pact, name, move |capability, info| {
 
         // Acquire a logger for arrange events.
         let logger = scope.logger_for::<crate::logging::DifferentialEventBuilder>("differential/arrange").map(Into::into);
@@ -447,8 +540,23 @@ where
         // Initialize to the minimal input frontier.
         let mut prev_frontier = Antichain::from_elem(<G::Timestamp as Timestamp>::minimum());
 
+        // Install any existing batches, and advance the batcher and our frontier to their upper bound.
+        // The initial capability is retained only long enough to send the batches downstream.
+        let mut restored = if batches.is_empty() { None } else { Some((capability, batches)) };
+        if let Some((_, batches)) = &restored {
+            for batch in batches.iter() {
+                writer.insert(batch.clone(), Some(<G::Timestamp as Timestamp>::minimum()));
+            }
+            prev_frontier.clone_from(batches.last().unwrap().upper());
+            let _empty = batcher.seal::<Bu>(prev_frontier.clone());
+        }
+
         move |input, output| {
 
+            if let Some((capability, batches)) = restored.take() {
+                output.session(&capability).give_iterator(batches.into_iter());
+            }
+
             // As we receive data, we need to (i) stash the data and (ii) keep *enough* capabilities.
             // We don't have to keep all capabilities, but we need to be able to form output messages
             // when we realize that time intervals are complete.
@@ -458,6 +566,12 @@ where
                 batcher.push_container(data);
             });
 
+            // Frontiers that have not yet caught up to restored batches have no new updates to seal.
+            if !PartialOrder::less_equal(&prev_frontier.borrow(), &input.frontier().frontier()) {
+                writer.exert();
+                return;
+            }
+
             // The frontier may have advanced by multiple elements, which is an issue because
             // timely dataflow currently only allows one capability per message. This means we
             // must pretend to process the frontier advances one element at a time, batching
@@ -0,0 +1,123 @@
+//! Bulk loading of arrangements from sorted updates.
+//!
+//! Arrangements ordinarily form batches with a `Batcher`, which sorts and consolidates arbitrarily
+//! ordered updates before handing them to a `Builder`. When updates are already sorted, as when they
+//! are read back from a sorted file or produced by another ordered index, this work is unnecessary.
+//! The `batch_sorted` function instead hands sorted updates directly to a `Builder`, and produces a
+//! batch that can be inserted into a `Spine` or used to seed an arrangement with `arrange_core_from`.
+//! The `arrange_by_key_sorted` and `arrange_by_self_sorted` functions do the latter for the default
+//! trace implementations.
+//!
+//! As with other arrangements, each worker should load only the keys it would be assigned by the
+//! arrangement's exchange, which for `arrange_by_key` and `arrange_by_self` is determined by the hash
+//! of the key.
+
+use timely::dataflow::Scope;
+use timely::progress::{Antichain, Timestamp};
+
+use crate::{Collection, ExchangeData, Hashable};
+use crate::difference::Semigroup;
+use crate::lattice::Lattice;
+use crate::trace::{Builder, Description, TraceReader};
+use crate::trace::implementations::{KeyBuilder, KeySpine, ValBuilder, ValSpine};
+
+use super::{Arranged, TraceAgent};
+use super::snapshot::{arrange_by_key_from, arrange_by_self_from};
+
+/// The number of bytes of updates in each chunk handed to the builder.
+const BUFFER_SIZE_BYTES: usize = 8 << 10;
+
+/// Builds a batch from updates sorted by `(key, val, time)`, spanning times from the minimum up to `upper`.
+///
+/// Updates with equal `(key, val, time)` are consolidated, and updates that accumulate to zero are
+/// dropped, but updates are otherwise neither sorted nor moved.
+///
+/// # Panics
+///
+/// Panics if the updates are not sorted, or if any time is greater or equal to an element of `upper`.
+///
+/// # Examples
+///
+/// ```
+/// use timely::progress::Antichain;
+/// use differential_dataflow::operators::arrange::bulk::batch_sorted;
+/// use differential_dataflow::trace::BatchReader;
+/// use differential_dataflow::trace::implementations::ValBuilder;
+///
+/// let updates = (0 .. 10u64).map(|key| ((key, key * key), 0u64, 1isize));
+/// let batch = batch_sorted::<ValBuilder<_,_,_,_>, _, _, _, _, _>(updates, Antichain::from_elem(1));
+/// assert_eq!(batch.len(), 10);
+/// ```
+pub fn batch_sorted<Bu, K, V, T, R, I>(updates: I, upper: Antichain<T>) -> Bu::Output
+where
+    Bu: Builder<Input = Vec<((K, V), T, R)>, Time = T>,
+    K: Ord,
+    V: Ord,
+    T: Timestamp,
+    R: Semigroup,
+    I: IntoIterator<Item = ((K, V), T, R)>,
+{
+    let size = std::mem::size_of::<((K, V), T, R)>();
+    let capacity = if size == 0 { BUFFER_SIZE_BYTES } else { std::cmp::max(BUFFER_SIZE_BYTES / size, 1) };
+
+    let mut chain = Vec::new();
+    let mut chunk = Vec::with_capacity(capacity);
+    let mut pending: Option<((K, V), T, R)> = None;
+    for (data, time, diff) in updates {
+        assert!(!upper.less_equal(&time), "update time not less than batch upper");
+        if let Some((p_data, p_time, p_diff)) = &mut pending {
+            match (&*p_data, &*p_time).cmp(&(&data, &time)) {
+                std::cmp::Ordering::Less => { },
+                std::cmp::Ordering::Equal => { p_diff.plus_equals(&diff); continue; },
+                std::cmp::Ordering::Greater => panic!("updates not sorted by (key, val, time)"),
+            }
+        }
+        if let Some(update) = pending.replace((data, time, diff)) {
+            if !update.2.is_zero() {
+                chunk.push(update);
+                if chunk.len() == capacity {
+                    chain.push(std::mem::replace(&mut chunk, Vec::with_capacity(capacity)));
+                }
+            }
+        }
+    }
+    if let Some(update) = pending {
+        if !update.2.is_zero() { chunk.push(update); }
+    }
+    if !chunk.is_empty() { chain.push(chunk); }
+
+    let description = Description::new(Antichain::from_elem(T::minimum()), upper, Antichain::from_elem(T::minimum()));
+    Bu::seal(&mut chain, description)
+}
+
+/// Arranges a collection of `(Key, Val)` records by `Key`, starting from updates sorted by `(key, val, time)`.
+///
+/// This is `arrange_by_key_from` applied to the batch `batch_sorted` forms from `updates` and `upper`.
+/// The collection should contain only updates at times in advance of `upper`.
+pub fn arrange_by_key_sorted<G, K, V, R, I>(collection: &Collection<G, (K, V), R>, name: &str, updates: I, upper: Antichain<G::Timestamp>) -> Arranged<G, TraceAgent<ValSpine<K, V, G::Timestamp, R>>>
+where
+    G: Scope<Timestamp: Lattice+Ord>,
+    K: ExchangeData+Hashable,
+    V: ExchangeData,
+    R: ExchangeData+Semigroup,
+    I: IntoIterator<Item = ((K, V), G::Timestamp, R)>,
+{
+    let batch: <ValSpine<K, V, G::Timestamp, R> as TraceReader>::Batch = batch_sorted::<ValBuilder<_,_,_,_>, _, _, _, _, _>(updates, upper);
+    arrange_by_key_from(collection, name, vec![batch])
+}
+
+/// Arranges a collection of `Key` records by `Key`, starting from updates sorted by `(key, time)`.
+///
+/// This is `arrange_by_self_from` applied to the batch `batch_sorted` forms from `updates` and `upper`.
+/// The collection should contain only updates at times in advance of `upper`.
+pub fn arrange_by_self_sorted<G, K, R, I>(collection: &Collection<G, K, R>, name: &str, updates: I, upper: Antichain<G::Timestamp>) -> Arranged<G, TraceAgent<KeySpine<K, G::Timestamp, R>>>
+where
+    G: Scope<Timestamp: Lattice+Ord>,
+    K: ExchangeData+Hashable,
+    R: ExchangeData+Semigroup,
+    I: IntoIterator<Item = (K, G::Timestamp, R)>,
+{
+    let updates = updates.into_iter().map(|(key, time, diff)| ((key, ()), time, diff));
+    let batch: <KeySpine<K, G::Timestamp, R> as TraceReader>::Batch = batch_sorted::<KeyBuilder<_,_,_>, _, _, _, _, _>(updates, upper);
+    arrange_by_self_from(collection, name, vec![batch])
+}
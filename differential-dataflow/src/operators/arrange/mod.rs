@@ -65,8 +65,12 @@ pub mod agent;
 pub mod arrangement;
 
 pub mod upsert;
+pub mod snapshot;
+pub mod partition;
+pub mod bulk;
 
 pub use self::writer::TraceWriter;
 pub use self::agent::{TraceAgent, ShutdownButton};
 
-pub use self::arrangement::{Arranged, Arrange, ArrangeByKey, ArrangeBySelf};
\ No newline at end of file
+pub use self::arrangement::{Arranged, Arrange, ArrangeByKey, ArrangeBySelf};
+pub use self::partition::{RangePartition, ArrangeByKeyRange, ArrangeBySelfRange};
\ No newline at end of file
@@ -0,0 +1,201 @@
+//! Range partitioning of arrangements by key.
+//!
+//! The `arrange_by_key` and `arrange_by_self` methods distribute records among workers by the hash of
+//! their keys. The methods here instead distribute records by ranges of keys, determined by a sorted
+//! list of split points: the first worker receives keys less than the first split point, the second
+//! worker receives keys from the first split point up to but not including the second, and so on.
+//! With one fewer split point than workers, the contents of the arrangements are sorted by key across
+//! workers, and an ordered scan over a range of keys only needs to visit the workers whose ranges
+//! overlap it. With more split points, the parts are assigned to workers round-robin.
+//!
+//! The split points are fixed when the arrangement is formed, as records already arranged would not
+//! move should they change. They can be supplied directly, or determined from a sample of keys.
+//! Each worker routes the records it holds using its own copy of the split points, and all workers
+//! must use identical split points, or records with the same key may be sent to different workers.
+//! Split points determined by each worker from its own sample will generally differ; the method
+//! `RangePartition::from_worker_samples` exchanges the samples of all workers so that each determines
+//! the same split points.
+//!
+//! Operators on `Arranged` that combine two arrangements, such as `join_core`, rely on matching records
+//! being on the same worker, and so should only combine arrangements partitioned the same way. Methods
+//! that arrange their other input themselves, such as `join_map` on an `Arranged`, partition it by hash
+//! and should not be used with range partitioned arrangements.
+
+use std::cell::RefCell;
+use std::ops::{Bound, Range};
+use std::rc::Rc;
+
+use timely::communication::Allocate;
+use timely::worker::Worker;
+use timely::dataflow::{Scope, ProbeHandle};
+use timely::dataflow::channels::pact::Exchange;
+use timely::dataflow::operators::{Broadcast, Inspect, Probe, ToStream};
+
+use crate::{Collection, ExchangeData};
+use crate::difference::Semigroup;
+use crate::lattice::Lattice;
+use crate::trace::implementations::{KeyBatcher, KeyBuilder, KeySpine, ValBatcher, ValBuilder, ValSpine};
+
+use super::{Arranged, TraceAgent};
+use super::arrangement::arrange_core;
+
+/// Split points that assign keys to parts by ranges.
+#[derive(Clone, Debug)]
+pub struct RangePartition<K> {
+    splits: Vec<K>,
+}
+
+impl<K: Ord> RangePartition<K> {
+    /// Creates a partition from split points, which are sorted and deduplicated.
+    ///
+    /// Part `i` contains the keys greater or equal to the `i-1`th split point and less than the `i`th.
+    pub fn new(mut splits: Vec<K>) -> Self {
+        splits.sort();
+        splits.dedup();
+        RangePartition { splits }
+    }
+
+    /// Creates a partition into `parts` parts with split points at the quantiles of a sample of keys.
+    ///
+    /// The resulting partition may have fewer parts if the sample has few distinct keys. All workers
+    /// must use identical split points, and so must supply identical samples; if each worker samples
+    /// its own data, use `from_worker_samples` instead.
+    pub fn from_sample(mut sample: Vec<K>, parts: usize) -> Self where K: Clone {
+        sample.sort();
+        let len = sample.len();
+        let splits = if len == 0 { Vec::new() } else { (1 .. parts).map(|part| sample[part * len / parts].clone()).collect() };
+        Self::new(splits)
+    }
+
+    /// Creates a partition into `parts` parts with split points at the quantiles of the samples of all workers.
+    ///
+    /// Each worker supplies a sample of keys, for example of the data it will introduce, and the samples
+    /// are broadcast to all workers, each of which determines the same split points from their union.
+    /// All workers must call this method at the same point in their construction of dataflows, as it
+    /// builds and runs a dataflow to exchange the samples, and returns once the exchange is complete.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::operators::arrange::partition::RangePartition;
+    ///
+    /// ::timely::execute(::timely::Config::process(2), |worker| {
+    ///
+    ///     // Each worker samples different keys, and all determine the same split points.
+    ///     let sample = (0 .. 50).map(|x| x + 50 * worker.index() as u64).collect();
+    ///     let partition = RangePartition::from_worker_samples(worker, sample, 2);
+    ///     assert_eq!(partition.splits(), &[50]);
+    /// }).unwrap();
+    /// ```
+    pub fn from_worker_samples<A: Allocate>(worker: &mut Worker<A>, sample: Vec<K>, parts: usize) -> Self where K: ExchangeData {
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let mut probe = ProbeHandle::new();
+        worker.dataflow::<u64,_,_>(|scope| {
+            let received = received.clone();
+            sample.to_stream(scope)
+                  .broadcast()
+                  .inspect(move |key| received.borrow_mut().push(key.clone()))
+                  .probe_with(&mut probe);
+        });
+        worker.step_while(|| !probe.done());
+        Self::from_sample(received.take(), parts)
+    }
+
+    /// The sorted and deduplicated split points.
+    pub fn splits(&self) -> &[K] {
+        &self.splits
+    }
+
+    /// The number of parts, one more than the number of split points.
+    pub fn parts(&self) -> usize {
+        self.splits.len() + 1
+    }
+
+    /// The part containing `key`.
+    pub fn part(&self, key: &K) -> usize {
+        self.splits.partition_point(|split| split <= key)
+    }
+
+    /// The parts that may contain keys within the supplied bounds.
+    ///
+    /// This is the range of workers an ordered scan over the bounds needs to visit, when there is one part per worker.
+    pub fn parts_overlapping(&self, lower: Bound<&K>, upper: Bound<&K>) -> Range<usize> {
+        let first = match lower {
+            Bound::Included(key) | Bound::Excluded(key) => self.part(key),
+            Bound::Unbounded => 0,
+        };
+        let last = match upper {
+            Bound::Included(key) | Bound::Excluded(key) => self.part(key),
+            Bound::Unbounded => self.splits.len(),
+        };
+        first .. std::cmp::max(first, last) + 1
+    }
+}
+
+/// Arranges `(Key, Val)` records by ranges of `Key`.
+pub trait ArrangeByKeyRange<G, K: ExchangeData, V: ExchangeData, R: ExchangeData+Semigroup>
+where
+    G: Scope<Timestamp: Lattice+Ord>,
+{
+    /// Arranges a collection of `(Key, Val)` records by `Key`, assigning the records of part `i` of
+    /// `partition` to worker `i` (modulo the number of workers).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::arrange::partition::{ArrangeByKeyRange, RangePartition};
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     let partition = RangePartition::new(vec![50]);
+    ///
+    ///     scope.new_collection_from((0 .. 100).map(|x| (x, x))).1
+    ///          .arrange_by_key_range(&partition)
+    ///          .as_collection(|k, v| (*k, *v));
+    /// });
+    /// ```
+    fn arrange_by_key_range(&self, partition: &RangePartition<K>) -> Arranged<G, TraceAgent<ValSpine<K, V, G::Timestamp, R>>> {
+        self.arrange_by_key_range_named(partition, "ArrangeByKeyRange")
+    }
+
+    /// As `arrange_by_key_range` but with the ability to name the arrangement.
+    fn arrange_by_key_range_named(&self, partition: &RangePartition<K>, name: &str) -> Arranged<G, TraceAgent<ValSpine<K, V, G::Timestamp, R>>>;
+}
+
+impl<G, K: ExchangeData, V: ExchangeData, R: ExchangeData+Semigroup> ArrangeByKeyRange<G, K, V, R> for Collection<G, (K, V), R>
+where
+    G: Scope<Timestamp: Lattice+Ord>,
+{
+    fn arrange_by_key_range_named(&self, partition: &RangePartition<K>, name: &str) -> Arranged<G, TraceAgent<ValSpine<K, V, G::Timestamp, R>>> {
+        let partition = partition.clone();
+        let exchange = Exchange::new(move |update: &((K,V),G::Timestamp,R)| partition.part(&(update.0).0) as u64);
+        arrange_core::<_,_,ValBatcher<_,_,_,_>,ValBuilder<_,_,_,_>,_>(&self.inner, exchange, name)
+    }
+}
+
+/// Arranges `Key` records by ranges of `Key`.
+pub trait ArrangeBySelfRange<G, K: ExchangeData, R: ExchangeData+Semigroup>
+where
+    G: Scope<Timestamp: Lattice+Ord>,
+{
+    /// Arranges a collection of `Key` records by `Key`, assigning the records of part `i` of
+    /// `partition` to worker `i` (modulo the number of workers).
+    fn arrange_by_self_range(&self, partition: &RangePartition<K>) -> Arranged<G, TraceAgent<KeySpine<K, G::Timestamp, R>>> {
+        self.arrange_by_self_range_named(partition, "ArrangeBySelfRange")
+    }
+
+    /// As `arrange_by_self_range` but with the ability to name the arrangement.
+    fn arrange_by_self_range_named(&self, partition: &RangePartition<K>, name: &str) -> Arranged<G, TraceAgent<KeySpine<K, G::Timestamp, R>>>;
+}
+
+impl<G, K: ExchangeData, R: ExchangeData+Semigroup> ArrangeBySelfRange<G, K, R> for Collection<G, K, R>
+where
+    G: Scope<Timestamp: Lattice+Ord>,
+{
+    fn arrange_by_self_range_named(&self, partition: &RangePartition<K>, name: &str) -> Arranged<G, TraceAgent<KeySpine<K, G::Timestamp, R>>> {
+        let partition = partition.clone();
+        let exchange = Exchange::new(move |update: &((K,()),G::Timestamp,R)| partition.part(&(update.0).0) as u64);
+        arrange_core::<_,_,KeyBatcher<_,_,_>,KeyBuilder<_,_,_>,_>(&self.map(|k| (k, ())).inner, exchange, name)
+    }
+}
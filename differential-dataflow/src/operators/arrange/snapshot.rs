@@ -0,0 +1,182 @@
+//! Durable snapshots of arrangements.
+//!
+//! A snapshot is a directory containing the batches of a trace, each serialized with `bincode`
+//! to its own file, and a manifest listing their descriptions. Each write uses batch files of a new
+//! generation, so that the files of the prior snapshot are left intact until the new manifest has
+//! replaced the prior one, and are only then removed. Snapshots are written with `write`,
+//! which captures all batches up to the trace's current upper frontier, and read back with `read`.
+//! The recovered batches can seed a new arrangement with `arrange_core_from`, or with the more
+//! specific `arrange_by_key_from` and `arrange_by_self_from`, which then continue to arrange newer
+//! updates as normal.
+//!
+//! Traces are worker-local, and each worker should write to and read from its own directory. As
+//! arrangements are partitioned by key, a snapshot should be restored with the same number of
+//! workers, and the same worker index, as it was written with.
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use timely::dataflow::Scope;
+use timely::dataflow::channels::pact::Exchange;
+use timely::progress::{Antichain, Timestamp};
+
+use crate::{Collection, ExchangeData, Hashable};
+use crate::difference::Semigroup;
+use crate::lattice::Lattice;
+use crate::trace::{BatchReader, Description, TraceReader};
+use crate::trace::implementations::{KeyBatcher, KeyBuilder, KeySpine, ValBatcher, ValBuilder, ValSpine};
+
+use super::{Arranged, TraceAgent};
+use super::arrangement::arrange_core_from;
+
+/// The name of the file listing a snapshot's batches.
+const MANIFEST: &str = "manifest.bin";
+
+/// Lists the batches of a snapshot, in order.
+#[derive(Serialize, Deserialize)]
+struct Manifest<T> {
+    /// The generation of the snapshot, distinct from that of any snapshot it replaced.
+    generation: u64,
+    /// The description of each batch, whose contents are in `batch-{generation}-{index}.bin`.
+    descriptions: Vec<Description<T>>,
+}
+
+/// The name of the file containing a batch of a snapshot.
+fn batch_file(generation: u64, index: usize) -> String {
+    format!("batch-{}-{}.bin", generation, index)
+}
+
+/// The generation of a batch file named by `batch_file`, if `name` is one.
+fn batch_generation(name: &str) -> Option<u64> {
+    let name = name.strip_prefix("batch-")?.strip_suffix(".bin")?;
+    let (generation, index) = name.split_once('-')?;
+    index.parse::<usize>().ok()?;
+    generation.parse().ok()
+}
+
+/// Writes the batches of `trace` to `directory`, returning the frontier the snapshot reaches.
+///
+/// The snapshot contains all updates at times not greater or equal to the returned frontier,
+/// which is the upper frontier of the trace when called. The directory is created if needed,
+/// and any prior snapshot in it is replaced. The batches are written to files not used by the
+/// prior snapshot, and the manifest is written last and renamed into place, so that an interrupted
+/// call leaves the prior snapshot readable. The batch files of prior snapshots are then removed.
+pub fn write<Tr>(trace: &Tr, directory: &Path) -> std::io::Result<Antichain<Tr::Time>>
+where
+    Tr: TraceReader<Batch: Serialize, Time: Serialize>,
+{
+    std::fs::create_dir_all(directory)?;
+
+    // A generation after that of any batch file present, including those of interrupted writes.
+    let mut generation = 0;
+    for entry in std::fs::read_dir(directory)? {
+        if let Some(prior) = entry?.file_name().to_str().and_then(batch_generation) {
+            generation = std::cmp::max(generation, prior + 1);
+        }
+    }
+
+    let mut batches = Vec::new();
+    trace.map_batches(|batch| batches.push(batch.clone()));
+
+    let mut descriptions = Vec::with_capacity(batches.len());
+    for (index, batch) in batches.iter().enumerate() {
+        write_file(&directory.join(batch_file(generation, index)), batch)?;
+        descriptions.push(batch.description().clone());
+    }
+
+    let upper = descriptions.last().map(|d| d.upper().clone()).unwrap_or_else(|| Antichain::from_elem(Tr::Time::minimum()));
+    let staged = directory.join(format!("{}.tmp", MANIFEST));
+    write_file(&staged, &Manifest { generation, descriptions })?;
+    std::fs::rename(&staged, directory.join(MANIFEST))?;
+    sync_directory(directory)?;
+
+    // The new manifest is in place, and the batch files of other generations are no longer referenced.
+    for entry in std::fs::read_dir(directory)? {
+        let entry = entry?;
+        if entry.file_name().to_str().and_then(batch_generation).is_some_and(|prior| prior != generation) {
+            std::fs::remove_file(entry.path())?;
+        }
+    }
+
+    Ok(upper)
+}
+
+/// Reads the batches of a snapshot written by `write` from `directory`.
+///
+/// The batches are returned in order, and are checked to be contiguous from the minimal frontier
+/// and to match the descriptions recorded when written.
+pub fn read<B>(directory: &Path) -> std::io::Result<Vec<B>>
+where
+    B: BatchReader<Time: DeserializeOwned> + DeserializeOwned,
+{
+    let manifest: Manifest<B::Time> = read_file(&directory.join(MANIFEST))?;
+
+    let mut upper = Antichain::from_elem(B::Time::minimum());
+    let mut batches = Vec::with_capacity(manifest.descriptions.len());
+    for (index, description) in manifest.descriptions.into_iter().enumerate() {
+        let batch: B = read_file(&directory.join(batch_file(manifest.generation, index)))?;
+        if batch.description() != &description || batch.lower() != &upper {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("batch {} does not match snapshot manifest", index)));
+        }
+        upper.clone_from(batch.upper());
+        batches.push(batch);
+    }
+
+    Ok(batches)
+}
+
+fn write_file<S: Serialize>(path: &Path, item: &S) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    bincode::serialize_into(&mut writer, item).map_err(std::io::Error::other)?;
+    writer.flush()?;
+    writer.get_ref().sync_all()
+}
+
+/// Makes durable the renaming of files in `directory`.
+#[cfg(unix)]
+fn sync_directory(directory: &Path) -> std::io::Result<()> {
+    File::open(directory)?.sync_all()
+}
+
+/// Directories cannot be opened as files on all platforms, in which case renames are not synced.
+#[cfg(not(unix))]
+fn sync_directory(_directory: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+fn read_file<D: DeserializeOwned>(path: &Path) -> std::io::Result<D> {
+    let reader = BufReader::new(File::open(path)?);
+    bincode::deserialize_from(reader).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+/// Arranges a collection of `(Key, Val)` records by `Key`, starting from the batches of a snapshot.
+///
+/// This is `arrange_by_key_named`, applied to a trace that starts with `batches`. The collection
+/// should contain only updates at times in advance of the snapshot's upper frontier.
+pub fn arrange_by_key_from<G, K, V, R>(collection: &Collection<G, (K, V), R>, name: &str, batches: Vec<<ValSpine<K, V, G::Timestamp, R> as TraceReader>::Batch>) -> Arranged<G, TraceAgent<ValSpine<K, V, G::Timestamp, R>>>
+where
+    G: Scope<Timestamp: Lattice+Ord>,
+    K: ExchangeData+Hashable,
+    V: ExchangeData,
+    R: ExchangeData+Semigroup,
+{
+    let exchange = Exchange::new(move |update: &((K,V),G::Timestamp,R)| (update.0).0.hashed().into());
+    arrange_core_from::<_, _, ValBatcher<_,_,_,_>, ValBuilder<_,_,_,_>, _>(&collection.inner, exchange, name, batches)
+}
+
+/// Arranges a collection of `Key` records by `Key`, starting from the batches of a snapshot.
+///
+/// This is `arrange_by_self_named`, applied to a trace that starts with `batches`. The collection
+/// should contain only updates at times in advance of the snapshot's upper frontier.
+pub fn arrange_by_self_from<G, K, R>(collection: &Collection<G, K, R>, name: &str, batches: Vec<<KeySpine<K, G::Timestamp, R> as TraceReader>::Batch>) -> Arranged<G, TraceAgent<KeySpine<K, G::Timestamp, R>>>
+where
+    G: Scope<Timestamp: Lattice+Ord>,
+    K: ExchangeData+Hashable,
+    R: ExchangeData+Semigroup,
+{
+    let exchange = Exchange::new(move |update: &((K,()),G::Timestamp,R)| (update.0).0.hashed().into());
+    arrange_core_from::<_, _, KeyBatcher<_,_,_>, KeyBuilder<_,_,_>, _>(&collection.map(|k| (k, ())).inner, exchange, name, batches)
+}
@@ -13,11 +13,19 @@
 //!
 //! # Notes
 //!
-//! Upserts currently only work with totally ordered timestamps.
+//! The `arrange_from_upsert` operator only works with totally ordered timestamps. The
+//! `arrange_from_upsert_lattice` operator works with partially ordered timestamps, at
+//! the cost of retaining for each key the upserts not yet dominated by a later upsert,
+//! whose number is bounded by the size of an antichain of times, in an additional arrangement.
 //!
 //! In the case of ties in timestamps (concurrent updates to the same key) they choose
 //! the *greatest* value according to `Option<Val>` ordering, which will prefer a value
 //! to `None` and choose the greatest value (informally, as if applied in order of value).
+//! For partially ordered timestamps, the value at a time is determined by the upsert that
+//! is greatest according to the `Ord` implementation of the timestamp among those upserts
+//! whose times are less or equal to the time. The `Ord` implementation extends the partial
+//! order, and so this resolves incomparable concurrent updates deterministically, and for
+//! totally ordered timestamps it coincides with the behavior of `arrange_from_upsert`.
 //!
 //! If the same value is repeated, no change will occur in the output. That may make this
 //! operator effective at determining the difference between collections of keyed values,
@@ -108,10 +116,15 @@ use timely::progress::Timestamp;
 use timely::progress::Antichain;
 use timely::dataflow::operators::Capability;
 
+use timely::Container;
+use timely::container::PushInto;
+
 use crate::operators::arrange::arrangement::Arranged;
+use crate::operators::arrange::ArrangeByKey;
 use crate::trace::{Builder, Description};
 use crate::trace::{self, Trace, TraceReader, Cursor};
-use crate::{ExchangeData, Hashable};
+use crate::{AsCollection, ExchangeData, Hashable};
+use crate::lattice::{Lattice, close_under_join};
 
 use crate::trace::implementations::containers::BatchContainer;
 
@@ -124,9 +137,8 @@ use super::TraceAgent;
 /// value in sequence either replaces or removes the existing value, should it
 /// exist.
 ///
-/// This method is only implemented for totally ordered times, as we do not yet
-/// understand what a "sequence" of upserts would mean for partially ordered
-/// timestamps.
+/// This method is only implemented for totally ordered times, for which upserts
+/// form a sequence. For partially ordered times, use `arrange_from_upsert_lattice`.
 pub fn arrange_from_upsert<G, Bu, Tr>(
     stream: &Stream<G, (Tr::KeyOwn, Option<Tr::ValOwn>, G::Timestamp)>,
     name: &str,
@@ -325,3 +337,160 @@ where
     Arranged { stream, trace: reader.unwrap() }
 
 }
+
+/// Arrange data from a stream of keyed upserts at partially ordered times.
+///
+/// The input should be a stream of timestamped pairs of Key and Option<Val>, as for
+/// `arrange_from_upsert`. The value associated with a key at a time is that of the
+/// upsert greatest in the `Ord` order of `(time, Option<Val>)` among those upserts to
+/// the key whose times are less or equal to the time, and the key is absent if this
+/// value is `None`.
+///
+/// An upsert is dominated at the times greater or equal to the time of a later upsert
+/// to the same key, and is never the greatest at those times. Once the input frontier
+/// passes the time of an upsert, it is retracted from the times it is dominated, and
+/// it is folded away entirely once the input frontier passes a dominating time. The
+/// remaining upserts to each key, the maximal writes as of the input frontier, are
+/// arranged by key and determine the value with a `reduce`.
+pub fn arrange_from_upsert_lattice<G, K, V, Bu, Tr>(
+    stream: &Stream<G, (K, Option<V>, G::Timestamp)>,
+    name: &str,
+) -> Arranged<G, TraceAgent<Tr>>
+where
+    G: Scope<Timestamp: Lattice+ExchangeData>,
+    K: ExchangeData+Hashable,
+    V: ExchangeData,
+    Tr: for<'a> Trace<
+        Key<'a>=&'a K,
+        KeyOwn=K,
+        ValOwn=V,
+        Time=G::Timestamp,
+        Diff=isize,
+    >+'static,
+    Bu: Builder<Time=G::Timestamp, Output = Tr::Batch, Input: Container + PushInto<((K, V), Tr::Time, Tr::Diff)>>,
+{
+    let exchange = Exchange::new(move |update: &(K,Option<V>,G::Timestamp)| (update.0).hashed().into());
+
+    let maximal = stream.unary_frontier(exchange, &format!("Upserts: {}", name), move |_capability, _info| {
+
+        // Tracks the lower envelope of times in `pending`.
+        let mut capabilities = Antichain::<Capability<G::Timestamp>>::new();
+        // Upserts whose times the input frontier has not yet passed.
+        let mut pending = Vec::new();
+        // For each key, the upserts that are not dominated as of the input frontier, with the
+        // updates to their presence in the output, which accumulate to one or zero at each time.
+        let mut state = BTreeMap::<K, Vec<((G::Timestamp, Option<V>), Vec<(G::Timestamp, isize)>)>>::new();
+        // Tracks the input frontier.
+        let mut frontier = Antichain::from_elem(<G::Timestamp as Timestamp>::minimum());
+
+        move |input, output| {
+
+            input.for_each(|cap, data| {
+                capabilities.insert(cap.retain());
+                pending.extend(data.drain(..));
+            });
+
+            if frontier.borrow() != input.frontier().frontier() {
+
+                frontier.clear();
+                frontier.extend(input.frontier().frontier().iter().cloned());
+
+                // Extract upserts whose times the input frontier has passed.
+                let mut to_process = BTreeMap::new();
+                let mut remaining = Vec::new();
+                for (key, val, time) in pending.drain(..) {
+                    if frontier.less_equal(&time) { remaining.push((key, val, time)); }
+                    else { to_process.entry(key).or_insert_with(Vec::new).push((time, std::cmp::Reverse(val))); }
+                }
+                pending = remaining;
+
+                // Updates to the presence of upserts, to be sent with the first capability less or equal to their time.
+                let mut updates = capabilities.elements().iter().map(|_| Vec::new()).collect::<Vec<_>>();
+                for (key, mut list) in to_process {
+
+                    // Sort the list of upserts to `key` by their time, suppress multiple updates.
+                    list.sort();
+                    list.dedup_by(|(t1,_), (t2,_)| t1 == t2);
+                    let times = list.iter().map(|(time, _)| time.clone()).collect::<Vec<_>>();
+
+                    // Prior upserts cannot be dominated by each other, nor dominate new upserts, whose times were
+                    // not yet passed by the input frontier when the prior upserts were processed.
+                    let upserts = state.entry(key.clone()).or_default();
+                    let prior = upserts.len();
+                    upserts.extend(list.into_iter().map(|(time, std::cmp::Reverse(val))| ((time.clone(), val), vec![(time, 1)])));
+                    for (index, ((time, val), history)) in upserts.iter_mut().enumerate() {
+                        let dominators = times.iter().filter(|t| time.less_than(t)).cloned().collect::<Vec<_>>();
+                        // New upserts are present from their own time onward, which must also be sent.
+                        let offset = if index < prior { history.len() } else { 0 };
+                        dominate(history, &dominators);
+                        for (t, diff) in history[offset..].iter() {
+                            let position = capabilities.elements().iter().position(|c| c.time().less_equal(t)).expect("failed to find capability");
+                            updates[position].push(((key.clone(), (time.clone(), val.clone())), t.clone(), *diff));
+                        }
+                    }
+
+                    // Fold away the presence of upserts that are dominated at all times the input frontier may yet reach.
+                    for (_, history) in upserts.iter_mut() {
+                        for (time, _) in history.iter_mut() { time.advance_by(frontier.borrow()); }
+                        crate::consolidation::consolidate(history);
+                    }
+                    upserts.retain(|(_, history)| !history.is_empty());
+                    if upserts.is_empty() { state.remove(&key); }
+                }
+
+                for (capability, updates) in capabilities.elements().iter().zip(updates) {
+                    if !updates.is_empty() {
+                        output.session(capability).give_iterator(updates.into_iter());
+                    }
+                }
+
+                // Downgrade capabilities to the lower envelope of the times of pending upserts.
+                let mut new_capabilities = Antichain::new();
+                for (_, _, time) in pending.iter() {
+                    if !new_capabilities.elements().iter().any(|c: &Capability<G::Timestamp>| c.time().less_equal(time)) {
+                        if let Some(capability) = capabilities.elements().iter().find(|c| c.time().less_equal(time)) {
+                            new_capabilities.insert(capability.delayed(time));
+                        }
+                        else {
+                            panic!("failed to find capability");
+                        }
+                    }
+                }
+                capabilities = new_capabilities;
+            }
+        }
+    });
+
+    maximal
+        .as_collection()
+        .arrange_by_key_named(&format!("Arrange: {}", name))
+        .reduce_abelian::<_,K,V,Bu,Tr>(name, |_key, input, output| {
+            // Upserts are sorted by `(time, val)`, and the last is the one in effect.
+            let ((_time, val), _count) = input.last().expect("Reduce inputs are non-empty");
+            if let Some(val) = val {
+                output.push((val.clone(), 1));
+            }
+        })
+}
+
+/// Appends updates to `history` so that its accumulation is zero at times greater or equal to any of `dominators`,
+/// and is otherwise unchanged.
+///
+/// The accumulations can only change at joins of the times involved, which are visited in an order consistent
+/// with the partial order, each receiving the update needed to correct the accumulation at that time.
+fn dominate<T: Lattice+Ord+Clone>(history: &mut Vec<(T, isize)>, dominators: &[T]) {
+    if dominators.is_empty() { return; }
+    let offset = history.len();
+    let mut times = history.iter().map(|(time, _)| time.clone()).chain(dominators.iter().cloned()).collect::<Vec<_>>();
+    close_under_join(&mut times);
+
+    for time in times {
+        let accum = |updates: &[(T, isize)]| updates.iter().filter(|(t, _)| t.less_equal(&time)).map(|(_, diff)| diff).sum::<isize>();
+        let present = accum(&history[.. offset]);
+        let target = if dominators.iter().any(|d| d.less_equal(&time)) { 0 } else { present };
+        let update = target - present - accum(&history[offset ..]);
+        if update != 0 {
+            history.push((time, update));
+        }
+    }
+}
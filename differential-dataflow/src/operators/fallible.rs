@@ -0,0 +1,139 @@
+//! Operators that report failures as data.
+//!
+//! Fallible operators produce a pair of collections `(oks, errs)`, where `oks` contains the results
+//! of successful evaluations and `errs` contains the errors of failed evaluations. Errors are data
+//! like any other: they are retracted when the records that produced them are retracted, and they
+//! can be accumulated across operators with `concat` (once mapped to a common type) and reported
+//! alongside results, rather than panicking and taking down the worker.
+//!
+//! The `map_fallible` and `flat_map_fallible` methods on `Collection` introduce errors, and the
+//! `ok_err` method splits a collection of `Result` values. The `Fallible` trait provides the same
+//! convention for joins and reductions, whose logic may fail on the records they combine. These take
+//! the errors of the stages before them, and return them together with their own errors, so that the
+//! errors of a pipeline of fallible operators flow through to its end.
+//!
+//! # Examples
+//!
+//! ```
+//! use differential_dataflow::input::Input;
+//! use differential_dataflow::operators::Fallible;
+//!
+//! ::timely::example(|scope| {
+//!
+//!     let data = scope.new_collection_from(-2 .. 6).1;
+//!
+//!     // negative numbers fail to convert, and odd sums fail to halve.
+//!     let (oks, errs) = data.map_fallible(|x| u32::try_from(x).map(|y| (y % 2, y)).map_err(|_| x));
+//!     let (oks, errs) = oks.reduce_fallible(&errs, |_key, input, output| {
+//!         let sum: u32 = input.iter().map(|(val, count)| **val * (*count as u32)).sum();
+//!         if sum % 2 == 1 { return Err(sum as i32); }
+//!         output.push((sum / 2, 1));
+//!         Ok(())
+//!     });
+//!
+//!     oks.assert_eq(&scope.new_collection_from(vec![(0, 3)]).1);
+//!     errs.assert_eq(&scope.new_collection_from(vec![-2, -1, 9]).1);
+//! });
+//! ```
+
+use timely::dataflow::Scope;
+
+use crate::{Data, ExchangeData, Collection};
+use crate::difference::{Semigroup, Abelian, Multiply};
+use crate::lattice::Lattice;
+use crate::operators::{Join, Reduce};
+
+/// Extension trait for joins and reductions whose logic may fail.
+pub trait Fallible<G: Scope<Timestamp: Lattice+Ord>, K: Data, V: Data, R: Semigroup> {
+    /// Matches pairs `(key,val1)` and `(key,val2)` based on `key` and then applies a fallible function.
+    ///
+    /// The first collection contains the successful results, and the second collection contains the
+    /// errors of `errs` followed by the errors of `logic`, each with the multiplicity of the pair of
+    /// records that produced it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::Fallible;
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     let x = scope.new_collection_from(vec![(0, 1), (1, 3), (2, -1)]).1;
+    ///     let y = scope.new_collection_from(vec![(0, 0), (1, 2), (2, 1)]).1;
+    ///
+    ///     // negative values are errors, as is division by zero.
+    ///     let (x, errs) = x.map_fallible(|(key, a)| if a >= 0 { Ok((key, a)) } else { Err(key) });
+    ///     let (oks, errs) = x.join_fallible(&y, &errs, |&key, &a, &b| a.checked_div(b).ok_or(key));
+    ///
+    ///     oks.assert_eq(&scope.new_collection_from(vec![1]).1);
+    ///     errs.assert_eq(&scope.new_collection_from(vec![0, 2]).1);
+    /// });
+    /// ```
+    fn join_fallible<V2, R2, D, E, L>(&self, other: &Collection<G, (K,V2), R2>, errs: &Collection<G, E, <R as Multiply<R2>>::Output>, logic: L) -> (Collection<G, D, <R as Multiply<R2>>::Output>, Collection<G, E, <R as Multiply<R2>>::Output>)
+    where
+        K: ExchangeData,
+        V2: ExchangeData,
+        R2: ExchangeData+Semigroup,
+        R: Multiply<R2, Output: Semigroup+'static>,
+        D: Data,
+        E: Data,
+        L: FnMut(&K, &V, &V2)->Result<D, E>+'static;
+
+    /// Applies a fallible reduction function on records grouped by key.
+    ///
+    /// The logic is as for `reduce`, except that it may return an error instead of populating its
+    /// output. In that case any output it produced is discarded, and the error is reported in the
+    /// second collection with a count of one, after the errors of `errs`. The logic is supplied the
+    /// key, and may include it in the error.
+    where
+        V2: Data,
+        R2: Ord+Abelian+From<i8>+'static,
+        E: Data,
+        L: FnMut(&K, &[(&V, R)], &mut Vec<(V2, R2)>)->Result<(), E>+'static;
+}
+
+impl<G, K, V, R, T> Fallible<G, K, V, R> for T
+where
+    G: Scope<Timestamp: Lattice+Ord>,
+    K: Data,
+    V: Data,
+    R: Semigroup,
+    T: Join<G, K, V, R>+Reduce<G, K, V, R>,
+{
+    fn join_fallible<V2, R2, D, E, L>(&self, other: &Collection<G, (K,V2), R2>, errs: &Collection<G, E, <R as Multiply<R2>>::Output>, logic: L) -> (Collection<G, D, <R as Multiply<R2>>::Output>, Collection<G, E, <R as Multiply<R2>>::Output>)
+    where
+        K: ExchangeData,
+        V2: ExchangeData,
+        R2: ExchangeData+Semigroup,
+        R: Multiply<R2, Output: Semigroup+'static>,
+        D: Data,
+        E: Data,
+        L: FnMut(&K, &V, &V2)->Result<D, E>+'static,
+    {
+        let (oks, new_errs) = self.join_map(other, logic).ok_err();
+        (oks, errs.concat(&new_errs))
+    }
+
+    fn reduce_fallible<L, V2, R2, E>(&self, errs: &Collection<G, E, R2>, mut logic: L) -> (Collection<G, (K, V2), R2>, Collection<G, E, R2>)
+    where
+        V2: Data,
+        R2: Ord+Abelian+From<i8>+'static,
+        E: Data,
+        L: FnMut(&K, &[(&V, R)], &mut Vec<(V2, R2)>)->Result<(), E>+'static,
+    {
+        let mut buffer = Vec::new();
+        let results = self.reduce_named("ReduceFallible", move |key, input, output| {
+            match logic(key, input, &mut buffer) {
+                Ok(()) => output.extend(buffer.drain(..).map(|(val, diff)| (Ok(val), diff))),
+                Err(error) => {
+                    buffer.clear();
+                    output.push((Err(error), R2::from(1)));
+                }
+            }
+        });
+        let oks = results.flat_map(|(key, result)| result.ok().map(|val| (key, val)));
+        let new_errs = results.flat_map(|(_key, result)| result.err());
+        (oks, errs.concat(&new_errs))
+    }
+}
@@ -0,0 +1,213 @@
+//! Hierarchical reductions that remain incremental for large groups.
+//!
+//! A `reduce` operator re-evaluates its logic over all values of a key whenever the key changes,
+//! which becomes expensive for keys with many values. For reductions that can be applied to parts
+//! of a group and then again to the combined results, such as retaining the top `k` values or the
+//! least value, we can instead reduce in stages: first over many small buckets of values, selected
+//! by a prefix of each value's hash, and then over progressively coarser buckets of the results.
+//! A change to one value only re-evaluates the logic on the buckets that contain it, each of which
+//! holds few records.
+
+use std::cmp::Reverse;
+use std::hash::Hash;
+
+use timely::dataflow::Scope;
+
+use crate::{Collection, ExchangeData, Hashable};
+use crate::lattice::Lattice;
+use crate::operators::Reduce;
+
+/// Hash shifts for the bucketing stages used by default, from finest to coarsest.
+///
+/// Each stage groups values by their key and by their hash shifted right by the indicated number
+/// of bits, and each stage collects the results of at most 256 buckets of the stage before.
+pub const DEFAULT_SHIFTS: [u32; 3] = [40, 48, 56];
+
+/// Applies `logic` to each key's values through stages of reductions over hash buckets.
+///
+/// The `logic` must be such that applying it to the concatenated results of applying it to any
+/// partition of the values produces the same output as applying it to all values.
+fn reduce_hierarchical<G, K, V, L>(collection: &Collection<G, (K, V), isize>, name: &str, shifts: &[u32], logic: L) -> Collection<G, (K, V), isize>
+where
+    G: Scope<Timestamp: Lattice+Ord>,
+    K: ExchangeData+Hash,
+    V: ExchangeData+Hash,
+    L: FnMut(&[(&V, isize)], &mut Vec<(V, isize)>)+Clone+'static,
+{
+    let mut result = collection.clone();
+    for &shift in shifts.iter() {
+        let mut logic = logic.clone();
+        result = result
+            .map(move |(key, val)| {
+                let hash: u64 = val.hashed().into();
+                ((key, hash.checked_shr(shift).unwrap_or(0)), val)
+            })
+            .reduce_named(&format!("{}: Bucket({})", name, shift), move |_bucket, input, output| logic(input, output))
+            .map(|((key, _bucket), val)| (key, val));
+    }
+    let mut logic = logic;
+    result.reduce_named(name, move |_key, input, output| logic(input, output))
+}
+
+/// Extension trait for the `top_k` and `bottom_k` differential dataflow methods.
+pub trait TopK<G: Scope<Timestamp: Lattice+Ord>, K: ExchangeData, V: ExchangeData> {
+    /// Retains for each key the `k` values with the largest `order`.
+    ///
+    /// Values with equal `order` are retained in increasing order of the values themselves, and a
+    /// value with multiplicity greater than one may be retained several times. The reduction happens
+    /// hierarchically, with the stages described by `DEFAULT_SHIFTS`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::TopK;
+    ///
+    /// ::timely::example(|scope| {
+    ///     // retain the two largest values for each group
+    ///     scope.new_collection_from(1 .. 10).1
+    ///          .map(|x| (x / 3, x))
+    ///          .top_k(2, |x| *x);
+    /// });
+    /// ```
+    fn top_k<O, F>(&self, k: usize, order: F) -> Collection<G, (K, V), isize>
+    where
+        O: Ord+'static,
+        F: Fn(&V)->O+Clone+'static,
+    {
+        self.top_k_core(k, order, &DEFAULT_SHIFTS)
+    }
+
+    /// Retains for each key the `k` values with the smallest `order`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::TopK;
+    ///
+    /// ::timely::example(|scope| {
+    ///     // retain the two smallest values for each group
+    ///     scope.new_collection_from(1 .. 10).1
+    ///          .map(|x| (x / 3, x))
+    ///          .bottom_k(2, |x| *x);
+    /// });
+    /// ```
+    fn bottom_k<O, F>(&self, k: usize, order: F) -> Collection<G, (K, V), isize>
+    where
+        O: Ord+'static,
+        F: Fn(&V)->O+Clone+'static,
+    {
+        self.top_k_core(k, move |val| Reverse(order(val)), &DEFAULT_SHIFTS)
+    }
+
+    /// As `top_k`, with explicit hash shifts for the bucketing stages.
+    ///
+    /// The shifts should be listed from finest to coarsest, which is in increasing order. An empty
+    /// list of shifts results in a single `reduce` over all values of each key.
+    fn top_k_core<O, F>(&self, k: usize, order: F, shifts: &[u32]) -> Collection<G, (K, V), isize>
+    where
+        O: Ord+'static,
+        F: Fn(&V)->O+Clone+'static;
+}
+
+impl<G, K, V> TopK<G, K, V> for Collection<G, (K, V), isize>
+where
+    G: Scope<Timestamp: Lattice+Ord>,
+    K: ExchangeData+Hash,
+    V: ExchangeData+Hash,
+{
+    fn top_k_core<O, F>(&self, k: usize, order: F, shifts: &[u32]) -> Collection<G, (K, V), isize>
+    where
+        O: Ord+'static,
+        F: Fn(&V)->O+Clone+'static,
+    {
+        reduce_hierarchical(self, "TopK", shifts, move |input, output| {
+            // Values arrive sorted, and the stable sort retains this order among equal `order`.
+            let mut sorted = input.iter().filter(|(_, count)| *count > 0).map(|(val, count)| (order(val), *val, *count)).collect::<Vec<_>>();
+            sorted.sort_by(|x, y| y.0.cmp(&x.0));
+            let mut remaining = k as isize;
+            for (_, val, count) in sorted {
+                if remaining == 0 { break; }
+                let count = std::cmp::min(count, remaining);
+                output.push((val.clone(), count));
+                remaining -= count;
+            }
+        })
+    }
+}
+
+/// Extension trait for the `min_by_key` and `max_by_key` differential dataflow methods.
+pub trait MinMax<G: Scope<Timestamp: Lattice+Ord>, K: ExchangeData, V: ExchangeData> {
+    /// Retains for each key its least value, with multiplicity one.
+    ///
+    /// The reduction happens hierarchically, with the stages described by `DEFAULT_SHIFTS`, so that
+    /// the retraction of the least value only requires revisiting the few values in its buckets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::MinMax;
+    ///
+    /// ::timely::example(|scope| {
+    ///     // report the smallest value for each group
+    ///     scope.new_collection_from(1 .. 10).1
+    ///          .map(|x| (x / 3, x))
+    ///          .min_by_key();
+    /// });
+    /// ```
+    fn min_by_key(&self) -> Collection<G, (K, V), isize> {
+        self.min_by_key_core(&DEFAULT_SHIFTS)
+    }
+
+    /// Retains for each key its greatest value, with multiplicity one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::MinMax;
+    ///
+    /// ::timely::example(|scope| {
+    ///     // report the largest value for each group
+    ///     scope.new_collection_from(1 .. 10).1
+    ///          .map(|x| (x / 3, x))
+    ///          .max_by_key();
+    /// });
+    /// ```
+    fn max_by_key(&self) -> Collection<G, (K, V), isize> {
+        self.max_by_key_core(&DEFAULT_SHIFTS)
+    }
+
+    /// As `min_by_key`, with explicit hash shifts for the bucketing stages.
+    fn min_by_key_core(&self, shifts: &[u32]) -> Collection<G, (K, V), isize>;
+
+    /// As `max_by_key`, with explicit hash shifts for the bucketing stages.
+    fn max_by_key_core(&self, shifts: &[u32]) -> Collection<G, (K, V), isize>;
+}
+
+impl<G, K, V> MinMax<G, K, V> for Collection<G, (K, V), isize>
+where
+    G: Scope<Timestamp: Lattice+Ord>,
+    K: ExchangeData+Hash,
+    V: ExchangeData+Hash,
+{
+    fn min_by_key_core(&self, shifts: &[u32]) -> Collection<G, (K, V), isize> {
+        // Values arrive sorted, and the first with a positive count is the least.
+        reduce_hierarchical(self, "MinByKey", shifts, |input, output| {
+            if let Some((val, _)) = input.iter().find(|(_, count)| *count > 0) {
+                output.push(((*val).clone(), 1));
+            }
+        })
+    }
+
+    fn max_by_key_core(&self, shifts: &[u32]) -> Collection<G, (K, V), isize> {
+        // Values arrive sorted, and the last with a positive count is the greatest.
+        reduce_hierarchical(self, "MaxByKey", shifts, |input, output| {
+            if let Some((val, _)) = input.iter().rev().find(|(_, count)| *count > 0) {
+                output.push(((*val).clone(), 1));
+            }
+        })
+    }
+}
@@ -10,6 +10,12 @@ pub use self::iterate::{Iterate, ResultsIn};
 pub use self::join::{Join, JoinCore};
 pub use self::count::CountTotal;
 pub use self::threshold::ThresholdTotal;
+pub use self::temporal::Temporal;
+pub use self::hierarchical::{TopK, MinMax};
+pub use self::range_join::RangeJoin;
+pub use self::fallible::Fallible;
+pub use self::skew_join::SkewJoin;
+pub use self::outer_join::{OuterJoin, OuterJoinCore};
 
 pub mod arrange;
 pub mod negate;
@@ -19,6 +25,12 @@ pub mod iterate;
 pub mod join;
 pub mod count;
 pub mod threshold;
+pub mod temporal;
+pub mod hierarchical;
+pub mod range_join;
+pub mod fallible;
+pub mod skew_join;
+pub mod outer_join;
 
 use crate::lattice::Lattice;
 use crate::trace::Cursor;
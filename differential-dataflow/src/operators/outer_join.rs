@@ -0,0 +1,645 @@
+//! Match pairs of records based on a key, retaining records whose key has no match.
+//!
+//! The outer joins produce the matching pairs of records of two collections, as `join` does, and in
+//! addition the records of one or both collections whose key is absent from the other collection, with
+//! `None` in place of a value from the other collection. A key is absent from a collection at a time if
+//! none of its values accumulate to a non-zero multiplicity at that time.
+//!
+//! The implementation is a single operator over the two arranged inputs, structured as `join_traces`.
+//! Each batch of updates is matched against the accepted contents of the other trace, and the absence
+//! of each of its keys is determined from the history of that key in the other trace. Retaining the
+//! unmatched records of the other input additionally requires the history of the key in the batch's
+//! own trace, to determine when the batch changes the absence of the key. No arrangements are formed
+//! beyond those of the inputs, although a retained input's trace is held back to its own frontier.
+
+use std::collections::VecDeque;
+
+use timely::order::PartialOrder;
+use timely::progress::Timestamp;
+use timely::progress::frontier::Antichain;
+use timely::dataflow::Scope;
+use timely::dataflow::operators::Capability;
+use timely::dataflow::operators::generic::Operator;
+use timely::dataflow::channels::pact::Pipeline;
+
+use crate::{Data, ExchangeData, Collection, AsCollection, Hashable};
+use crate::consolidation::{consolidate, consolidate_updates, consolidate_updates_from};
+use crate::difference::{Semigroup, Abelian, Multiply};
+use crate::lattice::{Lattice, close_under_join};
+use crate::operators::arrange::{Arranged, ArrangeByKey};
+use crate::trace::{BatchReader, Cursor, TraceReader};
+
+/// Outer join implementations for `(key,val)` data.
+pub trait OuterJoin<G: Scope, K: Data, V: Data, R: Semigroup> {
+
+    /// Matches pairs `(key,val1)` and `(key,val2)` based on `key`, retaining unmatched records of `self`.
+    ///
+    /// Each record of `self` is produced with `None` in place of a value from `other` for as long as its
+    /// key is absent from `other`. The absence of keys is determined from the arrangement of `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::OuterJoin;
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     let x = scope.new_collection_from(vec![(0, 1), (1, 3)]).1;
+    ///     let y = scope.new_collection_from(vec![(0, 'a'), (2, 'b')]).1;
+    ///     let z = scope.new_collection_from(vec![(0, (1, Some('a'))), (1, (3, None))]).1;
+    ///
+    ///     x.left_join(&y)
+    ///      .assert_eq(&z);
+    /// });
+    /// ```
+    fn left_join<V2>(&self, other: &Collection<G, (K,V2), R>) -> Collection<G, (K,(V,Option<V2>)), R>
+    where K: ExchangeData, V2: ExchangeData, R: ExchangeData+Abelian+Multiply<Output=R>+From<i8>;
+
+    /// Matches pairs `(key,val1)` and `(key,val2)` based on `key`, retaining unmatched records of `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::OuterJoin;
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     let x = scope.new_collection_from(vec![(0, 1), (1, 3)]).1;
+    ///     let y = scope.new_collection_from(vec![(0, 'a'), (2, 'b')]).1;
+    ///     let z = scope.new_collection_from(vec![(0, (Some(1), 'a')), (2, (None, 'b'))]).1;
+    ///
+    ///     x.right_join(&y)
+    ///      .assert_eq(&z);
+    /// });
+    /// ```
+    fn right_join<V2>(&self, other: &Collection<G, (K,V2), R>) -> Collection<G, (K,(Option<V>,V2)), R>
+    where K: ExchangeData, V2: ExchangeData, R: ExchangeData+Abelian+Multiply<Output=R>+From<i8>;
+
+    /// Matches pairs `(key,val1)` and `(key,val2)` based on `key`, retaining unmatched records of both inputs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::OuterJoin;
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     let x = scope.new_collection_from(vec![(0, 1), (1, 3)]).1;
+    ///     let y = scope.new_collection_from(vec![(0, 'a'), (2, 'b')]).1;
+    ///     let z = scope.new_collection_from(vec![(0, (Some(1), Some('a'))), (1, (Some(3), None)), (2, (None, Some('b')))]).1;
+    ///
+    ///     x.full_outer_join(&y)
+    ///      .assert_eq(&z);
+    /// });
+    /// ```
+    fn full_outer_join<V2>(&self, other: &Collection<G, (K,V2), R>) -> Collection<G, (K,(Option<V>,Option<V2>)), R>
+    where K: ExchangeData, V2: ExchangeData, R: ExchangeData+Abelian+Multiply<Output=R>+From<i8>;
+}
+
+impl<G, K, V, R> OuterJoin<G, K, V, R> for Collection<G, (K, V), R>
+where
+    G: Scope<Timestamp: Lattice+Ord>,
+    K: ExchangeData+Hashable,
+    V: ExchangeData,
+    R: ExchangeData+Semigroup,
+{
+    fn left_join<V2: ExchangeData>(&self, other: &Collection<G, (K, V2), R>) -> Collection<G, (K, (V, Option<V2>)), R>
+    where R: Abelian+Multiply<Output=R>+From<i8> {
+        let arranged1 = self.arrange_by_key();
+        let arranged2 = other.arrange_by_key();
+        arranged1.left_join_core(&arranged2, |k,v1,v2| Some((k.clone(), (v1.clone(), v2.cloned()))))
+    }
+
+    fn right_join<V2: ExchangeData>(&self, other: &Collection<G, (K, V2), R>) -> Collection<G, (K, (Option<V>, V2)), R>
+    where R: Abelian+Multiply<Output=R>+From<i8> {
+        let arranged1 = self.arrange_by_key();
+        let arranged2 = other.arrange_by_key();
+        arranged2.left_join_core(&arranged1, |k,v2,v1| Some((k.clone(), (v1.cloned(), v2.clone()))))
+    }
+
+    fn full_outer_join<V2: ExchangeData>(&self, other: &Collection<G, (K, V2), R>) -> Collection<G, (K, (Option<V>, Option<V2>)), R>
+    where R: Abelian+Multiply<Output=R>+From<i8> {
+        let arranged1 = self.arrange_by_key();
+        let arranged2 = other.arrange_by_key();
+        arranged1.full_outer_join_core(&arranged2, |k,v1,v2| Some((k.clone(), (v1.cloned(), v2.cloned()))))
+    }
+}
+
+impl<G, K, V, Tr> OuterJoin<G, K, V, Tr::Diff> for Arranged<G, Tr>
+where
+    G: Scope<Timestamp=Tr::Time>,
+    Tr: for<'a> TraceReader<Key<'a> = &'a K, Val<'a> = &'a V>+Clone+'static,
+    K: ExchangeData+Hashable,
+    V: Data + 'static,
+{
+    fn left_join<V2: ExchangeData>(&self, other: &Collection<G, (K, V2), Tr::Diff>) -> Collection<G, (K, (V, Option<V2>)), Tr::Diff>
+    where Tr::Diff: ExchangeData+Abelian+Multiply<Output=Tr::Diff>+From<i8> {
+        let arranged2 = other.arrange_by_key();
+        self.left_join_core(&arranged2, |k,v1,v2| Some((k.clone(), (v1.clone(), v2.cloned()))))
+    }
+
+    fn right_join<V2: ExchangeData>(&self, other: &Collection<G, (K, V2), Tr::Diff>) -> Collection<G, (K, (Option<V>, V2)), Tr::Diff>
+    where Tr::Diff: ExchangeData+Abelian+Multiply<Output=Tr::Diff>+From<i8> {
+        let arranged2 = other.arrange_by_key();
+        arranged2.left_join_core(self, |k,v2,v1| Some((k.clone(), (v1.cloned(), v2.clone()))))
+    }
+
+    fn full_outer_join<V2: ExchangeData>(&self, other: &Collection<G, (K, V2), Tr::Diff>) -> Collection<G, (K, (Option<V>, Option<V2>)), Tr::Diff>
+    where Tr::Diff: ExchangeData+Abelian+Multiply<Output=Tr::Diff>+From<i8> {
+        let arranged2 = other.arrange_by_key();
+        self.full_outer_join_core(&arranged2, |k,v1,v2| Some((k.clone(), (v1.cloned(), v2.cloned()))))
+    }
+}
+
+/// Outer joins of a collection against an arranged collection with the same key type.
+///
+/// This trait is implemented for collections, which are arranged by key. Arrangements (`Arranged<G, T>`)
+/// provide the same methods directly.
+pub trait OuterJoinCore<G: Scope<Timestamp: Lattice+Ord>, K: 'static + ?Sized, V: 'static + ?Sized, R: Semigroup> {
+
+    /// Joins two arranged collections with the same key type, retaining unmatched records of `self`.
+    ///
+    /// Each matching pair of records `(key, val1)` and `(key, val2)` is presented to the `result` function
+    /// with `Some(val2)`, and each record `(key, val1)` is presented with `None` for as long as its key is
+    /// absent from `stream2`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::arrange::ArrangeByKey;
+    /// use differential_dataflow::operators::outer_join::OuterJoinCore;
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     let x = scope.new_collection_from(vec![(0u32, 1), (1, 3)]).1;
+    ///     let y = scope.new_collection_from(vec![(0, 'a'), (2, 'b')]).1
+    ///                  .arrange_by_key();
+    ///
+    ///     let z = scope.new_collection_from(vec![(1, Some('a')), (3, None)]).1;
+    ///
+    ///     x.left_join_core(&y, |_key, &a, b| Some((a, b.cloned())))
+    ///      .assert_eq(&z);
+    /// });
+    /// ```
+    fn left_join_core<Tr2,I,L> (&self, stream2: &Arranged<G,Tr2>, result: L) -> Collection<G,I::Item,R>
+    where
+        Tr2: for<'a> TraceReader<Key<'a>=&'a K, Time=G::Timestamp, Diff=R>+Clone+'static,
+        R: Abelian+Multiply<Output=R>+From<i8>,
+        I: IntoIterator<Item: Data>,
+        L: FnMut(&K,&V,Option<Tr2::Val<'_>>)->I+'static,
+        ;
+
+    /// Joins two arranged collections with the same key type, retaining unmatched records of both inputs.
+    ///
+    /// As `left_join_core`, but additionally presents each record `(key, val2)` of `stream2` to the `result`
+    /// function with `None` in place of a value from `self`, for as long as its key is absent from `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::arrange::ArrangeByKey;
+    /// use differential_dataflow::operators::outer_join::OuterJoinCore;
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     let x = scope.new_collection_from(vec![(0u32, 1), (1, 3)]).1;
+    ///     let y = scope.new_collection_from(vec![(0, 'a'), (2, 'b')]).1
+    ///                  .arrange_by_key();
+    ///
+    ///     let z = scope.new_collection_from(vec![(Some(1), Some('a')), (Some(3), None), (None, Some('b'))]).1;
+    ///
+    ///     x.full_outer_join_core(&y, |_key, a, b| Some((a.cloned(), b.cloned())))
+    ///      .assert_eq(&z);
+    /// });
+    /// ```
+    fn full_outer_join_core<Tr2,I,L> (&self, stream2: &Arranged<G,Tr2>, result: L) -> Collection<G,I::Item,R>
+    where
+        Tr2: for<'a> TraceReader<Key<'a>=&'a K, Time=G::Timestamp, Diff=R>+Clone+'static,
+        R: Abelian+Multiply<Output=R>+From<i8>,
+        I: IntoIterator<Item: Data>,
+        L: FnMut(&K,Option<&V>,Option<Tr2::Val<'_>>)->I+'static,
+        ;
+}
+
+impl<G, K, V, R> OuterJoinCore<G, K, V, R> for Collection<G, (K, V), R>
+where
+    G: Scope<Timestamp: Lattice+Ord>,
+    K: ExchangeData+Hashable,
+    V: ExchangeData,
+    R: ExchangeData+Semigroup,
+{
+    fn left_join_core<Tr2,I,L> (&self, stream2: &Arranged<G,Tr2>, result: L) -> Collection<G,I::Item,R>
+    where
+        Tr2: for<'a> TraceReader<Key<'a>=&'a K, Time=G::Timestamp, Diff=R>+Clone+'static,
+        R: Abelian+Multiply<Output=R>+From<i8>,
+        I: IntoIterator<Item: Data>,
+        L: FnMut(&K,&V,Option<Tr2::Val<'_>>)->I+'static,
+    {
+        self.arrange_by_key()
+            .left_join_core(stream2, result)
+    }
+
+    fn full_outer_join_core<Tr2,I,L> (&self, stream2: &Arranged<G,Tr2>, result: L) -> Collection<G,I::Item,R>
+    where
+        Tr2: for<'a> TraceReader<Key<'a>=&'a K, Time=G::Timestamp, Diff=R>+Clone+'static,
+        R: Abelian+Multiply<Output=R>+From<i8>,
+        I: IntoIterator<Item: Data>,
+        L: FnMut(&K,Option<&V>,Option<Tr2::Val<'_>>)->I+'static,
+    {
+        self.arrange_by_key()
+            .full_outer_join_core(stream2, result)
+    }
+}
+
+/// An outer equijoin of two traces, sharing a common key type.
+///
+/// Each matching pair of values is presented to `result` as two `Some` values. If `left` is set, each value
+/// of `arranged1` is also presented with `None` for as long as its key is absent from `arranged2`, and if
+/// `right` is set, each value of `arranged2` is presented with `None` for as long as its key is absent from
+/// `arranged1`. The records of `result` are produced with the product of the multiplicities of the values.
+///
+/// The operator follows the structure of `join_traces`, except that every key of each batch is examined,
+/// rather than only those keys present in the other trace. When `right` is set the trace of `arranged1` is
+/// held back to the frontier of its own input, as its history is needed to determine the times at which
+/// its batches change the absence of their keys, and symmetrically for `left` and `arranged2`.
+pub fn outer_join_traces<G, T1, T2, I, L>(arranged1: &Arranged<G,T1>, arranged2: &Arranged<G,T2>, left: bool, right: bool, mut result: L) -> Collection<G, I::Item, T1::Diff>
+where
+    G: Scope<Timestamp=T1::Time>,
+    T1: TraceReader+Clone+'static,
+    T2: for<'a> TraceReader<Key<'a>=T1::Key<'a>, Time=T1::Time, Diff=T1::Diff>+Clone+'static,
+    T1::Diff: Abelian+Multiply<Output=T1::Diff>+From<i8>,
+    I: IntoIterator<Item: Data>,
+    L: FnMut(T1::Key<'_>,Option<T1::Val<'_>>,Option<T2::Val<'_>>)->I+'static,
+{
+    // Rename traces for symmetry from here on out.
+    let mut trace1 = arranged1.trace.clone();
+    let mut trace2 = arranged2.trace.clone();
+
+    arranged1.stream.binary_frontier(&arranged2.stream, Pipeline, Pipeline, "OuterJoin", move |capability, info| {
+
+        // Acquire an activator to reschedule the operator when it has unfinished work.
+        use timely::scheduling::Activator;
+        let activations = arranged1.stream.scope().activations().clone();
+        let activator = Activator::new(info.address, activations);
+
+        // Acknowledged frontier for each input, with the same role as in `join_traces`.
+        let mut acknowledged1 = Antichain::from_elem(<G::Timestamp>::minimum());
+        let mut acknowledged2 = Antichain::from_elem(<G::Timestamp>::minimum());
+
+        // Deferred work for the initial contents of each trace, and for batches from each input.
+        let mut start1 = VecDeque::new();
+        let mut start2 = VecDeque::new();
+        let mut todo1 = VecDeque::new();
+        let mut todo2 = VecDeque::new();
+
+        // The initial contents of each trace are accepted as single batches, those of `trace1` first.
+        // Nothing of `trace2` has been accepted when those of `trace1` are, and nothing of either trace
+        // precedes its own initial contents.
+        trace1.map_batches(|batch1| { acknowledged1.clone_from(batch1.upper()); });
+        assert!(PartialOrder::less_equal(&trace1.get_physical_compaction(), &acknowledged1.borrow()));
+        trace2.map_batches(|batch2| { acknowledged2.clone_from(batch2.upper()); });
+        assert!(PartialOrder::less_equal(&trace2.get_physical_compaction(), &acknowledged2.borrow()));
+
+        let (trace1_cursor, trace1_storage) = trace1.cursor_through(acknowledged1.borrow()).unwrap();
+        start1.push_back(OuterDeferred::new(None::<(T1::Cursor, T1::Storage)>, trace1_cursor, trace1_storage, None::<(T2::Cursor, T2::Storage)>, left, right, capability.clone()));
+        let (trace2_cursor, trace2_storage) = trace2.cursor_through(acknowledged2.borrow()).unwrap();
+        let other = trace1.cursor_through(acknowledged1.borrow()).unwrap();
+        start2.push_back(OuterDeferred::new(None::<(T2::Cursor, T2::Storage)>, trace2_cursor, trace2_storage, Some(other), right, left, capability.clone()));
+
+        // Droppable handles to shared trace data structures.
+        let mut trace1_option = Some(trace1);
+        let mut trace2_option = Some(trace2);
+
+        let mut buffer = Vec::new();
+
+        move |input1, input2, output| {
+
+            // Drain input 1, prepare work.
+            input1.for_each(|capability, data| {
+                // This test *should* always pass, as we only drop a trace in response to the other input emptying.
+                if let Some(ref mut trace2) = trace2_option {
+                    let capability = capability.retain();
+                    for batch1 in data.drain(..) {
+                        // Ignore any pre-loaded data.
+                        if PartialOrder::less_equal(&acknowledged1, batch1.lower()) {
+                            if !batch1.is_empty() {
+                                let other = trace2.cursor_through(acknowledged2.borrow()).unwrap();
+                                // The prior contents of `trace1` are only needed to retain unmatched records of `input2`,
+                                // in which case `trace1` is retained until `input1` is empty.
+                                let before = if right {
+                                    let trace1 = trace1_option.as_mut().expect("`trace1_option` dropped before `input1` emptied!");
+                                    Some(trace1.cursor_through(acknowledged1.borrow()).unwrap())
+                                }
+                                else { None };
+                                todo1.push_back(OuterDeferred::new(before, batch1.cursor(), batch1.clone(), Some(other), left, right, capability.clone()));
+                            }
+                            debug_assert!(PartialOrder::less_equal(&acknowledged1, batch1.upper()));
+                            acknowledged1.clone_from(batch1.upper());
+                        }
+                    }
+                }
+                else { panic!("`trace2_option` dropped before `input1` emptied!"); }
+            });
+
+            // Drain input 2, prepare work.
+            input2.for_each(|capability, data| {
+                // This test *should* always pass, as we only drop a trace in response to the other input emptying.
+                if let Some(ref mut trace1) = trace1_option {
+                    let capability = capability.retain();
+                    for batch2 in data.drain(..) {
+                        // Ignore any pre-loaded data.
+                        if PartialOrder::less_equal(&acknowledged2, batch2.lower()) {
+                            if !batch2.is_empty() {
+                                let other = trace1.cursor_through(acknowledged1.borrow()).unwrap();
+                                // The prior contents of `trace2` are only needed to retain unmatched records of `input1`,
+                                // in which case `trace2` is retained until `input2` is empty.
+                                let before = if left {
+                                    let trace2 = trace2_option.as_mut().expect("`trace2_option` dropped before `input2` emptied!");
+                                    Some(trace2.cursor_through(acknowledged2.borrow()).unwrap())
+                                }
+                                else { None };
+                                todo2.push_back(OuterDeferred::new(before, batch2.cursor(), batch2.clone(), Some(other), right, left, capability.clone()));
+                            }
+                            debug_assert!(PartialOrder::less_equal(&acknowledged2, batch2.upper()));
+                            acknowledged2.clone_from(batch2.upper());
+                        }
+                    }
+                }
+                else { panic!("`trace1_option` dropped before `input2` emptied!"); }
+            });
+
+            // Advance acknowledged frontiers through any empty regions that we may not receive as batches.
+            if let Some(trace1) = trace1_option.as_mut() {
+                trace1.advance_upper(&mut acknowledged1);
+            }
+            if let Some(trace2) = trace2_option.as_mut() {
+                trace2.advance_upper(&mut acknowledged2);
+            }
+
+            // Perform some amount of outstanding work for each input.
+            let mut fuel = 1_000_000;
+            while let (Some(deferred), true) = (start1.front_mut(), fuel > 0) {
+                deferred.work(&mut buffer, |k,v1,v2| result(k,v1,v2), &mut fuel);
+                output.session(&deferred.capability).give_iterator(buffer.drain(..));
+                if !deferred.work_remains() { start1.pop_front(); }
+            }
+            while let (Some(deferred), true) = (todo1.front_mut(), fuel > 0) {
+                deferred.work(&mut buffer, |k,v1,v2| result(k,v1,v2), &mut fuel);
+                output.session(&deferred.capability).give_iterator(buffer.drain(..));
+                if !deferred.work_remains() { todo1.pop_front(); }
+            }
+
+            let mut fuel = 1_000_000;
+            while let (Some(deferred), true) = (start2.front_mut(), fuel > 0) {
+                deferred.work(&mut buffer, |k,v2,v1| result(k,v1,v2), &mut fuel);
+                output.session(&deferred.capability).give_iterator(buffer.drain(..));
+                if !deferred.work_remains() { start2.pop_front(); }
+            }
+            while let (Some(deferred), true) = (todo2.front_mut(), fuel > 0) {
+                deferred.work(&mut buffer, |k,v2,v1| result(k,v1,v2), &mut fuel);
+                output.session(&deferred.capability).give_iterator(buffer.drain(..));
+                if !deferred.work_remains() { todo2.pop_front(); }
+            }
+
+            // Re-activate operator if work remains.
+            if !start1.is_empty() || !todo1.is_empty() || !start2.is_empty() || !todo2.is_empty() {
+                activator.activate();
+            }
+
+            // Maintain `trace1`. It is read by batches of `input2`, and by batches of `input1` if `right` is set.
+            if let Some(trace1) = trace1_option.as_mut() {
+                let mut frontier = Antichain::new();
+                frontier.extend(input2.frontier().frontier().iter().cloned());
+                if right { frontier.extend(input1.frontier().frontier().iter().cloned()); }
+                if frontier.is_empty() { trace1_option = None; }
+                else {
+                    trace1.set_logical_compaction(frontier.borrow());
+                    trace1.set_physical_compaction(acknowledged1.borrow());
+                }
+            }
+
+            // Maintain `trace2`. It is read by batches of `input1`, and by batches of `input2` if `left` is set.
+            if let Some(trace2) = trace2_option.as_mut() {
+                let mut frontier = Antichain::new();
+                frontier.extend(input1.frontier().frontier().iter().cloned());
+                if left { frontier.extend(input2.frontier().frontier().iter().cloned()); }
+                if frontier.is_empty() { trace2_option = None; }
+                else {
+                    trace2.set_logical_compaction(frontier.borrow());
+                    trace2.set_physical_compaction(acknowledged2.borrow());
+                }
+            }
+        }
+    })
+    .as_collection()
+}
+
+/// Deferred outer join computation for a batch of updates.
+///
+/// The batch is matched against the accepted contents of the other trace. If `retain_batch` is set, the
+/// records of the batch are also produced at the times their key is absent from the other trace. If
+/// `retain_other` is set, the records of the other trace are produced at the times the batch changes the
+/// absence of their key from the batch's own trace, whose contents before the batch are `before`.
+struct OuterDeferred<T, C1, CB, C2>
+where
+    T: Timestamp+Lattice+Ord,
+    C1: Cursor<Time=T>,
+    CB: for<'a> Cursor<Key<'a>=C1::Key<'a>, Val<'a>=C1::Val<'a>, Time=T, Diff=C1::Diff>,
+    C2: for<'a> Cursor<Key<'a>=C1::Key<'a>, Time=T, Diff=C1::Diff>,
+{
+    before: Option<(C1, C1::Storage)>,
+    batch: CB,
+    batch_storage: CB::Storage,
+    other: Option<(C2, C2::Storage)>,
+    retain_batch: bool,
+    retain_other: bool,
+    capability: Capability<T>,
+    done: bool,
+}
+
+impl<T, C1, CB, C2> OuterDeferred<T, C1, CB, C2>
+where
+    T: Timestamp+Lattice+Ord,
+    C1: Cursor<Time=T, Diff: Abelian+Multiply<Output=C1::Diff>+From<i8>>,
+    CB: for<'a> Cursor<Key<'a>=C1::Key<'a>, Val<'a>=C1::Val<'a>, Time=T, Diff=C1::Diff>,
+    C2: for<'a> Cursor<Key<'a>=C1::Key<'a>, Time=T, Diff=C1::Diff>,
+{
+    fn new(before: Option<(C1, C1::Storage)>, batch: CB, batch_storage: CB::Storage, other: Option<(C2, C2::Storage)>, retain_batch: bool, retain_other: bool, capability: Capability<T>) -> Self {
+        OuterDeferred {
+            before,
+            batch,
+            batch_storage,
+            other,
+            retain_batch,
+            retain_other,
+            capability,
+            done: false,
+        }
+    }
+
+    fn work_remains(&self) -> bool {
+        !self.done
+    }
+
+    /// Process keys until at least `fuel` output tuples produced, or the work is exhausted.
+    #[inline(never)]
+    fn work<D, I, L>(&mut self, output: &mut Vec<(D, T, C1::Diff)>, mut logic: L, fuel: &mut usize)
+    where
+        D: Ord,
+        I: IntoIterator<Item=D>,
+        L: for<'a> FnMut(C1::Key<'a>, Option<C1::Val<'a>>, Option<C2::Val<'a>>)->I,
+    {
+        let meet = self.capability.time();
+        let start = output.len();
+
+        let batch_storage = &self.batch_storage;
+        let batch = &mut self.batch;
+        let mut before = self.before.as_mut().map(|(cursor, storage)| (cursor, &*storage));
+        let mut other = self.other.as_mut().map(|(cursor, storage)| (cursor, &*storage));
+
+        // Updates for the current key in the batch, in its own trace before the batch, and in the other trace.
+        let mut history = Vec::new();
+        let mut history_before = Vec::new();
+        let mut history_other = Vec::new();
+        // Updates whose accumulation indicates the absence of the current key.
+        let mut absent = Vec::new();
+
+        while let (Some(key), true) = (batch.get_key(batch_storage), output.len() - start < *fuel) {
+
+            load(batch, batch_storage, key, meet, &mut history);
+            if let Some((cursor, storage)) = other.as_mut() {
+                load(&mut **cursor, *storage, key, meet, &mut history_other);
+            }
+
+            // Matching pairs of records.
+            for (val1, time1, diff1) in history.iter() {
+                for (val2, time2, diff2) in history_other.iter() {
+                    let time = time1.join(time2);
+                    let diff = diff1.clone().multiply(diff2);
+                    for datum in logic(key, Some(*val1), Some(*val2)) {
+                        output.push((datum, time.clone(), diff.clone()));
+                    }
+                }
+            }
+
+            // Records of the batch, for as long as the key is absent from the other trace.
+            if self.retain_batch {
+                absent.clear();
+                absence(&history_other, meet, &mut absent);
+                for (val1, time1, diff1) in history.iter() {
+                    for (time2, diff2) in absent.iter() {
+                        let time = time1.join(time2);
+                        let diff = diff1.clone().multiply(diff2);
+                        for datum in logic(key, Some(*val1), None) {
+                            output.push((datum, time.clone(), diff.clone()));
+                        }
+                    }
+                }
+            }
+
+            // Records of the other trace, for the change the batch makes to the absence of the key.
+            if self.retain_other && !history_other.is_empty() {
+                if let Some((cursor, storage)) = before.as_mut() {
+                    load(&mut **cursor, *storage, key, meet, &mut history_before);
+                }
+                absent.clear();
+                absence(&history_before, meet, &mut absent);
+                for (_time, diff) in absent.iter_mut() { diff.negate(); }
+                history_before.extend(history.iter().cloned());
+                consolidate_updates(&mut history_before);
+                absence(&history_before, meet, &mut absent);
+                consolidate(&mut absent);
+                for (time1, diff1) in absent.iter() {
+                    for (val2, time2, diff2) in history_other.iter() {
+                        let time = time1.join(time2);
+                        let diff = diff1.clone().multiply(diff2);
+                        for datum in logic(key, None, Some(*val2)) {
+                            output.push((datum, time.clone(), diff.clone()));
+                        }
+                    }
+                }
+            }
+
+            history.clear();
+            history_before.clear();
+            history_other.clear();
+            batch.step_key(batch_storage);
+        }
+        self.done = !batch.key_valid(batch_storage);
+
+        consolidate_updates_from(output, start);
+        let effort = output.len() - start;
+        if effort > *fuel { *fuel = 0; }
+        else              { *fuel -= effort; }
+    }
+}
+
+/// Loads the updates for `key` from `cursor` into `history`, with times advanced by `meet`.
+fn load<'a, C: Cursor>(cursor: &mut C, storage: &'a C::Storage, key: C::Key<'a>, meet: &C::Time, history: &mut Vec<(C::Val<'a>, C::Time, C::Diff)>) {
+    cursor.seek_key(storage, key);
+    if cursor.get_key(storage) == Some(key) {
+        while let Some(val) = cursor.get_val(storage) {
+            cursor.map_times(storage, |time, diff| {
+                let mut time = C::owned_time(time);
+                time.join_assign(meet);
+                history.push((val, time, C::owned_diff(diff)));
+            });
+            cursor.step_val(storage);
+        }
+    }
+    consolidate_updates(history);
+}
+
+/// Appends to `absent` updates whose accumulation at each time greater or equal to `meet` is one if no value
+/// accumulates to a non-zero multiplicity in `history` at that time, and zero otherwise.
+///
+/// The times of `history` must be greater or equal to `meet`, and `history` must be consolidated. The
+/// accumulations can only change at joins of times of `history`, which are visited in an order consistent
+/// with the partial order, each receiving the update needed to correct the accumulation at that time.
+fn absence<V, T, R>(history: &[(V, T, R)], meet: &T, absent: &mut Vec<(T, R)>)
+where
+    V: Ord,
+    T: Lattice+Ord+Clone,
+    R: Abelian+From<i8>,
+{
+    let mut times = Vec::with_capacity(history.len() + 1);
+    times.push(meet.clone());
+    times.extend(history.iter().map(|(_, time, _)| time.clone()));
+    close_under_join(&mut times);
+
+    let offset = absent.len();
+    for time in times {
+        // The key is present if some value, whose updates are contiguous, accumulates to non-zero.
+        let mut present = false;
+        let mut lower = 0;
+        while lower < history.len() && !present {
+            let mut upper = lower;
+            let mut accum = R::zero();
+            while upper < history.len() && history[upper].0 == history[lower].0 {
+                if history[upper].1.less_equal(&time) {
+                    accum.plus_equals(&history[upper].2);
+                }
+                upper += 1;
+            }
+            present = !accum.is_zero();
+            lower = upper;
+        }
+
+        let mut update = if present { R::zero() } else { R::from(1i8) };
+        for (prior, diff) in absent[offset..].iter() {
+            if prior.less_equal(&time) {
+                let mut diff = diff.clone();
+                diff.negate();
+                update.plus_equals(&diff);
+            }
+        }
+        if !update.is_zero() {
+            absent.push((time, update));
+        }
+    }
+}
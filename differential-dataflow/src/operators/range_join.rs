@@ -0,0 +1,442 @@
+//! Match records whose keys fall in ranges determined by the keys of another collection.
+//!
+//! The range join matches each `(key1, val1)` in one collection with each `(key2, val2)` in another
+//! for which `key1` lies in a range determined by `key2`. This covers band joins, where `key1` must be
+//! within some distance of `key2`, and inequality joins, where `key1` must be less than (or greater than)
+//! `key2`, as well as joins against intervals when `key2` is itself an interval.
+//!
+//! The implementation relies on the sorted key order of arrangements. New updates to the second input
+//! seek to the start of their range in the first input's trace and scan forward until the end of the
+//! range. New updates to the first input sweep through the second input's trace in key order, which
+//! requires that the lower bounds of ranges are monotone in `key2`: if `key2a <= key2b` then the range
+//! for `key2b` must not start before the range for `key2a`. The upper bounds are not constrained.
+//!
+//! As for `join`, this work is deferred and performed in steps, each producing a bounded amount of output,
+//! so that large batches do not stall the worker or buffer all of their output at once.
+
+use std::collections::VecDeque;
+use std::ops::Bound;
+
+use timely::order::PartialOrder;
+use timely::progress::Timestamp;
+use timely::progress::frontier::Antichain;
+use timely::dataflow::Scope;
+use timely::dataflow::operators::{Broadcast, Capability};
+use timely::dataflow::operators::generic::Operator;
+use timely::dataflow::channels::pact::Pipeline;
+
+use crate::{Data, ExchangeData, Collection, AsCollection, Hashable};
+use crate::difference::{Semigroup, Multiply};
+use crate::lattice::Lattice;
+use crate::operators::arrange::{Arranged, ArrangeByKey};
+use crate::operators::arrange::arrangement::arrange_core;
+use crate::trace::{BatchReader, Cursor, TraceReader};
+use crate::trace::implementations::{ValBatcher, ValBuilder, ValSpine};
+
+/// Join implementations matching keys against ranges of keys.
+pub trait RangeJoin<G: Scope, K: Data, V: Data, R: Semigroup> {
+    /// Matches pairs `(key1,val1)` and `(key2,val2)` where `key1` lies in the range `interval(key2)`.
+    ///
+    /// The lower bound of `interval(key2)` must be monotone in `key2`, as described in the module
+    /// documentation. The second collection is broadcast to all workers, each of which matches it
+    /// against its own part of the first collection, so the second collection should be the smaller.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound;
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::RangeJoin;
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     let x = scope.new_collection_from(vec![(1, 'a'), (3, 'b'), (5, 'c')]).1;
+    ///     let y = scope.new_collection_from(vec![(2, 'x'), (4, 'y')]).1;
+    ///     let z = scope.new_collection_from(vec![('a', 'x'), ('b', 'x'), ('b', 'y'), ('c', 'y')]).1;
+    ///
+    ///     // match keys within distance one of each other.
+    ///     x.range_join(&y, |k2| (Bound::Included(k2 - 1), Bound::Included(k2 + 1)), |_k1, v1, _k2, v2| (*v1, *v2))
+    ///      .assert_eq(&z);
+    /// });
+    /// ```
+    fn range_join<K2, V2, R2, F, D, L>(&self, other: &Collection<G, (K2,V2), R2>, interval: F, result: L) -> Collection<G, D, <R as Multiply<R2>>::Output>
+    where
+        K2: ExchangeData,
+        V2: ExchangeData,
+        R2: ExchangeData+Semigroup,
+        R: Multiply<R2, Output: Semigroup+'static>,
+        F: FnMut(&K2)->(Bound<K>, Bound<K>)+'static,
+        D: Data,
+        L: FnMut(&K,&V,&K2,&V2)->D+'static;
+}
+
+impl<G, K, V, R> RangeJoin<G, K, V, R> for Collection<G, (K, V), R>
+where
+    G: Scope<Timestamp: Lattice+Ord>,
+    K: ExchangeData+Hashable,
+    V: ExchangeData,
+    R: ExchangeData+Semigroup,
+{
+    fn range_join<K2, V2, R2, F, D, L>(&self, other: &Collection<G, (K2,V2), R2>, interval: F, mut result: L) -> Collection<G, D, <R as Multiply<R2>>::Output>
+    where
+        K2: ExchangeData,
+        V2: ExchangeData,
+        R2: ExchangeData+Semigroup,
+        R: Multiply<R2, Output: Semigroup+'static>,
+        F: FnMut(&K2)->(Bound<K>, Bound<K>)+'static,
+        D: Data,
+        L: FnMut(&K,&V,&K2,&V2)->D+'static,
+    {
+        let arranged1 = self.arrange_by_key();
+        let arranged2 = arrange_core::<_,_,ValBatcher<K2,V2,G::Timestamp,R2>,ValBuilder<_,_,_,_>,ValSpine<_,_,_,_>>(&other.inner.broadcast(), Pipeline, "RangeJoinBroadcast");
+        join_ranges(&arranged1, &arranged2, interval, move |k1,v1,k2,v2| Some(result(k1,v1,k2,v2)))
+    }
+}
+
+/// Matches the elements of two arranged traces, where the key of the first lies in a range determined by the key of the second.
+///
+/// Each worker matches the contents of its part of `arranged1` against its part of `arranged2`, and it is the
+/// responsibility of the caller to ensure that all matching pairs are present on some common worker, for
+/// example by broadcasting one of the inputs. The lower bound of `interval(key2)` must be monotone in `key2`.
+pub fn join_ranges<G, T1, T2, K1, K2, F, I, L>(arranged1: &Arranged<G,T1>, arranged2: &Arranged<G,T2>, mut interval: F, mut result: L) -> Collection<G, I::Item, <T1::Diff as Multiply<T2::Diff>>::Output>
+where
+    G: Scope<Timestamp=T1::Time>,
+    T1: for<'a> TraceReader<Key<'a>=&'a K1>+Clone+'static,
+    T2: for<'a> TraceReader<Key<'a>=&'a K2, Time=T1::Time>+Clone+'static,
+    K1: Ord+'static,
+    K2: Ord+Clone+'static,
+    T1::Diff: Multiply<T2::Diff, Output: Semigroup+'static>,
+    F: FnMut(&K2)->(Bound<K1>, Bound<K1>)+'static,
+    I: IntoIterator<Item: Data>,
+    L: FnMut(&K1,T1::Val<'_>,&K2,T2::Val<'_>)->I+'static,
+{
+    // Rename traces for symmetry from here on out.
+    let mut trace1 = arranged1.trace.clone();
+    let mut trace2 = arranged2.trace.clone();
+
+    arranged1.stream.binary_frontier(&arranged2.stream, Pipeline, Pipeline, "RangeJoin", move |capability, info| {
+
+        // Acquire an activator to reschedule the operator when it has unfinished work.
+        use timely::scheduling::Activator;
+        let activations = arranged1.stream.scope().activations().clone();
+        let activator = Activator::new(info.address, activations);
+
+        // Acknowledged frontier for each input, with the same role as in `join_traces`.
+        let mut acknowledged1 = Antichain::from_elem(<G::Timestamp>::minimum());
+        let mut acknowledged2 = Antichain::from_elem(<G::Timestamp>::minimum());
+
+        // Deferred work for batches from each input, against the acknowledged part of the other trace.
+        let mut todo1 = VecDeque::new();
+        let mut todo2 = VecDeque::new();
+
+        // Unload the initial batches, joining those of `trace2` against all of `trace1`.
+        trace1.map_batches(|batch1| { acknowledged1.clone_from(batch1.upper()); });
+        assert!(PartialOrder::less_equal(&trace1.get_physical_compaction(), &acknowledged1.borrow()));
+        let mut batches2 = Vec::new();
+        trace2.map_batches(|batch2| {
+            acknowledged2.clone_from(batch2.upper());
+            batches2.push(batch2.clone());
+        });
+        assert!(PartialOrder::less_equal(&trace2.get_physical_compaction(), &acknowledged2.borrow()));
+        for batch2 in batches2 {
+            let (trace1_cursor, trace1_storage) = trace1.cursor_through(acknowledged1.borrow()).unwrap();
+            todo2.push_back(RangeDeferred::new(trace1_cursor, trace1_storage, batch2.cursor(), batch2, false, capability.clone()));
+        }
+
+        // Droppable handles to shared trace data structures.
+        let mut trace1_option = Some(trace1);
+        let mut trace2_option = Some(trace2);
+
+        let mut buffer = Vec::new();
+
+        move |input1, input2, output| {
+
+            // Drain input 1, prepare work.
+            input1.for_each(|capability, data| {
+                if let Some(ref mut trace2) = trace2_option {
+                    let capability = capability.retain();
+                    for batch1 in data.drain(..) {
+                        // Ignore any pre-loaded data.
+                        if PartialOrder::less_equal(&acknowledged1, batch1.lower()) {
+                            if !batch1.is_empty() {
+                                let (trace2_cursor, trace2_storage) = trace2.cursor_through(acknowledged2.borrow()).unwrap();
+                                todo1.push_back(RangeDeferred::new(batch1.cursor(), batch1.clone(), trace2_cursor, trace2_storage, true, capability.clone()));
+                            }
+                            acknowledged1.clone_from(batch1.upper());
+                        }
+                    }
+                }
+                else { panic!("`trace2_option` dropped before `input1` emptied!"); }
+            });
+
+            // Drain input 2, prepare work.
+            input2.for_each(|capability, data| {
+                if let Some(ref mut trace1) = trace1_option {
+                    let capability = capability.retain();
+                    for batch2 in data.drain(..) {
+                        // Ignore any pre-loaded data.
+                        if PartialOrder::less_equal(&acknowledged2, batch2.lower()) {
+                            if !batch2.is_empty() {
+                                let (trace1_cursor, trace1_storage) = trace1.cursor_through(acknowledged1.borrow()).unwrap();
+                                todo2.push_back(RangeDeferred::new(trace1_cursor, trace1_storage, batch2.cursor(), batch2.clone(), false, capability.clone()));
+                            }
+                            acknowledged2.clone_from(batch2.upper());
+                        }
+                    }
+                }
+                else { panic!("`trace1_option` dropped before `input2` emptied!"); }
+            });
+
+            // Advance acknowledged frontiers through any empty regions that we may not receive as batches.
+            if let Some(trace1) = trace1_option.as_mut() {
+                trace1.advance_upper(&mut acknowledged1);
+            }
+            if let Some(trace2) = trace2_option.as_mut() {
+                trace2.advance_upper(&mut acknowledged2);
+            }
+
+            // New keys of `input1` sweep through the ranges of `trace2`, as fuel allows.
+            let mut fuel = 1_000_000;
+            while let (Some(deferred), true) = (todo1.front_mut(), fuel > 0) {
+                deferred.work(&mut interval, &mut result, &mut buffer, &mut fuel);
+                output.session(&deferred.capability).give_iterator(buffer.drain(..));
+                if !deferred.work_remains() { todo1.pop_front(); }
+            }
+
+            // New ranges of `input2` seek and scan through the keys of `trace1`, as fuel allows.
+            let mut fuel = 1_000_000;
+            while let (Some(deferred), true) = (todo2.front_mut(), fuel > 0) {
+                deferred.work(&mut interval, &mut result, &mut buffer, &mut fuel);
+                output.session(&deferred.capability).give_iterator(buffer.drain(..));
+                if !deferred.work_remains() { todo2.pop_front(); }
+            }
+
+            // Re-activate operator if work remains.
+            if !todo1.is_empty() || !todo2.is_empty() {
+                activator.activate();
+            }
+
+            // Maintain `trace1`. Drop if `input2` is empty, or advance based on future needs.
+            if let Some(trace1) = trace1_option.as_mut() {
+                if input2.frontier().is_empty() { trace1_option = None; }
+                else {
+                    trace1.set_logical_compaction(input2.frontier().frontier());
+                    trace1.set_physical_compaction(acknowledged1.borrow());
+                }
+            }
+
+            // Maintain `trace2`. Drop if `input1` is empty, or advance based on future needs.
+            if let Some(trace2) = trace2_option.as_mut() {
+                if input1.frontier().is_empty() { trace2_option = None; }
+                else {
+                    trace2.set_logical_compaction(input1.frontier().frontier());
+                    trace2.set_physical_compaction(acknowledged2.borrow());
+                }
+            }
+        }
+    })
+    .as_collection()
+}
+
+/// Deferred range join computation for a batch of updates, against the accepted contents of the other trace.
+///
+/// The keys of `points` are matched against the ranges of the keys of `intervals`, one of which is the batch.
+/// When the batch holds the points, they sweep once through the intervals. When the batch holds the intervals,
+/// each seeks to the start of its range among the points and scans forward, and a partly scanned range resumes
+/// where it stopped.
+struct RangeDeferred<T, C1, C2, K1, K2>
+where
+    T: Timestamp+Lattice+Ord,
+    C1: for<'a> Cursor<Key<'a>=&'a K1, Time=T>,
+    C2: for<'a> Cursor<Key<'a>=&'a K2, Time=T>,
+{
+    points: C1,
+    points_storage: C1::Storage,
+    intervals: C2,
+    intervals_storage: C2::Storage,
+    /// Whether the batch holds the points, rather than the intervals.
+    sweep: bool,
+    /// When sweeping, the admitted keys of `intervals` whose ranges have not yet ended, with their upper bounds.
+    active: Vec<(K2, Bound<K1>)>,
+    /// When sweeping, the next key of `intervals` to consider for admission, if any remain.
+    next: Option<K2>,
+    /// When seeking, the range of the current key of `intervals`, if it has been partly scanned.
+    scanning: Option<(Bound<K1>, Bound<K1>)>,
+    capability: Capability<T>,
+    done: bool,
+}
+
+impl<T, C1, C2, K1, K2> RangeDeferred<T, C1, C2, K1, K2>
+where
+    T: Timestamp+Lattice+Ord,
+    C1: for<'a> Cursor<Key<'a>=&'a K1, Time=T>,
+    C2: for<'a> Cursor<Key<'a>=&'a K2, Time=T>,
+    K1: Ord,
+    K2: Ord+Clone,
+{
+    fn new(points: C1, points_storage: C1::Storage, intervals: C2, intervals_storage: C2::Storage, sweep: bool, capability: Capability<T>) -> Self {
+        let next = if sweep { intervals.get_key(&intervals_storage).cloned() } else { None };
+        RangeDeferred {
+            points,
+            points_storage,
+            intervals,
+            intervals_storage,
+            sweep,
+            active: Vec::new(),
+            next,
+            scanning: None,
+            capability,
+            done: false,
+        }
+    }
+
+    fn work_remains(&self) -> bool {
+        !self.done
+    }
+
+    /// Process keys until at least `fuel` output tuples produced, or the work is exhausted.
+    #[inline(never)]
+    fn work<F, I, L>(&mut self, interval: &mut F, result: &mut L, output: &mut Vec<(I::Item, T, <C1::Diff as Multiply<C2::Diff>>::Output)>, fuel: &mut usize)
+    where
+        C1::Diff: Multiply<C2::Diff>,
+        F: FnMut(&K2)->(Bound<K1>, Bound<K1>),
+        I: IntoIterator<Item: Data>,
+        L: FnMut(&K1,C1::Val<'_>,&K2,C2::Val<'_>)->I,
+    {
+        let meet = self.capability.time();
+        let start = output.len();
+
+        let points_storage = &self.points_storage;
+        let intervals_storage = &self.intervals_storage;
+        let points = &mut self.points;
+        let intervals = &mut self.intervals;
+
+        if self.sweep {
+            // As the keys of `points` increase, keys of `intervals` are admitted once their range starts at or before
+            // the key of `points`, which relies on the monotonicity of lower bounds, and retired once their range ends
+            // before it.
+            while let (Some(key1), true) = (points.get_key(points_storage), output.len() - start < *fuel) {
+
+                if let Some(next) = self.next.take() {
+                    intervals.rewind_keys(intervals_storage);
+                    intervals.seek_key(intervals_storage, &next);
+                    while let Some(key2) = intervals.get_key(intervals_storage) {
+                        let (lower, upper) = interval(key2);
+                        if !after_lower(&lower, key1) { break; }
+                        self.active.push((key2.clone(), upper));
+                        intervals.step_key(intervals_storage);
+                    }
+                    self.next = intervals.get_key(intervals_storage).cloned();
+                }
+
+                // Keys of `points` only increase, so ranges that have ended are not needed again.
+                self.active.retain(|(_, upper)| before_upper(upper, key1));
+
+                for (key2, _) in self.active.iter() {
+                    intervals.rewind_keys(intervals_storage);
+                    intervals.seek_key(intervals_storage, key2);
+                    join_keys(points, points_storage, key1, intervals, intervals_storage, key2, meet, result, output);
+                }
+
+                points.step_key(points_storage);
+            }
+            self.done = !points.key_valid(points_storage);
+        }
+        else {
+            while let (Some(key2), true) = (intervals.get_key(intervals_storage), output.len() - start < *fuel) {
+
+                // Resume a partly scanned range, or seek to the start of a new one.
+                let (lower, upper) = match self.scanning.take() {
+                    Some(range) => range,
+                    None => {
+                        let (lower, upper) = interval(key2);
+                        points.rewind_keys(points_storage);
+                        if let Bound::Included(lower) | Bound::Excluded(lower) = &lower {
+                            points.seek_key(points_storage, lower);
+                        }
+                        (lower, upper)
+                    }
+                };
+
+                let mut ended = true;
+                while let Some(key1) = points.get_key(points_storage) {
+                    if !before_upper(&upper, key1) { break; }
+                    if output.len() - start >= *fuel { ended = false; break; }
+                    if after_lower(&lower, key1) {
+                        join_keys(points, points_storage, key1, intervals, intervals_storage, key2, meet, result, output);
+                    }
+                    points.step_key(points_storage);
+                }
+
+                if ended { intervals.step_key(intervals_storage); }
+                else { self.scanning = Some((lower, upper)); }
+            }
+            self.done = !intervals.key_valid(intervals_storage);
+        }
+
+        let effort = output.len() - start;
+        if effort > *fuel { *fuel = 0; }
+        else              { *fuel -= effort; }
+    }
+}
+
+/// Indicates whether `key` is at or after the lower bound `lower`.
+fn after_lower<K: Ord>(lower: &Bound<K>, key: &K) -> bool {
+    match lower {
+        Bound::Included(lower) => lower <= key,
+        Bound::Excluded(lower) => lower < key,
+        Bound::Unbounded => true,
+    }
+}
+
+/// Indicates whether `key` is before the upper bound `upper`.
+fn before_upper<K: Ord>(upper: &Bound<K>, key: &K) -> bool {
+    match upper {
+        Bound::Included(upper) => key <= upper,
+        Bound::Excluded(upper) => key < upper,
+        Bound::Unbounded => true,
+    }
+}
+
+/// Produces the joined updates for the current keys of two cursors.
+#[allow(clippy::too_many_arguments)]
+fn join_keys<C1, C2, K1, K2, I, L>(
+    cursor1: &mut C1,
+    storage1: &C1::Storage,
+    key1: &K1,
+    cursor2: &mut C2,
+    storage2: &C2::Storage,
+    key2: &K2,
+    meet: &C1::Time,
+    result: &mut L,
+    buffer: &mut Vec<(I::Item, C1::Time, <C1::Diff as Multiply<C2::Diff>>::Output)>,
+)
+where
+    C1: Cursor,
+    C2: Cursor<Time=C1::Time>,
+    C1::Diff: Multiply<C2::Diff>,
+    I: IntoIterator<Item: Data>,
+    L: FnMut(&K1,C1::Val<'_>,&K2,C2::Val<'_>)->I,
+{
+    let mut times1 = Vec::new();
+    let mut times2 = Vec::new();
+    cursor1.rewind_vals(storage1);
+    while let Some(val1) = cursor1.get_val(storage1) {
+        times1.clear();
+        cursor1.map_times(storage1, |time, diff| times1.push((C1::owned_time(time).join(meet), C1::owned_diff(diff))));
+        cursor2.rewind_vals(storage2);
+        while let Some(val2) = cursor2.get_val(storage2) {
+            times2.clear();
+            cursor2.map_times(storage2, |time, diff| times2.push((C2::owned_time(time).join(meet), C2::owned_diff(diff))));
+            for datum in result(key1, val1, key2, val2) {
+                for (time1, diff1) in times1.iter() {
+                    for (time2, diff2) in times2.iter() {
+                        buffer.push((datum.clone(), time1.join(time2), diff1.clone().multiply(diff2)));
+                    }
+                }
+            }
+            cursor2.step_val(storage2);
+        }
+        cursor1.step_val(storage1);
+    }
+}
@@ -0,0 +1,114 @@
+//! Joins that remain balanced across workers in the presence of heavy keys.
+//!
+//! The `join` operator exchanges records by the hash of their key, and so all records with the same key
+//! are joined by the same worker. When a few keys carry most of the records, the workers responsible for
+//! them do most of the work. The `skew_join` operator instead identifies heavy keys, and joins records
+//! with heavy keys by spreading the records of the first input across all workers and broadcasting the
+//! records of the second input to all workers. Records with light keys are joined as by `join`.
+//!
+//! Heavy keys are determined by the number of records in the first input, or supplied by the caller.
+//! Both inputs are additionally arranged without exchange on each worker, in order to classify their
+//! records as light or heavy without moving them, and re-classify them if the heavy keys change. These
+//! local arrangements cannot serve the joins, which need records exchanged by key or spread by value,
+//! and so each record is arranged twice: once locally, and once by the join for light or heavy keys.
+//! The records of the second input with heavy keys are arranged on every worker, and so the second
+//! input should be the one with fewer records for its heavy keys.
+
+use std::hash::Hash;
+
+use timely::dataflow::Scope;
+use timely::dataflow::operators::Broadcast;
+use timely::dataflow::channels::pact::{Exchange, Pipeline};
+
+use crate::{Data, ExchangeData, Collection, Hashable};
+use crate::lattice::Lattice;
+use crate::operators::{Join, Threshold};
+use crate::operators::arrange::arrangement::arrange_core;
+use crate::trace::implementations::{ValBatcher, ValBuilder, ValSpine};
+
+/// Join implementations that spread the work for heavy keys across workers.
+pub trait SkewJoin<G: Scope<Timestamp: Lattice+Ord>, K: ExchangeData, V: ExchangeData> {
+    /// Matches pairs `(key,val1)` and `(key,val2)` based on `key`, as `join` does.
+    ///
+    /// Keys with at least `threshold` records in `self` are treated as heavy, and their records are
+    /// joined by all workers rather than by the one worker responsible for the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::{Join, SkewJoin};
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     let x = scope.new_collection_from((0 .. 100).map(|x| (x % 3, x))).1;
+    ///     let y = scope.new_collection_from(vec![(0, 'a'), (1, 'b'), (1, 'c')]).1;
+    ///
+    ///     x.skew_join(&y, 10)
+    ///      .assert_eq(&x.join(&y));
+    /// });
+    /// ```
+    fn skew_join<V2: ExchangeData>(&self, other: &Collection<G, (K, V2), isize>, threshold: isize) -> Collection<G, (K, (V, V2)), isize> {
+        let heavy = self.heavy_keys(threshold);
+        self.skew_join_core(other, &heavy, |key, val1, val2| (key.clone(), (val1.clone(), val2.clone())))
+    }
+
+    /// The keys with at least `threshold` records, each with multiplicity one.
+    ///
+    /// Each worker accumulates the counts of its keys before exchanging them, so that heavy keys
+    /// contribute few updates to the worker that determines their totals.
+    fn heavy_keys(&self, threshold: isize) -> Collection<G, K, isize>;
+
+    /// Matches pairs `(key,val1)` and `(key,val2)` based on `key` and then applies a function,
+    /// treating the keys in `heavy` as heavy.
+    ///
+    /// The `heavy` collection is broadcast to all workers, and should be small. Keys present in it with
+    /// any positive multiplicity are heavy, which allows it to come from any source, for example from
+    /// `count_total` or from a sketch maintained outside of the dataflow.
+    fn skew_join_core<V2, D, L>(&self, other: &Collection<G, (K, V2), isize>, heavy: &Collection<G, K, isize>, logic: L) -> Collection<G, D, isize>
+    where
+        V2: ExchangeData,
+        D: Data,
+        L: FnMut(&K, &V, &V2)->D+Clone+'static;
+}
+
+impl<G, K, V> SkewJoin<G, K, V> for Collection<G, (K, V), isize>
+where
+    G: Scope<Timestamp: Lattice+Ord>,
+    K: ExchangeData+Hash,
+    V: ExchangeData+Hash,
+{
+    fn heavy_keys(&self, threshold: isize) -> Collection<G, K, isize> {
+        self.map(|(key, _)| key)
+            .consolidate_stream()
+            .threshold_named("HeavyKeys", move |_key, count| if *count >= threshold { 1 } else { 0 })
+    }
+
+    fn skew_join_core<V2, D, L>(&self, other: &Collection<G, (K, V2), isize>, heavy: &Collection<G, K, isize>, mut logic: L) -> Collection<G, D, isize>
+    where
+        V2: ExchangeData,
+        D: Data,
+        L: FnMut(&K, &V, &V2)->D+Clone+'static,
+    {
+        let heavy = heavy.distinct().map(|key| (key, ()));
+        let heavy = arrange_core::<_,_,ValBatcher<K,(),G::Timestamp,isize>,ValBuilder<_,_,_,_>,ValSpine<_,_,_,_>>(&heavy.inner.broadcast(), Pipeline, "SkewJoinHeavyKeys");
+
+        // Classify the records of each input as light or heavy, without exchanging them.
+        let local1 = arrange_core::<_,_,ValBatcher<K,V,G::Timestamp,isize>,ValBuilder<_,_,_,_>,ValSpine<_,_,_,_>>(&self.inner, Pipeline, "SkewJoinLocal1");
+        let heavy1 = local1.join_core(&heavy, |key, val, &()| Some((key.clone(), val.clone())));
+        let light1 = self.concat(&heavy1.negate());
+        let local2 = arrange_core::<_,_,ValBatcher<K,V2,G::Timestamp,isize>,ValBuilder<_,_,_,_>,ValSpine<_,_,_,_>>(&other.inner, Pipeline, "SkewJoinLocal2");
+        let heavy2 = local2.join_core(&heavy, |key, val, &()| Some((key.clone(), val.clone())));
+        let light2 = other.concat(&heavy2.negate());
+
+        let light = light1.join_map(&light2, logic.clone());
+
+        // Spread the heavy records of `self` by key and value, and broadcast the heavy records of `other`.
+        let exchange = Exchange::new(|((key, val), _, _): &((K, V), G::Timestamp, isize)| (key, val).hashed().into());
+        let spread1 = arrange_core::<_,_,ValBatcher<K,V,G::Timestamp,isize>,ValBuilder<_,_,_,_>,ValSpine<_,_,_,_>>(&heavy1.inner, exchange, "SkewJoinSpread");
+        let broadcast2 = arrange_core::<_,_,ValBatcher<K,V2,G::Timestamp,isize>,ValBuilder<_,_,_,_>,ValSpine<_,_,_,_>>(&heavy2.inner.broadcast(), Pipeline, "SkewJoinBroadcast");
+        let heavy = spread1.join_core(&broadcast2, move |key, val1, val2| Some(logic(key, val1, val2)));
+
+        light.concat(&heavy)
+    }
+}
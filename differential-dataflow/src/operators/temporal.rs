@@ -0,0 +1,143 @@
+//! Temporal filters and windows driven by record-embedded timestamps.
+//!
+//! These operators translate times carried in the data into updates at logical times: a record
+//! that should be visible over an interval `[lower, upper)` is inserted at `lower` and retracted
+//! at `upper`. Insertions and retractions of the input record are translated alike, which keeps
+//! the operators linear and stateless; the consolidation of the resulting future updates is left
+//! to downstream arrangements.
+//!
+//! The operators require totally ordered times, for which the interval `[lower, upper)` has its
+//! intuitive meaning. Each update is never moved to a time before its own time, so that a record
+//! that arrives after its interval has closed produces no output at all.
+
+use std::ops::{Add, Rem, Sub};
+
+use timely::order::{PartialOrder, TotalOrder};
+use timely::dataflow::Scope;
+use timely::dataflow::operators::Map;
+
+use crate::{Data, Collection, AsCollection};
+use crate::difference::Abelian;
+use crate::lattice::Lattice;
+
+/// Extension trait for temporal filters and windows.
+pub trait Temporal<G: Scope<Timestamp: TotalOrder+Lattice>, D: Data, R: Abelian> {
+    /// Restricts each record to the interval of times reported by `logic`.
+    ///
+    /// The function `logic` returns a lower bound and an optional upper bound; each record is
+    /// present in the output from its lower bound (or its own time, if later) until its upper
+    /// bound, and indefinitely if there is no upper bound.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::Temporal;
+    ///
+    /// ::timely::example(|scope| {
+    ///     // each `x` is present from time `x` until time `2 * x`.
+    ///     scope.new_collection_from(1 .. 10u64).1
+    ///          .temporal_filter(|x| (*x, Some(2 * x)));
+    /// });
+    /// ```
+    fn temporal_filter<L>(&self, logic: L) -> Collection<G, D, R>
+    where
+        L: FnMut(&D)->(G::Timestamp, Option<G::Timestamp>)+'static;
+
+    /// Retains each record for `size` time units after the timestamp reported by `logic`.
+    ///
+    /// This is a sliding window in the sense that at any time `t` the collection contains those
+    /// records whose timestamp lies in `(t - size, t]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::Temporal;
+    ///
+    /// ::timely::example(|scope| {
+    ///     // each `x` is present from time `x` until time `x + 5`.
+    ///     scope.new_collection_from(1 .. 10u64).1
+    ///          .sliding_window(5, |x| *x);
+    /// });
+    /// ```
+    fn sliding_window<L>(&self, size: G::Timestamp, mut logic: L) -> Collection<G, D, R>
+    where
+        G::Timestamp: Add<Output=G::Timestamp>,
+        L: FnMut(&D)->G::Timestamp+'static,
+    {
+        self.temporal_filter(move |data| {
+            let lower = logic(data);
+            let upper = lower.clone() + size.clone();
+            (lower, Some(upper))
+        })
+    }
+
+    /// Assigns each record to the tumbling window of width `width` containing the timestamp reported by `logic`.
+    ///
+    /// Each record is paired with the start of its window, and is present in the output from the start of
+    /// its window (or its own time, if later) until the start of the next window.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::Temporal;
+    ///
+    /// ::timely::example(|scope| {
+    ///     // each `x` is paired with `x - x % 4`, and present until time `x - x % 4 + 4`.
+    ///     scope.new_collection_from(1 .. 10u64).1
+    ///          .tumbling_window(4, |x| *x);
+    /// });
+    /// ```
+    fn tumbling_window<L>(&self, width: G::Timestamp, logic: L) -> Collection<G, (G::Timestamp, D), R>
+    where
+        G::Timestamp: Data+Add<Output=G::Timestamp>+Sub<Output=G::Timestamp>+Rem<Output=G::Timestamp>,
+        L: FnMut(&D)->G::Timestamp+'static;
+}
+
+impl<G, D, R> Temporal<G, D, R> for Collection<G, D, R>
+where
+    G: Scope<Timestamp: TotalOrder+Lattice>,
+    D: Data,
+    R: Abelian+'static,
+{
+    fn temporal_filter<L>(&self, mut logic: L) -> Collection<G, D, R>
+    where
+        L: FnMut(&D)->(G::Timestamp, Option<G::Timestamp>)+'static,
+    {
+        self.inner
+            .flat_map(move |(data, time, diff)| {
+                let (lower, upper) = logic(&data);
+                let lower = time.join(&lower);
+                let upper = upper.map(|upper| time.join(&upper));
+                let mut results = Vec::with_capacity(2);
+                match upper {
+                    // The interval is empty once clamped to the update's time.
+                    Some(upper) if upper.less_equal(&lower) => { },
+                    Some(upper) => {
+                        let mut negated = diff.clone();
+                        negated.negate();
+                        results.push((data.clone(), lower, diff));
+                        results.push((data, upper, negated));
+                    },
+                    None => { results.push((data, lower, diff)); },
+                }
+                results
+            })
+            .as_collection()
+    }
+
+    fn tumbling_window<L>(&self, width: G::Timestamp, mut logic: L) -> Collection<G, (G::Timestamp, D), R>
+    where
+        G::Timestamp: Data+Add<Output=G::Timestamp>+Sub<Output=G::Timestamp>+Rem<Output=G::Timestamp>,
+        L: FnMut(&D)->G::Timestamp+'static,
+    {
+        let width2 = width.clone();
+        self.map(move |data| {
+                let time = logic(&data);
+                (time.clone() - (time % width.clone()), data)
+            })
+            .temporal_filter(move |(start, _data)| (start.clone(), Some(start.clone() + width2.clone())))
+    }
+}
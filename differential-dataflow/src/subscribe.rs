@@ -0,0 +1,144 @@
+//! Subscriptions to the changes of a collection.
+//!
+//! A subscription is a handle from which the worker can pull the consolidated updates of a collection,
+//! one completed timestamp at a time, as well as the accumulated contents of the collection at the
+//! completed timestamps. This replaces the pattern of buffering updates observed with `inspect` and
+//! consulting a `probe` to learn when the buffered updates for a time are complete.
+//!
+//! Each worker observes only those updates that flow through it. To observe all updates at a single
+//! worker, first exchange the updates of the collection to that worker.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use timely::dataflow::Scope;
+use timely::dataflow::channels::pact::Pipeline;
+use timely::dataflow::operators::generic::Operator;
+use timely::progress::{Antichain, Timestamp};
+
+use crate::{Collection, Data};
+use crate::consolidation::consolidate;
+use crate::difference::Semigroup;
+
+/// State shared between the subscribe operator and its handle.
+struct SubscribeState<T, D, R> {
+    /// Consolidated updates at completed times not yet yielded, in increasing order of time.
+    ready: VecDeque<(T, Vec<(D, R)>)>,
+    /// Updates at all completed times, consolidated on demand.
+    snapshot: Vec<(D, R)>,
+    /// The length of `snapshot` after its most recent consolidation.
+    consolidated: usize,
+    /// Times not greater or equal to some element of the frontier are complete.
+    frontier: Antichain<T>,
+}
+
+impl<T, D: Ord, R: Semigroup> SubscribeState<T, D, R> {
+    /// Consolidates `snapshot` if it has at least doubled in length since its last consolidation.
+    fn maintain_snapshot(&mut self) {
+        if self.snapshot.len() > 2 * self.consolidated {
+            consolidate(&mut self.snapshot);
+            self.consolidated = self.snapshot.len();
+        }
+    }
+}
+
+/// A handle to the updates of a subscribed collection.
+///
+/// The handle is an iterator over pairs of a completed time and the consolidated updates at that time.
+/// Times are yielded once the frontier of the collection has passed them, in increasing order of time
+/// among those that complete together. The iterator returns `None` when no completed times are ready,
+/// which does not mean that no further times will complete; the `done` method indicates the latter.
+pub struct SubscribeHandle<T, D, R> {
+    state: Rc<RefCell<SubscribeState<T, D, R>>>,
+}
+
+impl<T: Timestamp, D: Data, R: Semigroup> SubscribeHandle<T, D, R> {
+    /// The frontier of the subscribed collection, as last observed by the subscription.
+    ///
+    /// All times not greater or equal to an element of the frontier are complete.
+    pub fn frontier(&self) -> Antichain<T> {
+        self.state.borrow().frontier.clone()
+    }
+
+    /// Indicates that the collection will produce no further updates.
+    pub fn done(&self) -> bool {
+        self.state.borrow().frontier.is_empty()
+    }
+
+    /// The consolidated contents of the collection accumulated through all completed times.
+    ///
+    /// The snapshot includes updates at completed times that have not yet been yielded by the handle.
+    pub fn snapshot(&self) -> Vec<(D, R)> {
+        let mut state = self.state.borrow_mut();
+        consolidate(&mut state.snapshot);
+        state.consolidated = state.snapshot.len();
+        state.snapshot.clone()
+    }
+}
+
+impl<T, D, R> Iterator for SubscribeHandle<T, D, R> {
+    type Item = (T, Vec<(D, R)>);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.state.borrow_mut().ready.pop_front()
+    }
+}
+
+/// Subscribes to the updates of `collection`, as described for `Collection::subscribe`.
+pub fn subscribe<G, D, R>(collection: &Collection<G, D, R>) -> SubscribeHandle<G::Timestamp, D, R>
+where
+    G: Scope,
+    D: Data,
+    R: Semigroup+'static,
+{
+    let state = Rc::new(RefCell::new(SubscribeState {
+        ready: VecDeque::new(),
+        snapshot: Vec::new(),
+        consolidated: 0,
+        frontier: Antichain::from_elem(<G::Timestamp as Timestamp>::minimum()),
+    }));
+
+    let shared = Rc::clone(&state);
+    let mut pending = Vec::new();
+    let mut complete = Vec::new();
+    collection.inner.sink(Pipeline, "Subscribe", move |input| {
+
+        input.for_each(|_capability, data| {
+            pending.extend(data.drain(..));
+        });
+
+        let mut state = shared.borrow_mut();
+        if state.frontier.borrow() != input.frontier().frontier() {
+
+            // Extract updates at times the frontier has passed, ordered by time.
+            let frontier = input.frontier();
+            let mut index = 0;
+            while index < pending.len() {
+                if !frontier.less_equal(&pending[index].1) {
+                    let (data, time, diff) = pending.swap_remove(index);
+                    complete.push(((time, data), diff));
+                }
+                else {
+                    index += 1;
+                }
+            }
+            consolidate(&mut complete);
+
+            let mut drain = complete.drain(..).peekable();
+            while let Some(((time, data), diff)) = drain.next() {
+                let mut updates = vec![(data, diff)];
+                while let Some(((_, data), diff)) = drain.next_if(|((next, _), _)| next == &time) {
+                    updates.push((data, diff));
+                }
+                state.snapshot.extend(updates.iter().cloned());
+                state.ready.push_back((time, updates));
+            }
+            state.maintain_snapshot();
+
+            state.frontier.clear();
+            state.frontier.extend(frontier.frontier().iter().cloned());
+        }
+    });
+
+    SubscribeHandle { state }
+}
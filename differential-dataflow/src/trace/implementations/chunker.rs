@@ -2,6 +2,7 @@
 
 use std::collections::VecDeque;
 
+use columnar::Columnar;
 use columnation::Columnation;
 use timely::Container;
 use timely::container::{ContainerBuilder, PushInto, SizableContainer};
@@ -9,6 +10,7 @@ use timely::container::{ContainerBuilder, PushInto, SizableContainer};
 use crate::containers::TimelyStack;
 use crate::consolidation::{consolidate_updates, ConsolidateLayout};
 use crate::difference::Semigroup;
+use crate::trace::implementations::columnar::Column;
 
 /// Chunk a stream of vectors into chains of vectors.
 pub struct VecChunker<T> {
@@ -124,6 +126,211 @@ where
     }
 }
 
+/// Types whose order is led by an unsigned integer, suitable for radix sorting.
+///
+/// Implementors must ensure that `a.radix_key() < b.radix_key()` implies `a < b`.
+/// Elements with equal radix keys are ordered by `Ord`.
+pub trait RadixKey {
+    /// An unsigned integer consistent with the type's order.
+    fn radix_key(&self) -> u64;
+}
+
+macro_rules! radix_key_implementation {
+    ($t:ty) => {
+        impl RadixKey for $t {
+            #[inline] fn radix_key(&self) -> u64 { *self as u64 }
+        }
+    };
+}
+
+radix_key_implementation!(u8);
+radix_key_implementation!(u16);
+radix_key_implementation!(u32);
+radix_key_implementation!(u64);
+radix_key_implementation!(usize);
+
+/// Chunk a stream of vectors into chains of vectors, using radix sorting by key.
+///
+/// Updates are sorted by the `radix_key()` of their keys, and then by `Ord` among updates
+/// with equal radix keys. This is most effective for unsigned integer keys, and for keys
+/// ordered by `Hashable::hashed()`, such as `rhh::HashWrapper`. Radix digits that are the
+/// same for all keys in a chunk are skipped, so small integers need only a few passes.
+pub struct RadixChunker<T> {
+    pending: Vec<T>,
+    ready: VecDeque<Vec<T>>,
+    empty: Option<Vec<T>>,
+    /// Staging for radix-keyed updates, retained to avoid re-allocation.
+    keyed: Vec<(u64, T)>,
+    /// Buckets for each radix digit, retained to avoid re-allocation.
+    buckets: Vec<Vec<(u64, T)>>,
+}
+
+impl<T> Default for RadixChunker<T> {
+    fn default() -> Self {
+        Self {
+            pending: Vec::default(),
+            ready: VecDeque::default(),
+            empty: None,
+            keyed: Vec::default(),
+            buckets: Vec::default(),
+        }
+    }
+}
+
+impl<K, V, T, R> RadixChunker<((K, V), T, R)>
+where
+    K: RadixKey + Ord,
+    V: Ord,
+    T: Ord,
+    R: Semigroup,
+{
+    const BUFFER_SIZE_BYTES: usize = 8 << 10;
+    fn chunk_capacity() -> usize {
+        let size = ::std::mem::size_of::<((K, V), T, R)>();
+        if size == 0 {
+            Self::BUFFER_SIZE_BYTES
+        } else if size <= Self::BUFFER_SIZE_BYTES {
+            Self::BUFFER_SIZE_BYTES / size
+        } else {
+            1
+        }
+    }
+
+    /// Sorts and consolidates `self.pending`.
+    ///
+    /// The updates are least-significant-digit radix sorted by key, one byte at a time, after
+    /// which only runs of equal radix keys need comparison-based sorting. Equal (data, time)
+    /// pairs are then adjacent, and are consolidated in a single linear pass.
+    fn consolidate_pending(&mut self) {
+        self.keyed.extend(self.pending.drain(..).map(|update| ((update.0).0.radix_key(), update)));
+
+        // Bytes that differ among the keys; only these digits need sorting passes.
+        let (mut ones, mut zeros) = (0u64, 0u64);
+        for (key, _) in self.keyed.iter() {
+            ones |= *key;
+            zeros |= !*key;
+        }
+        let varying = ones & zeros;
+
+        if self.buckets.is_empty() {
+            self.buckets.resize_with(256, Vec::new);
+        }
+        for digit in (0 .. 8).filter(|digit| (varying >> (8 * digit)) & 0xFF != 0) {
+            for (key, update) in self.keyed.drain(..) {
+                self.buckets[((key >> (8 * digit)) & 0xFF) as usize].push((key, update));
+            }
+            for bucket in self.buckets.iter_mut() {
+                self.keyed.append(bucket);
+            }
+        }
+
+        // Sort each run of equal radix keys, which is only needed for runs of more than one update.
+        let mut start = 0;
+        while start < self.keyed.len() {
+            let key = self.keyed[start].0;
+            let end = start + self.keyed[start ..].iter().take_while(|(other, _)| *other == key).count();
+            if end - start > 1 {
+                self.keyed[start .. end].sort_unstable_by(|(_, x), (_, y)| (&x.0, &x.1).cmp(&(&y.0, &y.1)));
+            }
+            start = end;
+        }
+
+        // Accumulate adjacent updates with equal data and time, discarding those that accumulate to zero.
+        for (_, update) in self.keyed.drain(..) {
+            if let Some(last) = self.pending.last_mut() {
+                if last.0 == update.0 && last.1 == update.1 {
+                    last.2.plus_equals(&update.2);
+                    continue;
+                }
+                if last.2.is_zero() {
+                    self.pending.pop();
+                }
+            }
+            self.pending.push(update);
+        }
+        if self.pending.last().map(|last| last.2.is_zero()).unwrap_or(false) {
+            self.pending.pop();
+        }
+    }
+
+    /// Form chunks out of pending data, if needed. This function is meant to be applied to
+    /// potentially full buffers, and ensures that if the buffer was full when called it is at most
+    /// half full when the function returns.
+    ///
+    /// `form_chunk` does the following:
+    /// * If pending is full, consolidate.
+    /// * If after consolidation it's more than half full, peel off chunks,
+    ///   leaving behind any partial chunk in pending.
+    fn form_chunk(&mut self) {
+        self.consolidate_pending();
+        if self.pending.len() >= Self::chunk_capacity() {
+            while self.pending.len() > Self::chunk_capacity() {
+                let mut chunk = Vec::with_capacity(Self::chunk_capacity());
+                chunk.extend(self.pending.drain(..chunk.capacity()));
+                self.ready.push_back(chunk);
+            }
+        }
+    }
+}
+
+impl<'a, K, V, T, R> PushInto<&'a mut Vec<((K, V), T, R)>> for RadixChunker<((K, V), T, R)>
+where
+    K: RadixKey + Ord + Clone,
+    V: Ord + Clone,
+    T: Ord + Clone,
+    R: Semigroup + Clone,
+{
+    fn push_into(&mut self, container: &'a mut Vec<((K, V), T, R)>) {
+        // Ensure `self.pending` has the desired capacity. We should never have a larger capacity
+        // because we don't write more than capacity elements into the buffer.
+        // Important: Consolidation requires `pending` to have twice the chunk capacity to
+        // amortize its cost. Otherwise, it risks to do quadratic work.
+        if self.pending.capacity() < Self::chunk_capacity() * 2 {
+            self.pending.reserve(Self::chunk_capacity() * 2 - self.pending.len());
+        }
+
+        let mut drain = container.drain(..).peekable();
+        while drain.peek().is_some() {
+            self.pending.extend((&mut drain).take(self.pending.capacity() - self.pending.len()));
+            if self.pending.len() == self.pending.capacity() {
+                self.form_chunk();
+            }
+        }
+    }
+}
+
+impl<K, V, T, R> ContainerBuilder for RadixChunker<((K, V), T, R)>
+where
+    K: RadixKey + Ord + Clone + 'static,
+    V: Ord + Clone + 'static,
+    T: Ord + Clone + 'static,
+    R: Semigroup + Clone + 'static,
+{
+    type Container = Vec<((K, V), T, R)>;
+
+    fn extract(&mut self) -> Option<&mut Self::Container> {
+        if let Some(ready) = self.ready.pop_front() {
+            self.empty = Some(ready);
+            self.empty.as_mut()
+        } else {
+            None
+        }
+    }
+
+    fn finish(&mut self) -> Option<&mut Self::Container> {
+        if !self.pending.is_empty() {
+            self.consolidate_pending();
+            while !self.pending.is_empty() {
+                let mut chunk = Vec::with_capacity(Self::chunk_capacity());
+                chunk.extend(self.pending.drain(..std::cmp::min(self.pending.len(), chunk.capacity())));
+                self.ready.push_back(chunk);
+            }
+        }
+        self.empty = self.ready.pop_front();
+        self.empty.as_mut()
+    }
+}
+
 /// Chunk a stream of vectors into chains of vectors.
 pub struct ColumnationChunker<T: Columnation> {
     pending: Vec<T>,
@@ -312,3 +519,95 @@ where
         }
     }
 }
+
+/// Chunk a stream of `Column` containers into chains of sorted, consolidated containers.
+///
+/// Each input container is sorted and consolidated on its own, and becomes at most one chunk.
+pub struct ColumnarChunker<C> {
+    /// Buffer into which we'll consolidate.
+    ///
+    /// Also the buffer where we'll stage responses to `extract` and `finish`.
+    /// When these calls return, the buffer is available for reuse.
+    empty: C,
+    /// Consolidated buffers ready to go.
+    ready: VecDeque<C>,
+}
+
+impl<C: Default> Default for ColumnarChunker<C> {
+    fn default() -> Self {
+        Self {
+            empty: C::default(),
+            ready: VecDeque::default(),
+        }
+    }
+}
+
+impl<'a, D, T, R, C2> PushInto<&'a mut Column<(D, T, R)>> for ColumnarChunker<C2>
+where
+    D: Columnar,
+    for<'b> columnar::Ref<'b, D>: Ord,
+    T: Columnar,
+    for<'b> columnar::Ref<'b, T>: Ord,
+    R: Columnar + for<'b> Semigroup<columnar::Ref<'b, R>>,
+    for<'b> columnar::Ref<'b, R>: Ord,
+    C2: Container + for<'b, 'c> PushInto<(columnar::Ref<'b, D>, columnar::Ref<'b, T>, &'c R)>,
+{
+    fn push_into(&mut self, container: &'a mut Column<(D, T, R)>) {
+        // Scoped to let borrow through `permutation` drop.
+        {
+            // Sort input data.
+            let mut permutation = Vec::with_capacity(container.len());
+            permutation.extend(container.drain());
+            permutation.sort();
+
+            self.empty.clear();
+            // Iterate over the data, accumulating diffs for like keys.
+            let mut iter = permutation.drain(..);
+            if let Some((data, time, diff)) = iter.next() {
+
+                let mut prev_data = data;
+                let mut prev_time = time;
+                let mut prev_diff = <R as Columnar>::into_owned(diff);
+
+                for (data, time, diff) in iter {
+                    if (&prev_data, &prev_time) == (&data, &time) {
+                        prev_diff.plus_equals(&diff);
+                    }
+                    else {
+                        if !prev_diff.is_zero() {
+                            self.empty.push_into((prev_data, prev_time, &prev_diff));
+                        }
+                        prev_data = data;
+                        prev_time = time;
+                        prev_diff = <R as Columnar>::into_owned(diff);
+                    }
+                }
+
+                if !prev_diff.is_zero() {
+                    self.empty.push_into((prev_data, prev_time, &prev_diff));
+                }
+            }
+        }
+
+        if !self.empty.is_empty() {
+            self.ready.push_back(std::mem::take(&mut self.empty));
+        }
+    }
+}
+
+impl<C: Container + Clone + 'static> ContainerBuilder for ColumnarChunker<C> {
+    type Container = C;
+
+    fn extract(&mut self) -> Option<&mut Self::Container> {
+        if let Some(ready) = self.ready.pop_front() {
+            self.empty = ready;
+            Some(&mut self.empty)
+        } else {
+            None
+        }
+    }
+
+    fn finish(&mut self) -> Option<&mut Self::Container> {
+        self.extract()
+    }
+}
@@ -0,0 +1,467 @@
+//! Trace implementations backed by containers from the `columnar` crate.
+//!
+//! The `ColumnarLayout` stores keys, values, times, and diffs each in a `Coltainer`, a thin wrapper
+//! around a `columnar` container. Updates arrive in `Column` containers, which can be exchanged between
+//! workers as aligned bytes. They are sorted and consolidated by a `ColumnarChunker`, merged by a
+//! `ColumnarMerger`, and formed into batches by the standard `ord_neu` builders.
+//!
+//! The type aliases in this module bundle these together, for use with `arrange_core`:
+//!
+//! ```ignore
+//! let pact = ExchangeCore::<ColumnBuilder<((String,()),u64,i64)>,_>::new_core(|x: &((&str,()),&u64,&i64)| (x.0).0.len() as u64);
+//! let arranged = arrange_core::<_,_,ColumnarKeyBatcher<_,_,_>, ColumnarKeyBuilder<_,_,_>, ColumnarKeySpine<_,_,_>>(&stream, pact, "Data");
+//! ```
+
+use std::rc::Rc;
+
+use columnar::{Columnar, Ref};
+use timely::progress::Timestamp;
+
+use crate::difference::Semigroup;
+use crate::lattice::Lattice;
+use crate::trace::implementations::chunker::ColumnarChunker;
+use crate::trace::implementations::merge_batcher::{MergeBatcher, ColumnarMerger};
+use crate::trace::implementations::ord_neu::{OrdKeyBatch, OrdKeyBuilder, OrdValBatch, OrdValBuilder};
+use crate::trace::implementations::spine_fueled::Spine;
+use crate::trace::implementations::{BatchContainer, BuilderInput, OffsetList};
+use crate::trace::rc_blanket_impls::RcBuilder;
+
+pub use self::container::Column;
+pub use self::builder::ColumnBuilder;
+pub use self::layout::{ColumnarLayout, Coltainer};
+
+/// A trace implementation backed by `columnar` storage.
+pub type ColumnarValSpine<K, V, T, R> = Spine<Rc<OrdValBatch<ColumnarLayout<((K,V),T,R)>>>>;
+/// A batcher for `columnar` storage.
+pub type ColumnarValBatcher<K, V, T, R> = MergeBatcher<Column<((K,V),T,R)>, ColumnarChunker<Column<((K,V),T,R)>>, ColumnarMerger<(K,V),T,R>>;
+/// A builder for `columnar` storage.
+pub type ColumnarValBuilder<K, V, T, R> = RcBuilder<OrdValBuilder<ColumnarLayout<((K,V),T,R)>, Column<((K,V),T,R)>>>;
+
+/// A layout for `columnar` storage of keys, whose empty values are read as `&()` as key batches expect.
+pub type ColumnarKeyLayout<K, T, R> = (Coltainer<K>, Vec<()>, Coltainer<T>, Coltainer<R>, OffsetList);
+/// A trace implementation for empty values backed by `columnar` storage.
+pub type ColumnarKeySpine<K, T, R> = Spine<Rc<OrdKeyBatch<ColumnarKeyLayout<K,T,R>>>>;
+/// A batcher for `columnar` storage of keys.
+pub type ColumnarKeyBatcher<K, T, R> = MergeBatcher<Column<((K,()),T,R)>, ColumnarChunker<Column<((K,()),T,R)>>, ColumnarMerger<(K,()),T,R>>;
+/// A builder for `columnar` storage of keys.
+pub type ColumnarKeyBuilder<K, T, R> = RcBuilder<OrdKeyBuilder<ColumnarKeyLayout<K,T,R>, Column<((K,()),T,R)>>>;
+
+impl<K, V, T, R> BuilderInput<Coltainer<K>, Coltainer<V>> for Column<((K, V), T, R)>
+where
+    K: Columnar + Ord + Clone,
+    for<'a> Ref<'a, K>: Ord,
+    V: Columnar + Ord + Clone,
+    for<'a> Ref<'a, V>: Ord,
+    T: Columnar + Timestamp + Lattice + Clone,
+    R: Columnar + Ord + Semigroup,
+{
+    type Key<'a> = Ref<'a, K>;
+    type Val<'a> = Ref<'a, V>;
+    type Time = T;
+    type Diff = R;
+
+    fn into_parts<'a>(((key, val), time, diff): Self::Item<'a>) -> (Self::Key<'a>, Self::Val<'a>, Self::Time, Self::Diff) {
+        (key, val, T::into_owned(time), R::into_owned(diff))
+    }
+
+    fn key_eq(this: &Ref<'_, K>, other: Ref<'_, K>) -> bool {
+        Coltainer::<K>::reborrow(*this) == Coltainer::<K>::reborrow(other)
+    }
+
+    fn val_eq(this: &Ref<'_, V>, other: Ref<'_, V>) -> bool {
+        Coltainer::<V>::reborrow(*this) == Coltainer::<V>::reborrow(other)
+    }
+
+    fn key_val_upd_counts(chain: &[Self]) -> (usize, usize, usize) {
+        use timely::Container;
+        let mut keys = 0;
+        let mut vals = 0;
+        let mut upds = 0;
+        let mut prev_keyval = None;
+        for link in chain.iter() {
+            for ((key, val), _, _) in link.iter() {
+                if let Some((p_key, p_val)) = prev_keyval {
+                    if p_key != key {
+                        keys += 1;
+                        vals += 1;
+                    } else if p_val != val {
+                        vals += 1;
+                    }
+                } else {
+                    keys += 1;
+                    vals += 1;
+                }
+                upds += 1;
+                prev_keyval = Some((key, val));
+            }
+        }
+        (keys, vals, upds)
+    }
+}
+
+impl<K, T, R> BuilderInput<Coltainer<K>, Vec<()>> for Column<((K, ()), T, R)>
+where
+    K: Columnar + Ord + Clone,
+    for<'a> Ref<'a, K>: Ord,
+    T: Columnar + Timestamp + Lattice + Clone,
+    R: Columnar + Ord + Semigroup,
+{
+    type Key<'a> = Ref<'a, K>;
+    type Val<'a> = &'a ();
+    type Time = T;
+    type Diff = R;
+
+    fn into_parts<'a>(((key, _val), time, diff): Self::Item<'a>) -> (Self::Key<'a>, Self::Val<'a>, Self::Time, Self::Diff) {
+        (key, &(), T::into_owned(time), R::into_owned(diff))
+    }
+
+    fn key_eq(this: &Ref<'_, K>, other: Ref<'_, K>) -> bool {
+        Coltainer::<K>::reborrow(*this) == Coltainer::<K>::reborrow(other)
+    }
+
+    fn val_eq(_this: &&(), _other: &()) -> bool { true }
+
+    fn key_val_upd_counts(chain: &[Self]) -> (usize, usize, usize) {
+        use timely::Container;
+        let mut keys = 0;
+        let mut upds = 0;
+        let mut prev_key = None;
+        for link in chain.iter() {
+            for ((key, _), _, _) in link.iter() {
+                if prev_key != Some(key) {
+                    keys += 1;
+                }
+                upds += 1;
+                prev_key = Some(key);
+            }
+        }
+        (keys, keys, upds)
+    }
+}
+
+/// A `Layout` and `BatchContainer` based on `columnar` containers.
+pub mod layout {
+
+    use columnar::{Columnar, Clear, Index, Len, Push, Ref};
+    use timely::container::PushInto;
+
+    use crate::trace::implementations::{BatchContainer, Layout, OffsetList, Update};
+
+    /// A layout whose key, value, time, and diff containers are `columnar` containers.
+    pub struct ColumnarLayout<U: Update> {
+        phantom: std::marker::PhantomData<U>,
+    }
+
+    impl<U> Layout for ColumnarLayout<U>
+    where
+        U: Update<
+            Key: Columnar,
+            Val: Columnar,
+            Time: Columnar,
+            Diff: Columnar + Ord,
+        >,
+        for<'a> Ref<'a, U::Key>: Ord,
+        for<'a> Ref<'a, U::Val>: Ord,
+        for<'a> Ref<'a, U::Time>: Ord,
+        for<'a> Ref<'a, U::Diff>: Ord,
+    {
+        type KeyContainer = Coltainer<U::Key>;
+        type ValContainer = Coltainer<U::Val>;
+        type TimeContainer = Coltainer<U::Time>;
+        type DiffContainer = Coltainer<U::Diff>;
+        type OffsetContainer = OffsetList;
+    }
+
+    /// A `BatchContainer` backed by the `columnar` container for `C`.
+    ///
+    /// Items are read back as `columnar::Ref<C>`, for example `&str` for `String`.
+    pub struct Coltainer<C: Columnar> {
+        /// The underlying columnar container.
+        pub container: C::Container,
+    }
+
+    impl<C: Columnar> Default for Coltainer<C> {
+        fn default() -> Self { Self { container: Default::default() } }
+    }
+
+    impl<C: Columnar, T> PushInto<T> for Coltainer<C> where C::Container: Push<T> {
+        #[inline]
+        fn push_into(&mut self, item: T) { self.container.push(item) }
+    }
+
+    impl<C: Columnar + Ord + Clone> BatchContainer for Coltainer<C>
+    where
+        for<'a> Ref<'a, C>: Ord,
+    {
+        type Owned = C;
+        type ReadItem<'a> = Ref<'a, C>;
+
+        #[inline(always)] fn into_owned<'a>(item: Self::ReadItem<'a>) -> Self::Owned { C::into_owned(item) }
+        #[inline(always)] fn clone_onto<'a>(item: Self::ReadItem<'a>, other: &mut Self::Owned) { other.copy_from(item) }
+
+        #[inline(always)]
+        fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b> {
+            <C::Container as columnar::Container>::reborrow_ref(item)
+        }
+
+        fn push_ref(&mut self, item: Self::ReadItem<'_>) { self.container.push(item) }
+        fn push_own(&mut self, item: &Self::Owned) { self.container.push(item) }
+
+        fn clear(&mut self) { self.container.clear() }
+
+        // Columnar containers do not expose a way to reserve capacity by element count, as the space
+        // an element needs depends on its contents (for example, the bytes of a `String`). The sizes
+        // are only hints: builders push every item regardless, and the underlying vectors grow by
+        // doubling, so ignoring the hints costs amortized re-allocation rather than correctness.
+        fn with_capacity(_size: usize) -> Self { Self::default() }
+        fn merge_capacity(_cont1: &Self, _cont2: &Self) -> Self { Self::default() }
+
+        #[inline(always)]
+        fn index(&self, index: usize) -> Self::ReadItem<'_> {
+            use columnar::Container;
+            self.container.borrow().get(index)
+        }
+        #[inline(always)]
+        fn len(&self) -> usize { self.container.len() }
+    }
+}
+
+/// A container of `columnar` data, either typed or as the aligned bytes received from other workers.
+pub mod container {
+
+    use columnar::{Columnar, Clear, Len, Index, FromBytes};
+    use columnar::bytes::{EncodeDecode, Indexed};
+    use columnar::common::IterOwn;
+    use timely::bytes::arc::Bytes;
+
+    /// A container based on a columnar store, encoded in aligned bytes.
+    pub enum Column<C: Columnar> {
+        /// The typed variant of the container.
+        Typed(C::Container),
+        /// The binary variant of the container.
+        Bytes(Bytes),
+        /// Relocated, aligned binary data, if `Bytes` doesn't work for some reason.
+        ///
+        /// Reasons could include misalignment, cloning of data, or wanting
+        /// to release the `Bytes` as a scarce resource.
+        Align(Box<[u64]>),
+    }
+
+    impl<C: Columnar> Default for Column<C> {
+        fn default() -> Self { Self::Typed(Default::default()) }
+    }
+
+    impl<C: Columnar<Container: Clone>> Clone for Column<C> {
+        fn clone(&self) -> Self {
+            match self {
+                Column::Typed(t) => Column::Typed(t.clone()),
+                Column::Bytes(b) => {
+                    assert!(b.len() % 8 == 0);
+                    let mut alloc: Vec<u64> = vec![0; b.len() / 8];
+                    bytemuck::cast_slice_mut(&mut alloc[..]).copy_from_slice(&b[..]);
+                    Self::Align(alloc.into())
+                },
+                Column::Align(a) => Column::Align(a.clone()),
+            }
+        }
+    }
+
+    type BorrowedOf<'a, C> = <<C as Columnar>::Container as columnar::Container>::Borrowed<'a>;
+
+    impl<C: Columnar> Column<C> {
+        /// Borrows the contents, whichever variant holds them.
+        pub fn borrow(&self) -> BorrowedOf<'_, C> {
+            use columnar::Container;
+            match self {
+                Column::Typed(t) => t.borrow(),
+                Column::Bytes(b) => <BorrowedOf<C> as FromBytes>::from_bytes(&mut Indexed::decode(bytemuck::cast_slice(b))),
+                Column::Align(a) => <BorrowedOf<C> as FromBytes>::from_bytes(&mut Indexed::decode(a)),
+            }
+        }
+        /// Reads the item at `index`.
+        pub fn get(&self, index: usize) -> columnar::Ref<'_, C> {
+            self.borrow().get(index)
+        }
+        /// Converts binary contents to the typed variant by copying them, and returns the typed container.
+        pub fn typed(&mut self) -> &mut C::Container {
+            if !matches!(self, Column::Typed(_)) {
+                let mut typed = C::Container::default();
+                for item in self.borrow().into_index_iter() {
+                    columnar::Push::push(&mut typed, item);
+                }
+                *self = Column::Typed(typed);
+            }
+            match self {
+                Column::Typed(t) => t,
+                _ => unreachable!("converted to the typed variant"),
+            }
+        }
+    }
+
+    use timely::Container;
+    impl<C: Columnar> Container for Column<C> {
+        fn len(&self) -> usize { self.borrow().len() }
+        // This sets the `Bytes` variant to be an empty `Typed` variant, appropriate for pushing into.
+        fn clear(&mut self) {
+            match self {
+                Column::Typed(t) => t.clear(),
+                Column::Bytes(_) => *self = Column::Typed(Default::default()),
+                Column::Align(_) => *self = Column::Typed(Default::default()),
+            }
+        }
+
+        type ItemRef<'a> = columnar::Ref<'a, C>;
+        type Iter<'a> = IterOwn<BorrowedOf<'a, C>>;
+        fn iter(&self) -> Self::Iter<'_> { self.borrow().into_index_iter() }
+
+        type Item<'a> = columnar::Ref<'a, C>;
+        type DrainIter<'a> = IterOwn<BorrowedOf<'a, C>>;
+        fn drain(&mut self) -> Self::DrainIter<'_> { self.borrow().into_index_iter() }
+    }
+
+    use timely::container::SizableContainer;
+    impl<C: Columnar> SizableContainer for Column<C> {
+        fn at_capacity(&self) -> bool {
+            match self {
+                Self::Typed(t) => {
+                    use columnar::Container;
+                    let length_in_bytes = Indexed::length_in_bytes(&t.borrow());
+                    length_in_bytes >= (1 << 20)
+                },
+                Self::Bytes(_) => true,
+                Self::Align(_) => true,
+            }
+        }
+        fn ensure_capacity(&mut self, _stash: &mut Option<Self>) { }
+    }
+
+    use timely::container::PushInto;
+    impl<T, C: Columnar<Container: columnar::Push<T>>> PushInto<T> for Column<C> {
+        #[inline]
+        fn push_into(&mut self, item: T) {
+            use columnar::Push;
+            // Binary contents are rarely pushed into, as containers are cleared first, but are
+            // copied into the typed variant if they are.
+            self.typed().push(item)
+        }
+    }
+
+    use timely::dataflow::channels::ContainerBytes;
+    impl<C: Columnar> ContainerBytes for Column<C> {
+        fn from_bytes(bytes: Bytes) -> Self {
+            // Our expectation / hope is that `bytes` is `u64` aligned and sized.
+            // If the alignment is borked, we can relocate. IF the size is borked,
+            // not sure what we do in that case.
+            assert!(bytes.len() % 8 == 0);
+            if bytemuck::try_cast_slice::<_, u64>(&bytes).is_ok() {
+                Self::Bytes(bytes)
+            }
+            else {
+                let mut alloc: Vec<u64> = vec![0; bytes.len() / 8];
+                bytemuck::cast_slice_mut(&mut alloc[..]).copy_from_slice(&bytes[..]);
+                Self::Align(alloc.into())
+            }
+        }
+
+        fn length_in_bytes(&self) -> usize {
+            match self {
+                Column::Typed(t) => {
+                    use columnar::Container;
+                    Indexed::length_in_bytes(&t.borrow())
+                },
+                Column::Bytes(b) => b.len(),
+                Column::Align(a) => 8 * a.len(),
+            }
+        }
+
+        fn into_bytes<W: ::std::io::Write>(&self, writer: &mut W) {
+            match self {
+                Column::Typed(t) => {
+                    use columnar::Container;
+                    Indexed::write(writer, &t.borrow()).unwrap()
+                },
+                Column::Bytes(b) => writer.write_all(b).unwrap(),
+                Column::Align(a) => writer.write_all(bytemuck::cast_slice(a)).unwrap(),
+            }
+        }
+    }
+}
+
+/// A container builder that produces `Column` containers, for example to exchange data.
+pub mod builder {
+
+    use std::collections::VecDeque;
+
+    use columnar::{Columnar, Clear, Len, Push};
+    use columnar::bytes::{EncodeDecode, Indexed};
+    use timely::container::{ContainerBuilder, LengthPreservingContainerBuilder, PushInto};
+
+    use super::Column;
+
+    /// A container builder for `Column<C>`.
+    pub struct ColumnBuilder<C: Columnar> {
+        /// Container that we're writing to.
+        current: C::Container,
+        /// Empty allocation.
+        empty: Option<Column<C>>,
+        /// Completed containers pending to be sent.
+        pending: VecDeque<Column<C>>,
+    }
+
+    impl<T, C: Columnar<Container: Push<T>>> PushInto<T> for ColumnBuilder<C> {
+        #[inline]
+        fn push_into(&mut self, item: T) {
+            self.current.push(item);
+            // If there is less than 10% slop with 2MB backing allocations, mint a container.
+            use columnar::Container;
+            let words = Indexed::length_in_words(&self.current.borrow());
+            let round = (words + ((1 << 18) - 1)) & !((1 << 18) - 1);
+            if round - words < round / 10 {
+                let mut alloc = Vec::with_capacity(words);
+                Indexed::encode(&mut alloc, &self.current.borrow());
+                self.pending.push_back(Column::Align(alloc.into_boxed_slice()));
+                self.current.clear();
+            }
+        }
+    }
+
+    impl<C: Columnar> Default for ColumnBuilder<C> {
+        fn default() -> Self {
+            ColumnBuilder {
+                current: Default::default(),
+                empty: None,
+                pending: Default::default(),
+            }
+        }
+    }
+
+    impl<C: Columnar<Container: Clone>> ContainerBuilder for ColumnBuilder<C> {
+        type Container = Column<C>;
+
+        #[inline]
+        fn extract(&mut self) -> Option<&mut Self::Container> {
+            if let Some(container) = self.pending.pop_front() {
+                self.empty = Some(container);
+                self.empty.as_mut()
+            } else {
+                None
+            }
+        }
+
+        #[inline]
+        fn finish(&mut self) -> Option<&mut Self::Container> {
+            if !self.current.is_empty() {
+                use columnar::Container;
+                let words = Indexed::length_in_words(&self.current.borrow());
+                let mut alloc = Vec::with_capacity(words);
+                Indexed::encode(&mut alloc, &self.current.borrow());
+                self.pending.push_back(Column::Align(alloc.into_boxed_slice()));
+                self.current.clear();
+            }
+            self.empty = self.pending.pop_front();
+            self.empty.as_mut()
+        }
+    }
+
+    impl<C: Columnar<Container: Clone>> LengthPreservingContainerBuilder for ColumnBuilder<C> { }
+}
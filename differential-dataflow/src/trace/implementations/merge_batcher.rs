@@ -220,7 +220,7 @@ pub trait Merger: Default {
     fn account(chunk: &Self::Chunk) -> (usize, usize, usize, usize);
 }
 
-pub use container::{VecMerger, ColMerger};
+pub use container::{VecMerger, ColMerger, ColumnarMerger};
 
 pub mod container {
 
@@ -234,7 +234,7 @@ pub mod container {
     //! These two traits exist instead of a stack of constraints on the structure of the associated items
     //! of the containers, allowing them to perform their functions without destructuring their guts.
     //!
-    //! Standard implementations exist in the `vec`, `columnation`, and `flat_container` modules.
+    //! Standard implementations exist in the `vec`, `columnation`, and `columnar` modules.
 
     use std::cmp::Ordering;
     use std::marker::PhantomData;
@@ -599,4 +599,101 @@ pub mod container {
             }
         }
     }
+
+    pub use columnar::ColumnarMerger;
+    /// Implementations of `ContainerQueue` and `MergerChunk` for `Column` containers (columnar).
+    pub mod columnar {
+
+        use timely::Container;
+        use timely::progress::{Antichain, frontier::AntichainRef};
+        use columnar::Columnar;
+
+        use crate::difference::Semigroup;
+        use crate::trace::implementations::columnar::Column;
+
+        use super::{ContainerQueue, MergerChunk};
+
+        /// A `Merger` implementation backed by `Column` containers (columnar).
+        pub type ColumnarMerger<D, T, R> = super::ContainerMerger<Column<(D,T,R)>,ColumnQueue<(D, T, R)>>;
+
+        /// A `Column` container and the position of the next item to read from it.
+        pub struct ColumnQueue<T: Columnar> {
+            list: Column<T>,
+            head: usize,
+        }
+
+        impl<D, T, R> ContainerQueue<Column<(D, T, R)>> for ColumnQueue<(D, T, R)>
+        where
+            D: Columnar,
+            for<'a> columnar::Ref<'a, D>: Ord,
+            T: Columnar,
+            for<'a> columnar::Ref<'a, T>: Ord,
+            R: Columnar,
+        {
+            fn next_or_alloc(&mut self) -> Result<columnar::Ref<'_, (D, T, R)>, Column<(D, T, R)>> {
+                if self.is_empty() {
+                    Err(std::mem::take(&mut self.list))
+                }
+                else {
+                    Ok(self.pop())
+                }
+            }
+            fn is_empty(&self) -> bool {
+                self.head == self.list.len()
+            }
+            fn cmp_heads(&self, other: &Self) -> std::cmp::Ordering {
+                let (data1, time1, _) = self.peek();
+                let (data2, time2, _) = other.peek();
+                (data1, time1).cmp(&(data2, time2))
+            }
+            fn from(list: Column<(D, T, R)>) -> Self {
+                ColumnQueue { list, head: 0 }
+            }
+        }
+
+        impl<T: Columnar> ColumnQueue<T> {
+            fn pop(&mut self) -> columnar::Ref<'_, T> {
+                self.head += 1;
+                self.list.get(self.head - 1)
+            }
+
+            fn peek(&self) -> columnar::Ref<'_, T> {
+                self.list.get(self.head)
+            }
+        }
+
+        impl<D, T, R> MergerChunk for Column<(D, T, R)>
+        where
+            D: Columnar,
+            T: timely::PartialOrder + Clone + Columnar,
+            R: Default + Semigroup + Columnar,
+        {
+            type TimeOwned = T;
+            type DiffOwned = R;
+
+            fn time_kept((_, time, _): &Self::Item<'_>, upper: &AntichainRef<Self::TimeOwned>, frontier: &mut Antichain<Self::TimeOwned>) -> bool {
+                let time = T::into_owned(*time);
+                if upper.less_equal(&time) {
+                    frontier.insert(time);
+                    true
+                }
+                else { false }
+            }
+            fn push_and_add<'a>(&mut self, item1: Self::Item<'a>, item2: Self::Item<'a>, stash: &mut Self::DiffOwned) {
+                let (data, time, diff1) = item1;
+                let (_data, _time, diff2) = item2;
+                stash.copy_from(diff1);
+                let diff2: R = R::into_owned(diff2);
+                stash.plus_equals(&diff2);
+                if !stash.is_zero() {
+                    use timely::container::PushInto;
+                    self.push_into((data, time, &*stash));
+                }
+            }
+            fn account(&self) -> (usize, usize, usize, usize) {
+                // The byte-level sizes of columnar containers are not yet reported.
+                (self.len(), 0, 0, 0)
+            }
+        }
+    }
 }
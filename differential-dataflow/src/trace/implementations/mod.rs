@@ -45,6 +45,8 @@ pub mod ord_neu;
 pub mod rhh;
 pub mod huffman_container;
 pub mod chunker;
+pub mod spill;
+pub mod columnar;
 
 // Opinionated takes on default spines.
 pub use self::ord_neu::OrdValSpine as ValSpine;
@@ -12,7 +12,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::Hashable;
 use crate::containers::TimelyStack;
-use crate::trace::implementations::chunker::{ColumnationChunker, VecChunker};
+use crate::trace::implementations::chunker::{ColumnationChunker, RadixChunker, RadixKey, VecChunker};
 use crate::trace::implementations::merge_batcher::{MergeBatcher, VecMerger, ColMerger};
 use crate::trace::implementations::spine_fueled::Spine;
 use crate::trace::rc_blanket_impls::RcBuilder;
@@ -20,16 +20,23 @@ use crate::trace::rc_blanket_impls::RcBuilder;
 use super::{Layout, Vector, TStack};
 
 use self::val_batch::{RhhValBatch, RhhValBuilder};
+use self::key_batch::{RhhKeyBatch, RhhKeyBuilder};
 
 /// A trace implementation using a spine of ordered lists.
 pub type VecSpine<K, V, T, R> = Spine<Rc<RhhValBatch<Vector<((K,V),T,R)>>>>;
 /// A batcher for ordered lists.
 pub type VecBatcher<K,V,T,R> = MergeBatcher<Vec<((K,V),T,R)>, VecChunker<((K,V),T,R)>, VecMerger<(K, V), T, R>>;
+/// A batcher for ordered lists, radix sorting by key hash.
+pub type VecRadixBatcher<K,V,T,R> = MergeBatcher<Vec<((K,V),T,R)>, RadixChunker<((K,V),T,R)>, VecMerger<(K, V), T, R>>;
 /// A builder for ordered lists.
 pub type VecBuilder<K,V,T,R> = RcBuilder<RhhValBuilder<Vector<((K,V),T,R)>, Vec<((K,V),T,R)>>>;
 
-// /// A trace implementation for empty values using a spine of ordered lists.
-// pub type OrdKeySpine<K, T, R> = Spine<Rc<OrdKeyBatch<Vector<((K,()),T,R)>>>>;
+/// A trace implementation for empty values using a spine of ordered lists.
+pub type VecKeySpine<K, T, R> = Spine<Rc<RhhKeyBatch<Vector<((K,()),T,R)>>>>;
+/// A batcher for ordered lists of keys.
+pub type VecKeyBatcher<K,T,R> = MergeBatcher<Vec<((K,()),T,R)>, VecChunker<((K,()),T,R)>, VecMerger<(K, ()), T, R>>;
+/// A builder for ordered lists of keys.
+pub type VecKeyBuilder<K,T,R> = RcBuilder<RhhKeyBuilder<Vector<((K,()),T,R)>, Vec<((K,()),T,R)>>>;
 
 /// A trace implementation backed by columnar storage.
 pub type ColSpine<K, V, T, R> = Spine<Rc<RhhValBatch<TStack<((K,V),T,R)>>>>;
@@ -38,8 +45,12 @@ pub type ColBatcher<K,V,T,R> = MergeBatcher<Vec<((K,V),T,R)>, ColumnationChunker
 /// A builder for columnar storage.
 pub type ColBuilder<K,V,T,R> = RcBuilder<RhhValBuilder<TStack<((K,V),T,R)>, TimelyStack<((K,V),T,R)>>>;
 
-// /// A trace implementation backed by columnar storage.
-// pub type ColKeySpine<K, T, R> = Spine<Rc<OrdKeyBatch<TStack<((K,()),T,R)>>>>;
+/// A trace implementation for empty values backed by columnar storage.
+pub type ColKeySpine<K, T, R> = Spine<Rc<RhhKeyBatch<TStack<((K,()),T,R)>>>>;
+/// A batcher for columnar storage of keys.
+pub type ColKeyBatcher<K,T,R> = MergeBatcher<Vec<((K,()),T,R)>, ColumnationChunker<((K,()),T,R)>, ColMerger<(K,()),T,R>>;
+/// A builder for columnar storage of keys.
+pub type ColKeyBuilder<K,T,R> = RcBuilder<RhhKeyBuilder<TStack<((K,()),T,R)>, TimelyStack<((K,()),T,R)>>>;
 
 /// A carrier trait indicating that the type's `Ord` and `PartialOrd` implementations are by `Hashable::hashed()`.
 pub trait HashOrdered: Hashable { }
@@ -69,6 +80,10 @@ impl<T: Ord + PartialOrd + std::hash::Hash + Hashable<Output: PartialOrd>> Ord f
 
 impl<T: std::hash::Hash + Hashable> HashOrdered for HashWrapper<T> { }
 
+impl<T: std::hash::Hash + Hashable> RadixKey for HashWrapper<T> {
+    #[inline] fn radix_key(&self) -> u64 { self.inner.hashed().into() }
+}
+
 impl<T: std::hash::Hash + Hashable> Hashable for HashWrapper<T> {
     type Output = T::Output;
     fn hashed(&self) -> Self::Output { self.inner.hashed() }
@@ -888,6 +903,587 @@ mod val_batch {
 
 mod key_batch {
 
-    // Copy the above, once it works!
+    use std::convert::TryInto;
+    use std::marker::PhantomData;
+    use serde::{Deserialize, Serialize};
+    use timely::container::PushInto;
+    use timely::progress::{Antichain, frontier::AntichainRef};
+
+    use crate::hashable::Hashable;
+    use crate::trace::{Batch, BatchReader, Builder, Cursor, Description, Merger};
+    use crate::trace::implementations::{BatchContainer, BuilderInput};
+    use crate::trace::implementations::layout;
+
+    use super::{Layout, HashOrdered};
+
+    /// Update tuples organized as a Robin Hood Hash map, ordered by `(hash(Key), Key, Time)`.
+    ///
+    /// This is the key-only analogue of `RhhValStorage`, and places keys in the same way.
+    /// Each key indexes directly into its updates, and as with that storage a repeated
+    /// `keys_offs` offset indicates an absent element. This rules out the singleton update
+    /// optimization, which would borrow the same representation.
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct RhhKeyStorage<L: Layout>
+    where
+        layout::Key<L>: Default + HashOrdered,
+    {
+
+        /// The requested capacity for `keys`. We use this when determining where a key with a certain hash
+        /// would most like to end up. The `BatchContainer` trait does not provide a `capacity()` method,
+        /// otherwise we would just use that.
+        pub key_capacity: usize,
+        /// A number large enough that when it divides any `u64` the result is at most `self.key_capacity`.
+        /// When that capacity is zero or one, this is set to zero instead.
+        pub divisor: u64,
+        /// The number of present keys, distinct from `keys.len()` which contains dead space.
+        pub key_count: usize,
+
+        /// An ordered list of keys, corresponding to entries in `keys_offs`.
+        pub keys: L::KeyContainer,
+        /// Offsets used to provide indexes from keys to updates.
+        ///
+        /// The length of this list is one longer than `keys`, so that we can avoid bounds logic.
+        pub keys_offs: L::OffsetContainer,
+        /// Concatenated ordered lists of update times, bracketed by offsets in `keys_offs`.
+        pub times: L::TimeContainer,
+        /// Concatenated ordered lists of update diffs, bracketed by offsets in `keys_offs`.
+        pub diffs: L::DiffContainer,
+    }
+
+    impl<L: Layout> RhhKeyStorage<L>
+    where
+        layout::Key<L>: Default + HashOrdered,
+        for<'a> layout::KeyRef<'a, L>: HashOrdered,
+    {
+        /// Lower and upper bounds in `self.times` and `self.diffs` corresponding to the key at `index`.
+        fn updates_for_key(&self, index: usize) -> (usize, usize) {
+            let lower = self.keys_offs.index(index);
+            let upper = self.keys_offs.index(index+1);
+            // Looking up updates for an invalid key indicates something is wrong.
+            assert!(lower < upper, "{:?} v {:?} at {:?}", lower, upper, index);
+            (lower, upper)
+        }
+
+        /// Inserts the key at its desired location, or nearby.
+        ///
+        /// Because there may be collisions, they key may be placed just after its desired location.
+        /// If necessary, this method will introduce default keys and copy the offsets to create space
+        /// after which to insert the key.
+        ///
+        /// If `offset` is specified, we will insert it at the appropriate location. If it is not specified,
+        /// we leave `keys_offs` ready to receive it as the next `push`. This is so that builders that may
+        /// not know the final offset at the moment of key insertion can prepare for receiving the offset.
+        fn insert_key(&mut self, key: layout::KeyRef<'_, L>, offset: Option<usize>) {
+            let desired = self.desired_location(&key);
+            // Were we to push the key now, it would be at `self.keys.len()`, so while that is wrong,
+            // push additional blank entries in.
+            while self.keys.len() < desired {
+                // We insert a default (dummy) key and repeat the offset to indicate this.
+                let current_offset = self.keys_offs.index(self.keys.len());
+                self.keys.push_own(&<layout::Key<L> as Default>::default());
+                self.keys_offs.push_ref(current_offset);
+            }
+
+            // Now we insert the key. Even if it is no longer the desired location because of contention.
+            // If an offset has been supplied we insert it, and otherwise leave it for future determination.
+            self.keys.push_ref(key);
+            if let Some(offset) = offset {
+                self.keys_offs.push_ref(offset);
+            }
+            self.key_count += 1;
+        }
+
+        /// Inserts a key in any form the key container accepts, by way of a temporary container.
+        fn insert_key_into<K>(&mut self, key: K, offset: Option<usize>)
+        where
+            L::KeyContainer: PushInto<K>,
+        {
+            let mut key_con = L::KeyContainer::with_capacity(1);
+            key_con.push_into(key);
+            self.insert_key(key_con.index(0), offset)
+        }
+
+        /// Indicates both the desired location and the hash signature of the key.
+        fn desired_location<K: Hashable>(&self, key: &K) -> usize {
+            if self.divisor == 0 { 0 }
+            else {
+                (key.hashed().into() / self.divisor).try_into().expect("divisor not large enough to force u64 into uisze")
+            }
+        }
+
+        /// Returns true if one should advance one's index in the search for `key`.
+        fn advance_key(&self, index: usize, key: layout::KeyRef<'_, L>) -> bool {
+            // Ideally this short-circuits, as `self.keys[index]` is bogus data.
+            !self.live_key(index) || self.keys.index(index).lt(&<L::KeyContainer as BatchContainer>::reborrow(key))
+        }
+
+        /// Indicates that a key is valid, rather than dead space, by looking for a valid offset range.
+        fn live_key(&self, index: usize) -> bool {
+            self.keys_offs.index(index) != self.keys_offs.index(index+1)
+        }
+
+        /// Advances `index` until it references a live key, or is `keys.len()`.
+        fn advance_to_live_key(&self, index: &mut usize) {
+            while *index < self.keys.len() && !self.live_key(*index) {
+                *index += 1;
+            }
+        }
+
+        /// A value large enough that any `u64` divided by it is less than `capacity`.
+        ///
+        /// This is `2^64 / capacity`, except in the cases where `capacity` is zero or one.
+        /// In those cases, we'll return `0` to communicate the exception.
+        fn divisor_for_capacity(capacity: usize) -> u64 {
+            let capacity: u64 = capacity.try_into().expect("usize exceeds u64");
+            if capacity == 0 || capacity == 1 { 0 }
+            else {
+                ((1 << 63) / capacity) << 1
+            }
+        }
+    }
+
+    /// An immutable collection of update tuples, from a contiguous interval of logical times.
+    ///
+    /// The `L` parameter captures how the updates should be laid out.
+    #[derive(Serialize, Deserialize)]
+    #[serde(bound = "
+        L::KeyContainer: Serialize + for<'a> Deserialize<'a>,
+        L::OffsetContainer: Serialize + for<'a> Deserialize<'a>,
+        L::TimeContainer: Serialize + for<'a> Deserialize<'a>,
+        L::DiffContainer: Serialize + for<'a> Deserialize<'a>,
+    ")]
+    pub struct RhhKeyBatch<L: Layout>
+    where
+        layout::Key<L>: Default + HashOrdered,
+    {
+        /// The updates themselves.
+        pub storage: RhhKeyStorage<L>,
+        /// Description of the update times this layer represents.
+        pub description: Description<layout::Time<L>>,
+        /// The number of updates reflected in the batch.
+        pub updates: usize,
+    }
+
+    impl<L> WithLayout for RhhKeyBatch<L>
+    where
+        L: for<'a> Layout<ValContainer: BatchContainer<ReadItem<'a> = &'a ()>>,
+        layout::Key<L>: Default + HashOrdered,
+        for<'a> layout::KeyRef<'a, L>: HashOrdered,
+    {
+        type Layout = L;
+    }
+
+    impl<L> BatchReader for RhhKeyBatch<L>
+    where
+        L: for<'a> Layout<ValContainer: BatchContainer<ReadItem<'a> = &'a ()>>,
+        layout::Key<L>: Default + HashOrdered,
+        for<'a> layout::KeyRef<'a, L>: HashOrdered,
+    {
+        type Cursor = RhhKeyCursor<L>;
+        fn cursor(&self) -> Self::Cursor {
+            let mut cursor = RhhKeyCursor {
+                key_cursor: 0,
+                val_stepped: false,
+                phantom: std::marker::PhantomData,
+            };
+            cursor.rewind_keys(self);
+            cursor
+        }
+        fn len(&self) -> usize { self.updates }
+        fn description(&self) -> &Description<layout::Time<L>> { &self.description }
+    }
+
+    impl<L> Batch for RhhKeyBatch<L>
+    where
+        L: for<'a> Layout<ValContainer: BatchContainer<ReadItem<'a> = &'a ()>>,
+        layout::Key<L>: Default + HashOrdered,
+        for<'a> layout::KeyRef<'a, L>: HashOrdered,
+    {
+        type Merger = RhhKeyMerger<L>;
+
+        fn begin_merge(&self, other: &Self, compaction_frontier: AntichainRef<layout::Time<L>>) -> Self::Merger {
+            RhhKeyMerger::new(self, other, compaction_frontier)
+        }
+
+        fn empty(lower: Antichain<Self::Time>, upper: Antichain<Self::Time>) -> Self {
+            use timely::progress::Timestamp;
+            Self {
+                storage: RhhKeyStorage {
+                    keys: L::KeyContainer::with_capacity(0),
+                    keys_offs: L::OffsetContainer::with_capacity(0),
+                    times: L::TimeContainer::with_capacity(0),
+                    diffs: L::DiffContainer::with_capacity(0),
+                    key_count: 0,
+                    key_capacity: 0,
+                    divisor: 0,
+                },
+                description: Description::new(lower, upper, Antichain::from_elem(Self::Time::minimum())),
+                updates: 0,
+            }
+        }
+    }
+
+    /// State for an in-progress merge.
+    pub struct RhhKeyMerger<L: Layout>
+    where
+        layout::Key<L>: Default + HashOrdered,
+    {
+        /// Key position to merge next in the first batch.
+        key_cursor1: usize,
+        /// Key position to merge next in the second batch.
+        key_cursor2: usize,
+        /// result that we are currently assembling.
+        result: RhhKeyStorage<L>,
+        /// description
+        description: Description<layout::Time<L>>,
+
+        /// Local stash of updates, to use for consolidation.
+        update_stash: Vec<(layout::Time<L>, layout::Diff<L>)>,
+    }
+
+    impl<L> Merger<RhhKeyBatch<L>> for RhhKeyMerger<L>
+    where
+        L: for<'a> Layout<ValContainer: BatchContainer<ReadItem<'a> = &'a ()>>,
+        layout::Key<L>: Default + HashOrdered,
+        RhhKeyBatch<L>: Batch<Time=layout::Time<L>>,
+        for<'a> layout::KeyRef<'a, L>: HashOrdered,
+    {
+        fn new(batch1: &RhhKeyBatch<L>, batch2: &RhhKeyBatch<L>, compaction_frontier: AntichainRef<layout::Time<L>>) -> Self {
+
+            assert!(batch1.upper() == batch2.lower());
+            use crate::lattice::Lattice;
+            let mut since = batch1.description().since().join(batch2.description().since());
+            since = since.join(&compaction_frontier.to_owned());
+
+            let description = Description::new(batch1.lower().clone(), batch2.upper().clone(), since);
+
+            // As with values, a massive overestimate on the number of keys.
+            let max_cap = batch1.storage.key_count + batch2.storage.key_count;
+            let rhh_cap = 2 * max_cap;
+
+            let batch1 = &batch1.storage;
+            let batch2 = &batch2.storage;
+
+            let mut storage = RhhKeyStorage {
+                keys: L::KeyContainer::merge_capacity(&batch1.keys, &batch2.keys),
+                keys_offs: L::OffsetContainer::with_capacity(batch1.keys_offs.len() + batch2.keys_offs.len()),
+                times: L::TimeContainer::merge_capacity(&batch1.times, &batch2.times),
+                diffs: L::DiffContainer::merge_capacity(&batch1.diffs, &batch2.diffs),
+                key_count: 0,
+                key_capacity: rhh_cap,
+                divisor: RhhKeyStorage::<L>::divisor_for_capacity(rhh_cap),
+            };
+
+            // Mark explicit types because type inference fails to resolve it.
+            let keys_offs: &mut L::OffsetContainer = &mut storage.keys_offs;
+            keys_offs.push_ref(0);
+
+            RhhKeyMerger {
+                key_cursor1: 0,
+                key_cursor2: 0,
+                result: storage,
+                description,
+                update_stash: Vec::new(),
+            }
+        }
+        fn done(self) -> RhhKeyBatch<L> {
+            RhhKeyBatch {
+                updates: self.result.times.len(),
+                storage: self.result,
+                description: self.description,
+            }
+        }
+        fn work(&mut self, source1: &RhhKeyBatch<L>, source2: &RhhKeyBatch<L>, fuel: &mut isize) {
+
+            // An (incomplete) indication of the amount of work we've done so far.
+            let starting_updates = self.result.times.len();
+            let mut effort = 0isize;
+
+            source1.storage.advance_to_live_key(&mut self.key_cursor1);
+            source2.storage.advance_to_live_key(&mut self.key_cursor2);
+
+            // While both mergees are still active, perform single-key merges.
+            while self.key_cursor1 < source1.storage.keys.len() && self.key_cursor2 < source2.storage.keys.len() && effort < *fuel {
+                self.merge_key(&source1.storage, &source2.storage);
+                source1.storage.advance_to_live_key(&mut self.key_cursor1);
+                source2.storage.advance_to_live_key(&mut self.key_cursor2);
+                // An (incomplete) accounting of the work we've done.
+                effort = (self.result.times.len() - starting_updates) as isize;
+            }
+
+            // Merging is complete, and only copying remains.
+            // Key-by-key copying allows effort interruption, and compaction.
+            while self.key_cursor1 < source1.storage.keys.len() && effort < *fuel {
+                self.copy_key(&source1.storage, self.key_cursor1);
+                self.key_cursor1 += 1;
+                source1.storage.advance_to_live_key(&mut self.key_cursor1);
+                effort = (self.result.times.len() - starting_updates) as isize;
+            }
+            while self.key_cursor2 < source2.storage.keys.len() && effort < *fuel {
+                self.copy_key(&source2.storage, self.key_cursor2);
+                self.key_cursor2 += 1;
+                source2.storage.advance_to_live_key(&mut self.key_cursor2);
+                effort = (self.result.times.len() - starting_updates) as isize;
+            }
+
+            *fuel -= effort;
+        }
+    }
+
+    // Helper methods in support of merging batches.
+    impl<L: Layout> RhhKeyMerger<L>
+    where
+        layout::Key<L>: Default + HashOrdered,
+        for<'a> layout::KeyRef<'a, L>: HashOrdered,
+    {
+        /// Copy the next key in `source`.
+        ///
+        /// The method extracts the key in `source` at `cursor`, and merges it in to `self`.
+        /// If the result does not wholly cancel, they key will be present in `self` with the
+        /// compacted updates.
+        ///
+        /// The caller should be certain to update the cursor, as this method does not do this.
+        fn copy_key(&mut self, source: &RhhKeyStorage<L>, cursor: usize) {
+            self.stash_updates_for_key(source, cursor);
+            if let Some(off) = self.consolidate_updates() {
+                self.result.insert_key(source.keys.index(cursor), Some(off));
+            }
+        }
+        /// Merge the next key in each of `source1` and `source2` into `self`, updating the appropriate cursors.
+        ///
+        /// This method only merges a single key. It applies all compaction necessary, and may result in no output
+        /// if the updates cancel either directly or after compaction.
+        fn merge_key(&mut self, source1: &RhhKeyStorage<L>, source2: &RhhKeyStorage<L>) {
+
+            use ::std::cmp::Ordering;
+            match source1.keys.index(self.key_cursor1).cmp(&source2.keys.index(self.key_cursor2)) {
+                Ordering::Less => {
+                    self.copy_key(source1, self.key_cursor1);
+                    self.key_cursor1 += 1;
+                },
+                Ordering::Equal => {
+                    // Keys are equal; must merge all updates from both sources for this one key.
+                    self.stash_updates_for_key(source1, self.key_cursor1);
+                    self.stash_updates_for_key(source2, self.key_cursor2);
+                    if let Some(off) = self.consolidate_updates() {
+                        self.result.insert_key(source1.keys.index(self.key_cursor1), Some(off));
+                    }
+                    // Increment cursors in either case; the keys are merged.
+                    self.key_cursor1 += 1;
+                    self.key_cursor2 += 1;
+                },
+                Ordering::Greater => {
+                    self.copy_key(source2, self.key_cursor2);
+                    self.key_cursor2 += 1;
+                },
+            }
+        }
+
+        /// Transfer updates for an indexed key in `source` into `self`, with compaction applied.
+        fn stash_updates_for_key(&mut self, source: &RhhKeyStorage<L>, index: usize) {
+            let (lower, upper) = source.updates_for_key(index);
+            for i in lower .. upper {
+                let time = source.times.index(i);
+                let diff = source.diffs.index(i);
+                let mut new_time = L::TimeContainer::into_owned(time);
+                use crate::lattice::Lattice;
+                new_time.advance_by(self.description.since().borrow());
+                self.update_stash.push((new_time, L::DiffContainer::into_owned(diff)));
+            }
+        }
+
+        /// Consolidates `self.updates_stash` and produces the offset to record, if any.
+        fn consolidate_updates(&mut self) -> Option<usize> {
+            use crate::consolidation;
+            consolidation::consolidate(&mut self.update_stash);
+            if !self.update_stash.is_empty() {
+                for (time, diff) in self.update_stash.drain(..) {
+                    self.result.times.push_own(&time);
+                    self.result.diffs.push_own(&diff);
+                }
+                Some(self.result.times.len())
+            } else {
+                None
+            }
+        }
+    }
+
+    /// A cursor through a Robin Hood Hashed list of keys and their updates.
+    ///
+    /// As with `RhhValCursor`, not all of `keys` represent valid keys, and the cursor
+    /// skips over the invalid keys rather than report them through `key_valid`.
+    pub struct RhhKeyCursor<L: Layout>
+    where
+        layout::Key<L>: Default + HashOrdered,
+    {
+        /// Absolute position of the current key.
+        key_cursor: usize,
+        /// If the value has been stepped for the key, there are no more values.
+        val_stepped: bool,
+        /// Phantom marker for Rust happiness.
+        phantom: PhantomData<L>,
+    }
+
+    use crate::trace::implementations::WithLayout;
+    impl<L> WithLayout for RhhKeyCursor<L>
+    where
+        L: for<'a> Layout<ValContainer: BatchContainer<ReadItem<'a> = &'a ()>>,
+        layout::Key<L>: Default + HashOrdered,
+        for<'a> layout::KeyRef<'a, L>: HashOrdered,
+    {
+        type Layout = L;
+    }
+
+    impl<L> Cursor for RhhKeyCursor<L>
+    where
+        L: for<'a> Layout<ValContainer: BatchContainer<ReadItem<'a> = &'a ()>>,
+        layout::Key<L>: Default + HashOrdered,
+        for<'a> layout::KeyRef<'a, L>: HashOrdered,
+    {
+        type Storage = RhhKeyBatch<L>;
+
+        fn get_key<'a>(&self, storage: &'a RhhKeyBatch<L>) -> Option<Self::Key<'a>> { storage.storage.keys.get(self.key_cursor) }
+        fn get_val<'a>(&self, storage: &'a RhhKeyBatch<L>) -> Option<&'a ()> { if self.val_valid(storage) { Some(&()) } else { None } }
+        fn key<'a>(&self, storage: &'a RhhKeyBatch<L>) -> Self::Key<'a> { storage.storage.keys.index(self.key_cursor) }
+        fn val<'a>(&self, _storage: &'a RhhKeyBatch<L>) -> &'a () { &() }
+        fn map_times<L2: FnMut(Self::TimeGat<'_>, Self::DiffGat<'_>)>(&mut self, storage: &RhhKeyBatch<L>, mut logic: L2) {
+            let (lower, upper) = storage.storage.updates_for_key(self.key_cursor);
+            for index in lower .. upper {
+                let time = storage.storage.times.index(index);
+                let diff = storage.storage.diffs.index(index);
+                logic(time, diff);
+            }
+        }
+        fn key_valid(&self, storage: &RhhKeyBatch<L>) -> bool { self.key_cursor < storage.storage.keys.len() }
+        fn val_valid(&self, _storage: &RhhKeyBatch<L>) -> bool { !self.val_stepped }
+        fn step_key(&mut self, storage: &RhhKeyBatch<L>){
+            // We advance the cursor by one for certain, and then as long as we need to find a valid key.
+            self.key_cursor += 1;
+            storage.storage.advance_to_live_key(&mut self.key_cursor);
+
+            if self.key_valid(storage) {
+                self.rewind_vals(storage);
+            }
+            else {
+                self.key_cursor = storage.storage.keys.len();
+            }
+        }
+        fn seek_key(&mut self, storage: &RhhKeyBatch<L>, key: Self::Key<'_>) {
+            let desired = storage.storage.desired_location(&key);
+            // Advance the cursor, if `desired` is ahead of it.
+            if self.key_cursor < desired {
+                self.key_cursor = desired;
+            }
+            // Advance the cursor as long as we have not found a value greater or equal to `key`.
+            // We may have already passed `key`, and confirmed its absence, but our goal is to
+            // find the next key afterwards so that users can, for example, alternately iterate.
+            while self.key_valid(storage) && storage.storage.advance_key(self.key_cursor, key) {
+                self.key_cursor += 1;
+            }
+
+            if self.key_valid(storage) {
+                self.rewind_vals(storage);
+            }
+        }
+        fn step_val(&mut self, _storage: &RhhKeyBatch<L>) {
+            self.val_stepped = true;
+        }
+        fn seek_val(&mut self, _storage: &RhhKeyBatch<L>, _val: Self::Val<'_>) { }
+        fn rewind_keys(&mut self, storage: &RhhKeyBatch<L>) {
+            self.key_cursor = 0;
+            storage.storage.advance_to_live_key(&mut self.key_cursor);
+
+            if self.key_valid(storage) {
+                self.rewind_vals(storage)
+            }
+        }
+        fn rewind_vals(&mut self, _storage: &RhhKeyBatch<L>) {
+            self.val_stepped = false;
+        }
+    }
+
+    /// A builder for creating layers from unsorted update tuples.
+    pub struct RhhKeyBuilder<L: Layout, CI>
+    where
+        layout::Key<L>: Default + HashOrdered,
+    {
+        result: RhhKeyStorage<L>,
+        _marker: PhantomData<CI>,
+    }
+
+    impl<L: Layout, CI> Builder for RhhKeyBuilder<L, CI>
+    where
+        L: for<'a> Layout<
+            KeyContainer: PushInto<CI::Key<'a>>,
+            ValContainer: BatchContainer<ReadItem<'a> = &'a ()>,
+        >,
+        layout::Key<L>: Default + HashOrdered,
+        CI: BuilderInput<L::KeyContainer, L::ValContainer, Time=layout::Time<L>, Diff=layout::Diff<L>>,
+        for<'a> layout::KeyRef<'a, L>: HashOrdered,
+    {
+        type Input = CI;
+        type Time = layout::Time<L>;
+        type Output = RhhKeyBatch<L>;
+
+        fn with_capacity(keys: usize, _vals: usize, upds: usize) -> Self {
+
+            // Double the capacity for RHH; probably excessive.
+            let rhh_capacity = 2 * keys;
+            let divisor = RhhKeyStorage::<L>::divisor_for_capacity(rhh_capacity);
+            // We want some additive slop, in case we spill over.
+            let keys = rhh_capacity + 10;
+
+            // We don't introduce zero offsets as they will be introduced by the first `push` call.
+            Self {
+                result: RhhKeyStorage {
+                    keys: L::KeyContainer::with_capacity(keys),
+                    keys_offs: L::OffsetContainer::with_capacity(keys + 1),
+                    times: L::TimeContainer::with_capacity(upds),
+                    diffs: L::DiffContainer::with_capacity(upds),
+                    key_count: 0,
+                    key_capacity: rhh_capacity,
+                    divisor,
+                },
+                _marker: PhantomData,
+            }
+        }
+
+        #[inline]
+        fn push(&mut self, chunk: &mut Self::Input) {
+            for item in chunk.drain() {
+                let (key, _val, time, diff) = CI::into_parts(item);
+                // Perhaps this is a continuation of an already received key.
+                if !self.result.keys.last().map(|k| CI::key_eq(&key, k)).unwrap_or(false) {
+                    // New key; complete representation of prior key.
+                    self.result.keys_offs.push_ref(self.result.times.len());
+                    // Insert the key, but with no specified offset.
+                    self.result.insert_key_into(key, None);
+                }
+                self.result.times.push_own(&time);
+                self.result.diffs.push_own(&diff);
+            }
+        }
+
+        #[inline(never)]
+        fn done(mut self, description: Description<Self::Time>) -> RhhKeyBatch<L> {
+            // Record the final offsets
+            self.result.keys_offs.push_ref(self.result.times.len());
+            RhhKeyBatch {
+                updates: self.result.times.len(),
+                storage: self.result,
+                description,
+            }
+        }
+
+        fn seal(chain: &mut Vec<Self::Input>, description: Description<Self::Time>) -> Self::Output {
+            let (keys, vals, upds) = Self::Input::key_val_upd_counts(&chain[..]);
+            let mut builder = Self::with_capacity(keys, vals, upds);
+            for mut chunk in chain.drain(..) {
+                builder.push(&mut chunk);
+            }
+
+            builder.done(description)
+        }
+    }
 
 }
@@ -0,0 +1,697 @@
+//! Batches whose contents may be spilled to local files.
+//!
+//! A `SpillBatch<B>` holds its updates as a sequence of chunks, each a batch `B` covering a contiguous
+//! range of keys, and each either resident in memory or written to a file. Batches produced by a
+//! `SpillBuilder` are a single resident chunk, as they are immediately shared with downstream operators.
+//! Merges whose inputs reach the spine level indicated by the worker's `SpillPolicy` write their result
+//! to a file a chunk at a time, as it is produced. As larger merged batches are also the ones least
+//! frequently merged, this moves the bulk of a trace's data out of memory while leaving recent batches
+//! resident.
+//!
+//! A spilled batch keeps in memory only the first key of each chunk and the chunk's place in the file.
+//! Cursors read only the chunks they visit: `seek_key` reads the one chunk that may contain the key, and
+//! stepping reads chunks in order as it reaches them. Chunks read through a copy of a batch remain in
+//! memory until that copy is dropped, and so a reader that visits every key holds the whole batch for as
+//! long as it holds its storage. Merges read their inputs and write their result one chunk at a time,
+//! rather than through these copies, and so hold at most a chunk of each. An arrangement then holds in
+//! memory its resident batches, a key for each spilled chunk, and the chunks its readers visit, and may
+//! index more data than fits in memory. The file of a spilled batch is removed once its last copy is
+//! dropped.
+//!
+//! The policy is installed per worker thread with `set_policy`, which should be called from within
+//! the worker closure before dataflows are constructed. Without a policy, no batches are spilled.
+//!
+//! Should writing a chunk fail, it and the chunks that follow it remain resident. Should reading a chunk
+//! fail, it reads as empty. Neither panics: the error is retained for `take_error`, which should be
+//! checked by users of spilling traces. `SpillBatch::try_chunk` reports read errors directly instead.
+
+use std::cell::{OnceCell, RefCell};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use timely::progress::{Antichain, frontier::AntichainRef};
+
+use crate::consolidation::consolidate;
+use crate::lattice::Lattice;
+use crate::trace::{Batch, BatchReader, Builder, Cursor, Description, Merger};
+use crate::trace::implementations::{LayoutExt, WithLayout};
+use crate::trace::implementations::spine_fueled::Spine;
+use crate::trace::implementations::ord_neu::{OrdValBatch, OrdValBuilder, OrdValBatcher, OrdKeyBatch, OrdKeyBuilder, OrdKeyBatcher};
+
+use super::Vector;
+
+/// A trace implementation whose large merged batches may be spilled to files.
+pub type SpillValSpine<K, V, T, R> = Spine<SpillBatch<OrdValBatch<Vector<((K,V),T,R)>>>>;
+/// A batcher for spilled ordered lists.
+pub type SpillValBatcher<K, V, T, R> = OrdValBatcher<K, V, T, R>;
+/// A builder for spilled ordered lists.
+pub type SpillValBuilder<K, V, T, R> = SpillBuilder<OrdValBuilder<Vector<((K,V),T,R)>, Vec<((K,V),T,R)>>>;
+
+/// A trace implementation for empty values whose large merged batches may be spilled to files.
+pub type SpillKeySpine<K, T, R> = Spine<SpillBatch<OrdKeyBatch<Vector<((K,()),T,R)>>>>;
+/// A batcher for spilled ordered lists.
+pub type SpillKeyBatcher<K, T, R> = OrdKeyBatcher<K, T, R>;
+/// A builder for spilled ordered lists.
+pub type SpillKeyBuilder<K, T, R> = SpillBuilder<OrdKeyBuilder<Vector<((K,()),T,R)>, Vec<((K,()),T,R)>>>;
+
+/// The number of updates in each chunk of a merged batch, absent a policy.
+const CHUNK_UPDATES: usize = 1 << 16;
+
+/// Determines which merged batches are spilled, and where to.
+#[derive(Clone, Debug)]
+pub struct SpillPolicy {
+    /// The directory in which to write spilled batches.
+    pub directory: PathBuf,
+    /// The least spine level whose merged batches are spilled.
+    ///
+    /// A batch of `len` updates occupies level `len.next_power_of_two().trailing_zeros()`.
+    pub min_level: usize,
+    /// The number of updates after which a merged batch starts a new chunk.
+    ///
+    /// Chunks end at key boundaries, and so may hold more updates. Each chunk is the unit that
+    /// cursors and merges read, and each retains its first key in memory.
+    pub chunk_updates: usize,
+}
+
+impl SpillPolicy {
+    /// Spill merged batches at or above `min_level` to files in `directory`.
+    pub fn new<P: Into<PathBuf>>(directory: P, min_level: usize) -> Self {
+        SpillPolicy { directory: directory.into(), min_level, chunk_updates: CHUNK_UPDATES }
+    }
+    /// Indicates whether a merged batch of `len` updates should be spilled.
+    pub fn should_spill(&self, len: usize) -> bool {
+        len > 0 && len.next_power_of_two().trailing_zeros() as usize >= self.min_level
+    }
+}
+
+thread_local! {
+    static POLICY: RefCell<Option<SpillPolicy>> = const { RefCell::new(None) };
+    static ERROR: RefCell<Option<std::io::Error>> = const { RefCell::new(None) };
+}
+
+/// Installs the spill policy for the current worker thread, returning any prior policy.
+///
+/// The policy applies to all merges subsequently started on this thread. Passing `None`
+/// stops further spilling, but does not reload batches that have already been spilled.
+pub fn set_policy(policy: Option<SpillPolicy>) -> Option<SpillPolicy> {
+    POLICY.with(|current| std::mem::replace(&mut *current.borrow_mut(), policy))
+}
+
+/// Returns the most recent error encountered while writing or reading a chunk on this worker thread.
+///
+/// Chunks that could not be written remain resident, and are otherwise unaffected. Chunks that
+/// could not be read have been read as empty.
+pub fn take_error() -> Option<std::io::Error> {
+    ERROR.with(|error| error.borrow_mut().take())
+}
+
+/// Retains `error` for `take_error`.
+fn report(error: std::io::Error) {
+    ERROR.with(|current| *current.borrow_mut() = Some(error));
+}
+
+/// Distinguishes the files written by this process.
+static SPILL_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// A file holding the spilled chunks of a batch, removed when dropped.
+struct SpillFile {
+    path: PathBuf,
+}
+
+impl SpillFile {
+    /// Creates a new file in `directory`, and a writer for it.
+    fn create(directory: &Path) -> std::io::Result<(Self, BufWriter<File>)> {
+        let count = SPILL_COUNT.fetch_add(1, Ordering::Relaxed);
+        let path = directory.join(format!("spill-{}-{}.bin", std::process::id(), count));
+        let writer = BufWriter::new(File::create(&path)?);
+        Ok((SpillFile { path }, writer))
+    }
+    /// Writes `chunk` at the end of the file, returning its length in bytes.
+    fn write<B: Serialize>(chunk: &B, writer: &mut BufWriter<File>) -> std::io::Result<u64> {
+        let length = bincode::serialized_size(chunk).map_err(std::io::Error::other)?;
+        bincode::serialize_into(&mut *writer, chunk).map_err(std::io::Error::other)?;
+        writer.flush()?;
+        Ok(length)
+    }
+    /// Reads the chunk of `length` bytes at `offset` in the file.
+    fn read<B: DeserializeOwned>(&self, offset: u64, length: u64) -> std::io::Result<B> {
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(offset))?;
+        bincode::deserialize_from(BufReader::new(file.take(length))).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Batches that can be built from sorted runs of owned updates, and so merged and spilled a chunk at a time.
+pub trait SpillChunk : Batch<KeyOwn: Ord+Clone> + Serialize + DeserializeOwned {
+    /// A builder of chunks from sorted and consolidated updates.
+    type Builder: Builder<Input = Vec<((Self::KeyOwn, Self::ValOwn), Self::Time, Self::Diff)>, Time = Self::Time, Output = Self>;
+}
+
+impl<K, V, T, R> SpillChunk for OrdValBatch<Vector<((K,V),T,R)>>
+where
+    OrdValBatch<Vector<((K,V),T,R)>>: Batch<KeyOwn = K, ValOwn = V, Time = T, Diff = R> + Serialize + DeserializeOwned,
+    OrdValBuilder<Vector<((K,V),T,R)>, Vec<((K,V),T,R)>>: Builder<Input = Vec<((K,V),T,R)>, Time = T, Output = OrdValBatch<Vector<((K,V),T,R)>>>,
+    K: Ord+Clone,
+{
+    type Builder = OrdValBuilder<Vector<((K,V),T,R)>, Vec<((K,V),T,R)>>;
+}
+
+impl<K, T, R> SpillChunk for OrdKeyBatch<Vector<((K,()),T,R)>>
+where
+    OrdKeyBatch<Vector<((K,()),T,R)>>: Batch<KeyOwn = K, ValOwn = (), Time = T, Diff = R> + Serialize + DeserializeOwned,
+    OrdKeyBuilder<Vector<((K,()),T,R)>, Vec<((K,()),T,R)>>: Builder<Input = Vec<((K,()),T,R)>, Time = T, Output = OrdKeyBatch<Vector<((K,()),T,R)>>>,
+    K: Ord+Clone,
+{
+    type Builder = OrdKeyBuilder<Vector<((K,()),T,R)>, Vec<((K,()),T,R)>>;
+}
+
+/// The location of a chunk of a batch.
+enum Chunk<B> {
+    /// The chunk is in memory.
+    Resident(Rc<B>),
+    /// The chunk occupies `length` bytes at `offset` in the batch's file.
+    Spilled { offset: u64, length: u64 },
+}
+
+/// The chunks of a batch, in key order.
+struct Chunks<B: BatchReader> {
+    /// The location of each chunk.
+    locations: Vec<Chunk<B>>,
+    /// The first key of each chunk after the first.
+    bounds: Vec<B::KeyOwn>,
+}
+
+/// A batch whose chunks are each either resident in memory, or spilled to a file.
+pub struct SpillBatch<B: BatchReader> {
+    /// Description of the update times the batch represents.
+    description: Description<B::Time>,
+    /// The number of updates in the batch.
+    len: usize,
+    /// The file holding the spilled chunks, if any.
+    file: Option<Rc<SpillFile>>,
+    /// The chunks of the batch, shared by all copies.
+    chunks: Rc<Chunks<B>>,
+    /// Spilled chunks read through this copy of the batch.
+    loaded: Box<[OnceCell<Rc<B>>]>,
+}
+
+impl<B: BatchReader> SpillBatch<B> {
+    /// Wraps a batch that remains resident in memory.
+    pub fn resident(batch: B) -> Self {
+        SpillBatch {
+            description: batch.description().clone(),
+            len: batch.len(),
+            file: None,
+            chunks: Rc::new(Chunks { locations: vec![Chunk::Resident(Rc::new(batch))], bounds: Vec::new() }),
+            loaded: vec![OnceCell::new()].into_boxed_slice(),
+        }
+    }
+    /// True if some chunks of the batch have been written to a file.
+    pub fn is_spilled(&self) -> bool { self.file.is_some() }
+    /// True if all chunks of this copy of the batch are in memory, either never spilled or since read.
+    pub fn is_resident(&self) -> bool {
+        self.chunks.locations.iter().zip(self.loaded.iter()).all(|(chunk, loaded)| matches!(chunk, Chunk::Resident(_)) || loaded.get().is_some())
+    }
+    /// The number of chunks in the batch.
+    pub fn chunks(&self) -> usize { self.chunks.locations.len() }
+    /// The chunk at `index`, read from the file into this copy of the batch if necessary.
+    pub fn try_chunk(&self, index: usize) -> std::io::Result<&B> where B: DeserializeOwned {
+        match &self.chunks.locations[index] {
+            Chunk::Resident(batch) => Ok(&**batch),
+            Chunk::Spilled { offset, length } => {
+                if self.loaded[index].get().is_none() {
+                    // Batches with spilled chunks are constructed with a file.
+                    let file = self.file.as_ref().expect("spilled chunk without a file");
+                    let _ = self.loaded[index].set(Rc::new(file.read(*offset, *length)?));
+                }
+                Ok(&**self.loaded[index].get().unwrap())
+            }
+        }
+    }
+}
+
+impl<B: SpillChunk> SpillBatch<B> {
+    /// The index of the chunk that would contain `key`.
+    fn seek_chunk(&self, key: B::Key<'_>) -> usize {
+        if self.chunks.bounds.is_empty() { 0 }
+        else {
+            let key = B::owned_key(key);
+            self.chunks.bounds.partition_point(|bound| bound <= &key)
+        }
+    }
+    /// The chunk at `index`, read as empty should reading fail.
+    fn chunk(&self, index: usize) -> &B {
+        match self.try_chunk(index) {
+            Ok(batch) => batch,
+            Err(error) => {
+                report(error);
+                &**self.loaded[index].get_or_init(|| Rc::new(self.empty_chunk()))
+            }
+        }
+    }
+    /// The chunk at `index`, read without retaining it in this copy of the batch.
+    fn load(&self, index: usize) -> Rc<B> {
+        match &self.chunks.locations[index] {
+            Chunk::Resident(batch) => Rc::clone(batch),
+            Chunk::Spilled { offset, length } => {
+                if let Some(batch) = self.loaded[index].get() {
+                    return Rc::clone(batch);
+                }
+                let file = self.file.as_ref().expect("spilled chunk without a file");
+                file.read(*offset, *length).map(Rc::new).unwrap_or_else(|error| {
+                    report(error);
+                    Rc::new(self.empty_chunk())
+                })
+            }
+        }
+    }
+    /// The sole chunk of the batch, if it is resident.
+    fn resident_batch(&self) -> Option<&Rc<B>> {
+        match &self.chunks.locations[..] {
+            [Chunk::Resident(batch)] => Some(batch),
+            _ => None,
+        }
+    }
+    /// An empty chunk, standing in for one that could not be read.
+    fn empty_chunk(&self) -> B {
+        B::empty(self.description.lower().clone(), self.description.upper().clone())
+    }
+}
+
+impl<B: BatchReader> Clone for SpillBatch<B> {
+    fn clone(&self) -> Self {
+        // Copies start out without spilled chunks, so that chunks read through one copy are
+        // not retained in memory for as long as any other copy lives.
+        SpillBatch {
+            description: self.description.clone(),
+            len: self.len,
+            file: self.file.clone(),
+            chunks: Rc::clone(&self.chunks),
+            loaded: self.loaded.iter().map(|_| OnceCell::new()).collect(),
+        }
+    }
+}
+
+impl<B: BatchReader> WithLayout for SpillBatch<B> {
+    type Layout = B::Layout;
+}
+
+impl<B: SpillChunk> BatchReader for SpillBatch<B> {
+
+    type Cursor = SpillCursor<B::Cursor>;
+    /// Acquires a cursor to the batch's contents.
+    ///
+    /// The cursor does not read a spilled chunk until it is first used.
+    fn cursor(&self) -> Self::Cursor {
+        SpillCursor { state: OnceCell::new() }
+    }
+
+    fn len(&self) -> usize { self.len }
+    fn description(&self) -> &Description<Self::Time> { &self.description }
+}
+
+impl<B: SpillChunk> Batch for SpillBatch<B> {
+    type Merger = SpillMerger<B>;
+    fn empty(lower: Antichain<Self::Time>, upper: Antichain<Self::Time>) -> Self {
+        SpillBatch::resident(B::empty(lower, upper))
+    }
+}
+
+/// A cursor over a `SpillBatch`, moving through its chunks in order.
+pub struct SpillCursor<C> {
+    /// The index of the current chunk and a cursor into it, acquired on first use.
+    state: OnceCell<(usize, C)>,
+}
+
+impl<C: Cursor> WithLayout for SpillCursor<C> {
+    type Layout = C::Layout;
+}
+
+impl<C> SpillCursor<C>
+where
+    C: Cursor<Storage: SpillChunk + BatchReader<Cursor = C>>,
+{
+    /// A cursor at the first key in the chunks from `index` onward, or at the end of the last chunk.
+    fn start(storage: &SpillBatch<C::Storage>, mut index: usize) -> (usize, C) {
+        let mut cursor = storage.chunk(index).cursor();
+        while !cursor.key_valid(storage.chunk(index)) && index + 1 < storage.chunks() {
+            index += 1;
+            cursor = storage.chunk(index).cursor();
+        }
+        (index, cursor)
+    }
+    #[inline]
+    fn get(&self, storage: &SpillBatch<C::Storage>) -> &(usize, C) {
+        self.state.get_or_init(|| Self::start(storage, 0))
+    }
+    #[inline]
+    fn get_mut(&mut self, storage: &SpillBatch<C::Storage>) -> &mut (usize, C) {
+        if self.state.get().is_none() {
+            let _ = self.state.set(Self::start(storage, 0));
+        }
+        self.state.get_mut().unwrap()
+    }
+    /// Moves on to the following chunks should the keys of the current chunk be exhausted.
+    fn settle(&mut self, storage: &SpillBatch<C::Storage>) {
+        let (index, cursor) = self.get_mut(storage);
+        let exhausted = !cursor.key_valid(storage.chunk(*index));
+        let next = *index + 1;
+        if exhausted && next < storage.chunks() {
+            self.state = OnceCell::from(Self::start(storage, next));
+        }
+    }
+}
+
+impl<C> Cursor for SpillCursor<C>
+where
+    C: Cursor<Storage: SpillChunk + BatchReader<Cursor = C>>,
+{
+
+    type Storage = SpillBatch<C::Storage>;
+
+    #[inline] fn key_valid(&self, storage: &Self::Storage) -> bool { let (index, cursor) = self.get(storage); cursor.key_valid(storage.chunk(*index)) }
+    #[inline] fn val_valid(&self, storage: &Self::Storage) -> bool { let (index, cursor) = self.get(storage); cursor.val_valid(storage.chunk(*index)) }
+
+    #[inline] fn key<'a>(&self, storage: &'a Self::Storage) -> Self::Key<'a> { let (index, cursor) = self.get(storage); cursor.key(storage.chunk(*index)) }
+    #[inline] fn val<'a>(&self, storage: &'a Self::Storage) -> Self::Val<'a> { let (index, cursor) = self.get(storage); cursor.val(storage.chunk(*index)) }
+
+    #[inline] fn get_key<'a>(&self, storage: &'a Self::Storage) -> Option<Self::Key<'a>> { let (index, cursor) = self.get(storage); cursor.get_key(storage.chunk(*index)) }
+    #[inline] fn get_val<'a>(&self, storage: &'a Self::Storage) -> Option<Self::Val<'a>> { let (index, cursor) = self.get(storage); cursor.get_val(storage.chunk(*index)) }
+
+    #[inline]
+    fn map_times<L: FnMut(Self::TimeGat<'_>, Self::DiffGat<'_>)>(&mut self, storage: &Self::Storage, logic: L) {
+        let (index, cursor) = self.get_mut(storage);
+        cursor.map_times(storage.chunk(*index), logic)
+    }
+
+    #[inline]
+    fn step_key(&mut self, storage: &Self::Storage) {
+        let (index, cursor) = self.get_mut(storage);
+        cursor.step_key(storage.chunk(*index));
+        self.settle(storage);
+    }
+    #[inline]
+    fn seek_key(&mut self, storage: &Self::Storage, key: Self::Key<'_>) {
+        // Skip directly to the chunk that would contain `key`, as cursors only move forward.
+        let target = storage.seek_chunk(key);
+        if self.state.get().map_or(true, |(index, _)| target > *index) {
+            self.state = OnceCell::from(Self::start(storage, target));
+        }
+        let (index, cursor) = self.get_mut(storage);
+        cursor.seek_key(storage.chunk(*index), key);
+        self.settle(storage);
+    }
+
+    #[inline] fn step_val(&mut self, storage: &Self::Storage) { let (index, cursor) = self.get_mut(storage); cursor.step_val(storage.chunk(*index)) }
+    #[inline] fn seek_val(&mut self, storage: &Self::Storage, val: Self::Val<'_>) { let (index, cursor) = self.get_mut(storage); cursor.seek_val(storage.chunk(*index), val) }
+
+    #[inline] fn rewind_keys(&mut self, storage: &Self::Storage) { self.state = OnceCell::from(Self::start(storage, 0)); }
+    #[inline] fn rewind_vals(&mut self, storage: &Self::Storage) { let (index, cursor) = self.get_mut(storage); cursor.rewind_vals(storage.chunk(*index)) }
+}
+
+/// Wrapper type for building batches that may later be spilled.
+///
+/// Built batches are always resident; only merged batches are considered for spilling.
+pub struct SpillBuilder<B: Builder> { builder: B }
+
+impl<B: Builder<Output: BatchReader>> Builder for SpillBuilder<B> {
+    type Input = B::Input;
+    type Time = B::Time;
+    type Output = SpillBatch<B::Output>;
+    fn with_capacity(keys: usize, vals: usize, upds: usize) -> Self { SpillBuilder { builder: B::with_capacity(keys, vals, upds) } }
+    fn push(&mut self, input: &mut Self::Input) { self.builder.push(input) }
+    fn done(self, description: Description<Self::Time>) -> Self::Output { SpillBatch::resident(self.builder.done(description)) }
+    fn seal(chain: &mut Vec<Self::Input>, description: Description<Self::Time>) -> Self::Output {
+        SpillBatch::resident(B::seal(chain, description))
+    }
+}
+
+/// Wrapper type for merging batches that may be spilled.
+///
+/// Resident inputs whose result is not to be spilled are merged by the merger of `B`. Otherwise the
+/// inputs are read a chunk at a time, and the result built a chunk at a time, each chunk written to
+/// a file as it completes if the worker's `SpillPolicy` calls for spilling the combined inputs.
+pub struct SpillMerger<B: SpillChunk> { merge: Merge<B> }
+
+/// The two ways of merging spill batches.
+enum Merge<B: SpillChunk> {
+    /// Both inputs are a single resident chunk, retained here, and the result is not spilled.
+    Resident(B::Merger, Rc<B>, Rc<B>),
+    /// The inputs are read, and the result built, a chunk at a time.
+    Chunked(ChunkedMerge<B>),
+}
+
+impl<B: SpillChunk> Merger<SpillBatch<B>> for SpillMerger<B> {
+    fn new(source1: &SpillBatch<B>, source2: &SpillBatch<B>, compaction_frontier: AntichainRef<B::Time>) -> Self {
+        let policy = POLICY.with(|policy| policy.borrow().clone());
+        let spill = policy.filter(|policy| policy.should_spill(source1.len() + source2.len()));
+        let merge = match (&spill, source1.resident_batch(), source2.resident_batch()) {
+            (None, Some(batch1), Some(batch2)) => {
+                Merge::Resident(<B as Batch>::begin_merge(batch1, batch2, compaction_frontier), Rc::clone(batch1), Rc::clone(batch2))
+            }
+            _ => Merge::Chunked(ChunkedMerge::new(source1, source2, compaction_frontier, spill.as_ref())),
+        };
+        SpillMerger { merge }
+    }
+    fn work(&mut self, source1: &SpillBatch<B>, source2: &SpillBatch<B>, fuel: &mut isize) {
+        match &mut self.merge {
+            Merge::Resident(merger, batch1, batch2) => merger.work(batch1, batch2, fuel),
+            Merge::Chunked(merge) => merge.work(source1, source2, fuel),
+        }
+    }
+    fn done(self) -> SpillBatch<B> {
+        match self.merge {
+            Merge::Resident(merger, _, _) => SpillBatch::resident(merger.done()),
+            Merge::Chunked(merge) => merge.done(),
+        }
+    }
+}
+
+/// The updates of a key, grouped by value.
+type KeyGroup<B> = (<B as LayoutExt>::KeyOwn, Vec<(<B as LayoutExt>::ValOwn, Vec<(<B as LayoutExt>::Time, <B as LayoutExt>::Diff)>)>);
+
+/// Merges batches a key at a time, holding at most one chunk of each input and of the result.
+struct ChunkedMerge<B: SpillChunk> {
+    /// Description of the merged batch, whose `since` times are advanced by.
+    description: Description<B::Time>,
+    reader1: ChunkReader<B>,
+    reader2: ChunkReader<B>,
+    writer: ChunkWriter<B>,
+}
+
+impl<B: SpillChunk> ChunkedMerge<B> {
+    fn new(source1: &SpillBatch<B>, source2: &SpillBatch<B>, compaction_frontier: AntichainRef<B::Time>, spill: Option<&SpillPolicy>) -> Self {
+        assert!(source1.upper() == source2.lower());
+        let mut since = source1.description().since().join(source2.description().since());
+        since = since.join(&compaction_frontier.to_owned());
+        let description = Description::new(source1.lower().clone(), source2.upper().clone(), since);
+
+        ChunkedMerge {
+            reader1: ChunkReader::new(source1),
+            reader2: ChunkReader::new(source2),
+            writer: ChunkWriter::new(description.clone(), spill),
+            description,
+        }
+    }
+    fn work(&mut self, source1: &SpillBatch<B>, source2: &SpillBatch<B>, fuel: &mut isize) {
+        let mut effort = 0;
+        while effort < *fuel {
+            // Take the least key from either input, or from both if they are equal.
+            let (take1, take2) = match (&self.reader1.head, &self.reader2.head) {
+                (None, None) => break,
+                (Some(_), None) => (true, false),
+                (None, Some(_)) => (false, true),
+                (Some((key1, _)), Some((key2, _))) => (key1 <= key2, key2 <= key1),
+            };
+            let group1 = if take1 { self.reader1.advance(source1) } else { None };
+            let group2 = if take2 { self.reader2.advance(source2) } else { None };
+            let (key, vals) = match (group1, group2) {
+                (Some((key, mut vals1)), Some((_, vals2))) => {
+                    vals1.extend(vals2);
+                    vals1.sort_by(|x, y| x.0.cmp(&y.0));
+                    (key, vals1)
+                }
+                (Some(group), None) | (None, Some(group)) => group,
+                (None, None) => break,
+            };
+            effort += self.writer.push(key, vals, self.description.since().borrow()) as isize;
+        }
+        *fuel -= effort;
+    }
+    fn done(self) -> SpillBatch<B> {
+        self.writer.done()
+    }
+}
+
+/// Reads the keys of a batch in order, one chunk at a time.
+struct ChunkReader<B: BatchReader> {
+    /// The index of the next chunk to read.
+    next: usize,
+    /// The current chunk and a cursor into it.
+    chunk: Option<(Rc<B>, B::Cursor)>,
+    /// The next key and its updates, unless the batch is exhausted.
+    head: Option<KeyGroup<B>>,
+}
+
+impl<B: SpillChunk> ChunkReader<B> {
+    fn new(source: &SpillBatch<B>) -> Self {
+        let mut reader = ChunkReader { next: 0, chunk: None, head: None };
+        reader.head = reader.read(source);
+        reader
+    }
+    /// Returns the next key and its updates, and reads the key after it.
+    fn advance(&mut self, source: &SpillBatch<B>) -> Option<KeyGroup<B>> {
+        let head = self.head.take();
+        self.head = self.read(source);
+        head
+    }
+    /// Reads the next key and its updates, moving on to the next chunk as needed.
+    fn read(&mut self, source: &SpillBatch<B>) -> Option<KeyGroup<B>> {
+        loop {
+            if self.chunk.is_none() {
+                if self.next >= source.chunks() { return None; }
+                let chunk = source.load(self.next);
+                let cursor = (*chunk).cursor();
+                self.chunk = Some((chunk, cursor));
+                self.next += 1;
+            }
+            if let Some((chunk, cursor)) = self.chunk.as_mut() {
+                let chunk: &B = chunk;
+                if let Some(key) = cursor.get_key(chunk) {
+                    let key = B::owned_key(key);
+                    let mut vals = Vec::new();
+                    while let Some(val) = cursor.get_val(chunk) {
+                        let mut upds = Vec::new();
+                        cursor.map_times(chunk, |time, diff| upds.push((B::owned_time(time), B::owned_diff(diff))));
+                        vals.push((B::owned_val(val), upds));
+                        cursor.step_val(chunk);
+                    }
+                    cursor.step_key(chunk);
+                    return Some((key, vals));
+                }
+            }
+            // The chunk is exhausted; release it.
+            self.chunk = None;
+        }
+    }
+}
+
+/// Builds the chunks of a merged batch, writing each to a file as it completes if spilling.
+struct ChunkWriter<B: SpillChunk> {
+    /// Description shared by the merged batch and its chunks.
+    description: Description<B::Time>,
+    /// The number of updates after which to start a new chunk.
+    chunk_updates: usize,
+    /// Updates of the chunk being built.
+    pending: Vec<((B::KeyOwn, B::ValOwn), B::Time, B::Diff)>,
+    /// The number of updates in completed chunks.
+    len: usize,
+    /// Completed chunks.
+    chunks: Vec<Chunk<B>>,
+    /// The first key of each completed chunk after the first.
+    bounds: Vec<B::KeyOwn>,
+    /// The file receiving chunks, if spilling.
+    file: Option<SpillFile>,
+    /// A writer to the file and its length so far, until writing fails.
+    writer: Option<(BufWriter<File>, u64)>,
+}
+
+impl<B: SpillChunk> ChunkWriter<B> {
+    fn new(description: Description<B::Time>, spill: Option<&SpillPolicy>) -> Self {
+        let (file, writer) = match spill.map(|policy| SpillFile::create(&policy.directory)) {
+            Some(Ok((file, writer))) => (Some(file), Some((writer, 0))),
+            Some(Err(error)) => { report(error); (None, None) },
+            None => (None, None),
+        };
+        ChunkWriter {
+            description,
+            chunk_updates: spill.map_or(CHUNK_UPDATES, |policy| policy.chunk_updates),
+            pending: Vec::new(),
+            len: 0,
+            chunks: Vec::new(),
+            bounds: Vec::new(),
+            file,
+            writer,
+        }
+    }
+    /// Adds the updates of `key`, advanced by `since` and consolidated, returning the number of updates read.
+    ///
+    /// Values must be sorted, and each key must be greater than those before it.
+    fn push(&mut self, key: B::KeyOwn, vals: Vec<(B::ValOwn, Vec<(B::Time, B::Diff)>)>, since: AntichainRef<B::Time>) -> usize {
+        let mut read = 0;
+        let mut vals = vals.into_iter().peekable();
+        while let Some((val, mut upds)) = vals.next() {
+            while let Some((_, more)) = vals.next_if(|(next, _)| next == &val) {
+                upds.extend(more);
+            }
+            read += upds.len();
+            for (time, _) in upds.iter_mut() {
+                time.advance_by(since);
+            }
+            consolidate(&mut upds);
+            for (time, diff) in upds {
+                self.pending.push(((key.clone(), val.clone()), time, diff));
+            }
+        }
+        if self.pending.len() >= self.chunk_updates {
+            self.flush();
+        }
+        read
+    }
+    /// Completes the chunk being built, writing it to the file if spilling.
+    fn flush(&mut self) {
+        if self.pending.is_empty() { return; }
+        if !self.chunks.is_empty() {
+            self.bounds.push(self.pending[0].0.0.clone());
+        }
+        self.len += self.pending.len();
+        let batch = <B::Builder as Builder>::seal(&mut vec![std::mem::take(&mut self.pending)], self.description.clone());
+        let written = self.writer.as_mut().map(|(writer, offset)| {
+            SpillFile::write(&batch, writer).map(|length| {
+                let start = *offset;
+                *offset += length;
+                (start, length)
+            })
+        });
+        let chunk = match written {
+            Some(Ok((offset, length))) => Chunk::Spilled { offset, length },
+            Some(Err(error)) => {
+                // Keep this and all following chunks resident.
+                report(error);
+                self.writer = None;
+                Chunk::Resident(Rc::new(batch))
+            }
+            None => Chunk::Resident(Rc::new(batch)),
+        };
+        self.chunks.push(chunk);
+    }
+    fn done(mut self) -> SpillBatch<B> {
+        self.flush();
+        if self.chunks.is_empty() {
+            let empty = B::empty(self.description.lower().clone(), self.description.upper().clone());
+            self.chunks.push(Chunk::Resident(Rc::new(empty)));
+        }
+        // The file is dropped, and so removed, if no chunk made it there.
+        let spilled = self.chunks.iter().any(|chunk| matches!(chunk, Chunk::Spilled { .. }));
+        let file = if spilled { self.file.take().map(Rc::new) } else { None };
+        let loaded = self.chunks.iter().map(|_| OnceCell::new()).collect();
+        SpillBatch {
+            description: self.description,
+            len: self.len,
+            file,
+            chunks: Rc::new(Chunks { locations: self.chunks, bounds: self.bounds }),
+            loaded,
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use timely::dataflow::operators::{Capture, Probe};
+use timely::dataflow::operators::capture::Extract;
+use timely::progress::Antichain;
+
+use differential_dataflow::input::Input;
+use differential_dataflow::consolidation::consolidate;
+use differential_dataflow::operators::arrange::bulk::{arrange_by_key_sorted, batch_sorted};
+use differential_dataflow::trace::{BatchReader, Cursor};
+use differential_dataflow::trace::implementations::ValBuilder;
+
+#[test]
+fn batch_sorted_consolidates() {
+
+    let updates = vec![((0u64, 'a'), 0u64, 1isize), ((0, 'a'), 0, 2), ((0, 'b'), 0, 1), ((0, 'b'), 0, -1), ((1, 'a'), 1, 1)];
+    let batch = batch_sorted::<ValBuilder<_,_,_,_>, _, _, _, _, _>(updates, Antichain::from_elem(2));
+    assert_eq!(batch.upper(), &Antichain::from_elem(2));
+
+    let mut cursor = batch.cursor();
+    let mut contents = Vec::new();
+    while cursor.key_valid(&batch) {
+        while cursor.val_valid(&batch) {
+            let (key, val) = (*cursor.key(&batch), *cursor.val(&batch));
+            cursor.map_times(&batch, |time, diff| contents.push(((key, val), *time, *diff)));
+            cursor.step_val(&batch);
+        }
+        cursor.step_key(&batch);
+    }
+    assert_eq!(contents, vec![((0, 'a'), 0, 3), ((1, 'a'), 1, 1)]);
+}
+
+#[test]
+#[should_panic]
+fn batch_sorted_unsorted() {
+    let updates = vec![((1u64, 'a'), 0u64, 1isize), ((0, 'a'), 0, 1)];
+    batch_sorted::<ValBuilder<_,_,_,_>, _, _, _, _, _>(updates, Antichain::from_elem(1));
+}
+
+#[test]
+#[should_panic]
+fn batch_sorted_beyond_upper() {
+    let updates = vec![((0u64, 'a'), 0u64, 1isize), ((1, 'a'), 1, 1)];
+    batch_sorted::<ValBuilder<_,_,_,_>, _, _, _, _, _>(updates, Antichain::from_elem(1));
+}
+
+#[test]
+fn arrange_sorted_then_updates() {
+
+    let captured = timely::execute_directly(|worker| {
+
+        // Sorted updates at times zero and one seed the arrangement, and later updates continue it.
+        let sorted = (0 .. 100u64).flat_map(|key| vec![((key, key), 0u64, 1isize), ((key, key), 1, -1), ((key, key + 1), 1, 1)]);
+        let (mut input, captured, probe) = worker.dataflow::<u64, _, _>(|scope| {
+            let (input, data) = scope.new_collection::<(u64, u64), isize>();
+            let arranged = arrange_by_key_sorted(&data, "BulkLoaded", sorted, Antichain::from_elem(2));
+            let collection = arranged.as_collection(|key, val| (*key, *val));
+            (input, collection.inner.capture(), collection.inner.probe())
+        });
+
+        input.advance_to(2);
+        for key in 0 .. 10 {
+            input.remove((key, key + 1));
+        }
+        input.advance_to(3);
+        input.close();
+        while !probe.done() { worker.step(); }
+        captured
+    });
+
+    let mut contents = captured.extract().into_iter().flat_map(|(_, data)| data).map(|(data, _, diff)| (data, diff)).collect::<Vec<_>>();
+    consolidate(&mut contents);
+    assert_eq!(contents, (10 .. 100).map(|key| ((key, key + 1), 1)).collect::<Vec<_>>());
+}
@@ -0,0 +1,153 @@
+use std::io::{Read, Write};
+use std::time::Duration;
+
+use timely::dataflow::operators::Capture;
+use timely::dataflow::operators::capture::Extract;
+
+use differential_dataflow::AsCollection;
+use differential_dataflow::input::InputSession;
+use differential_dataflow::capture::{file, tcp, Message, Writer};
+
+/// Introduces a collection that changes over several rounds.
+fn populate(mut input: InputSession<u64, u64, isize>) {
+    for round in 0 .. 5 {
+        input.advance_to(round);
+        for value in 0 .. 10 { input.insert(value + round); }
+        if round > 0 { input.remove(round - 1); }
+    }
+    input.close();
+}
+
+/// The consolidated updates introduced by `populate`.
+fn expected() -> Vec<(u64, u64, isize)> {
+    let mut expected = Vec::new();
+    for round in 0 .. 5u64 {
+        for value in 0 .. 10 { expected.push((value + round, round, 1)); }
+        if round > 0 { expected.push((round - 1, round, -1)); }
+    }
+    differential_dataflow::consolidation::consolidate_updates(&mut expected);
+    expected.sort();
+    expected
+}
+
+#[test]
+fn capture_replay_file() {
+
+    let directory = std::env::temp_dir().join(format!("dd-capture-test-{}", std::process::id()));
+
+    // Record a collection that changes over several rounds.
+    let written = directory.clone();
+    timely::execute(timely::Config::thread(), move |worker| {
+        let mut input = InputSession::<u64, u64, isize>::new();
+        let token = worker.dataflow(|scope| {
+            let stream = input.to_collection(scope).consolidate().inner;
+            file::sink(&stream, &written).unwrap()
+        });
+        populate(input);
+        // The sink runs only as long as its token is held, and must finish writing.
+        while worker.has_dataflows() { worker.step(); }
+        drop(token);
+    }).unwrap();
+
+    // Duplicate the segment, as a restarted writer or a careless copy might.
+    std::fs::copy(directory.join("worker-0.log"), directory.join("copy.log")).unwrap();
+
+    // Replay the recorded collection, which should match the original.
+    let read = directory.clone();
+    let captured = timely::execute(timely::Config::thread(), move |worker| {
+        let (token, captured) = worker.dataflow(|scope| {
+            let (token, stream) = file::source::<_, u64, u64, isize>(scope.clone(), &read, Duration::from_millis(10));
+            (token, stream.as_collection().consolidate().inner.capture())
+        });
+        // The source runs only as long as its token is held.
+        while worker.has_dataflows() { worker.step(); }
+        drop(token);
+        captured
+    }).unwrap().join().into_iter().map(|x| x.unwrap()).next().unwrap();
+
+    let mut results = captured.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+    results.sort();
+    assert_eq!(results, expected());
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn capture_replay_tcp() {
+
+    // Find an available address, but do not listen on it yet, so that writers must retry.
+    let address = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+
+    let writer = std::thread::spawn(move || {
+        timely::execute(timely::Config::thread(), move |worker| {
+            let mut input = InputSession::<u64, u64, isize>::new();
+            let token = worker.dataflow(|scope| {
+                let stream = input.to_collection(scope).consolidate().inner;
+                tcp::sink(&stream, &address.to_string())
+            });
+            populate(input);
+            // The sink runs only as long as its token is held, and must finish sending.
+            while worker.has_dataflows() { worker.step(); }
+            drop(token);
+        }).unwrap();
+    });
+
+    std::thread::sleep(Duration::from_millis(200));
+    let listener = std::net::TcpListener::bind(address).unwrap();
+
+    let captured = timely::execute(timely::Config::thread(), move |worker| {
+        let listener = if worker.index() == 0 { listener.try_clone().ok() } else { None };
+        let (token, captured) = worker.dataflow(|scope| {
+            let (token, stream) = tcp::source::<_, u64, u64, isize>(scope.clone(), listener).unwrap();
+            (token, stream.as_collection().consolidate().inner.capture())
+        });
+        // The source runs only as long as its token is held.
+        while worker.has_dataflows() { worker.step(); }
+        drop(token);
+        captured
+    }).unwrap().join().into_iter().map(|x| x.unwrap()).next().unwrap();
+
+    writer.join().unwrap();
+
+    let mut results = captured.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+    results.sort();
+    assert_eq!(results, expected());
+}
+
+/// Reads one length-prefixed message from `stream`.
+fn read_message(stream: &mut std::net::TcpStream) -> (usize, Message<u64, u64, isize>) {
+    let mut prefix = [0u8; 8];
+    stream.read_exact(&mut prefix).unwrap();
+    let mut payload = vec![0u8; u64::from_le_bytes(prefix) as usize];
+    stream.read_exact(&mut payload).unwrap();
+    (prefix.len() + payload.len(), bincode::deserialize(&payload).unwrap())
+}
+
+#[test]
+fn tcp_writer_retransmits_unacknowledged() {
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut writer = tcp::TcpWriter::<u64, u64, isize>::new(listener.local_addr().unwrap().to_string());
+
+    // Acknowledge the first message, and then fail the connection.
+    assert_eq!(writer.poll(&Message::Updates(vec![(1, 0, 1)])), None);
+    let (mut stream, _) = listener.accept().unwrap();
+    let (length, message) = read_message(&mut stream);
+    assert_eq!(message, Message::Updates(vec![(1, 0, 1)]));
+    stream.write_all(&(length as u64).to_le_bytes()).unwrap();
+    drop(stream);
+    std::thread::sleep(Duration::from_millis(200));
+
+    // The failure is noticed, and the reconnection sends only the unacknowledged message.
+    let mut attempts = 0;
+    while writer.poll(&Message::Updates(vec![(2, 0, 1)])).is_some() {
+        attempts += 1;
+        assert!(attempts < 10);
+    }
+    let (mut stream, _) = listener.accept().unwrap();
+    stream.set_read_timeout(Some(Duration::from_millis(200))).unwrap();
+    let (_, message) = read_message(&mut stream);
+    assert_eq!(message, Message::Updates(vec![(2, 0, 1)]));
+    let mut rest = Vec::new();
+    assert!(stream.read_to_end(&mut rest).is_err() && rest.is_empty());
+}
@@ -0,0 +1,114 @@
+use timely::container::{CapacityContainerBuilder, PushInto};
+use timely::dataflow::InputHandleCore;
+use timely::dataflow::channels::pact::ExchangeCore;
+use timely::dataflow::operators::generic::OperatorInfo;
+use timely::dataflow::operators::Probe;
+use timely::progress::Antichain;
+
+use differential_dataflow::operators::arrange::arrangement::arrange_core;
+use differential_dataflow::trace::{Batcher, Trace, TraceReader};
+use differential_dataflow::trace::cursor::Cursor;
+use differential_dataflow::trace::implementations::{ValBatcher, ValBuilder, ValSpine};
+use differential_dataflow::trace::implementations::columnar::{Column, ColumnBuilder};
+use differential_dataflow::trace::implementations::columnar::{ColumnarKeyBatcher, ColumnarKeyBuilder, ColumnarKeySpine};
+use differential_dataflow::trace::implementations::columnar::{ColumnarValBatcher, ColumnarValBuilder, ColumnarValSpine};
+
+fn updates(round: u64) -> Vec<((String, u64), u64, i64)> {
+    (0 .. 100u64).map(|i| ((format!("{}", i % 13), i % 5), round, if (i + round) % 7 == 0 { -1 } else { 1 })).collect()
+}
+
+fn column(updates: Vec<((String, u64), u64, i64)>) -> Column<((String, u64), u64, i64)> {
+    let mut column = Column::default();
+    for ((key, val), time, diff) in updates {
+        column.push_into(((&key, val), time, diff));
+    }
+    column
+}
+
+#[test]
+fn columnar_val_contents() {
+
+    let mut columnar_trace = ColumnarValSpine::<String, u64, u64, i64>::new(OperatorInfo::new(0, 0, [].into()), None, None);
+    let mut vec_trace = ValSpine::<String, u64, u64, i64>::new(OperatorInfo::new(0, 0, [].into()), None, None);
+    let mut columnar_batcher = ColumnarValBatcher::<String, u64, u64, i64>::new(None, 0);
+    let mut vec_batcher = ValBatcher::<String, u64, u64, i64>::new(None, 0);
+
+    for round in 0 .. 20 {
+        columnar_batcher.push_container(&mut column(updates(round)));
+        vec_batcher.push_container(&mut updates(round));
+        columnar_trace.insert(columnar_batcher.seal::<ColumnarValBuilder<_,_,_,_>>(Antichain::from_elem(round + 1)));
+        vec_trace.insert(vec_batcher.seal::<ValBuilder<_,_,_,_>>(Antichain::from_elem(round + 1)));
+    }
+
+    let (mut columnar_cursor, columnar_storage) = columnar_trace.cursor();
+    let (mut vec_cursor, vec_storage) = vec_trace.cursor();
+    let mut columnar_contents = columnar_cursor.to_vec(&columnar_storage, |k| k.to_string(), |v| *v);
+    let mut vec_contents = vec_cursor.to_vec(&vec_storage, |k| k.clone(), |v| *v);
+    for (_, times) in columnar_contents.iter_mut().chain(vec_contents.iter_mut()) {
+        differential_dataflow::consolidation::consolidate(times);
+    }
+    columnar_contents.retain(|(_, times)| !times.is_empty());
+    vec_contents.retain(|(_, times)| !times.is_empty());
+    assert!(!columnar_contents.is_empty());
+    assert_eq!(columnar_contents, vec_contents);
+}
+
+#[test]
+fn columnar_arrange_core() {
+
+    let contents = timely::execute(timely::Config::thread(), move |worker| {
+
+        let mut input = <InputHandleCore<_, CapacityContainerBuilder<Column<((String, ()), u64, i64)>>>>::new();
+        let (mut trace, probe) = worker.dataflow::<u64, _, _>(|scope| {
+            let pact = ExchangeCore::<ColumnBuilder<((String, ()), u64, i64)>, _>::new_core(|x: &((&str, ()), &u64, &i64)| (x.0).0.len() as u64);
+            let arranged = arrange_core::<_, _, ColumnarKeyBatcher<_,_,_>, ColumnarKeyBuilder<_,_,_>, ColumnarKeySpine<_,_,_>>(&input.to_stream(scope), pact, "Columnar");
+            (arranged.trace, arranged.stream.probe())
+        });
+
+        let mut buffer = String::new();
+        for round in 0 .. 3u64 {
+            input.advance_to(round);
+            for i in 0 .. 10u64 {
+                use std::fmt::Write;
+                write!(buffer, "{}", i % 4).unwrap();
+                input.send(((&buffer, ()), round, 1i64));
+                buffer.clear();
+            }
+        }
+        input.advance_to(3);
+        worker.step_while(|| probe.less_than(input.time()));
+
+        let (mut cursor, storage) = trace.cursor();
+        let mut contents = cursor.to_vec(&storage, |k| k.to_string(), |v| *v);
+        for (_, times) in contents.iter_mut() {
+            differential_dataflow::consolidation::consolidate(times);
+        }
+        contents
+    }).unwrap().join().into_iter().map(|x| x.unwrap()).next().unwrap();
+
+    // Keys "0" and "1" occur three times per round, and "2" and "3" twice.
+    let expected = (0 .. 4u64).map(|k| {
+        let count = if k < 2 { 3 } else { 2 };
+        ((k.to_string(), ()), (0 .. 3).map(|round| (round, count)).collect::<Vec<_>>())
+    }).collect::<Vec<_>>();
+    assert_eq!(contents, expected);
+}
+
+#[test]
+fn columnar_push_into_bytes() {
+    use timely::Container;
+    use timely::bytes::arc::BytesMut;
+    use timely::dataflow::channels::ContainerBytes;
+
+    // A column received as bytes is copied to a typed column when pushed into.
+    let mut bytes = Vec::new();
+    column(updates(0)).into_bytes(&mut bytes);
+    let mut received = Column::<((String, u64), u64, i64)>::from_bytes(BytesMut::from(bytes).freeze());
+    let extra = "extra".to_string();
+    received.push_into(((&extra, 0u64), 0u64, 1i64));
+
+    let mut expected = updates(0);
+    expected.push(((extra, 0), 0, 1));
+    let contents = received.iter().map(|((k, v), t, r)| ((k.to_string(), *v), *t, *r)).collect::<Vec<_>>();
+    assert_eq!(contents, expected);
+}
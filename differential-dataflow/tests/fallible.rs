@@ -0,0 +1,129 @@
+use timely::dataflow::operators::Capture;
+use timely::dataflow::operators::capture::Extract;
+
+use differential_dataflow::input::Input;
+use differential_dataflow::consolidation::consolidate;
+use differential_dataflow::operators::Fallible;
+
+/// Accumulates captured updates at times less or equal to `time`.
+fn accumulate_to<D: Ord+Clone>(extracted: &[(u64, Vec<(D, u64, isize)>)], time: u64) -> Vec<(D, isize)> {
+    let mut updates = extracted.iter().flat_map(|(_, data)| data.iter()).filter(|(_, t, _)| *t <= time).map(|(d, _, r)| (d.clone(), *r)).collect::<Vec<_>>();
+    consolidate(&mut updates);
+    updates
+}
+
+#[test]
+fn fallible_errors_retract() {
+
+    let (oks, errs, joined, join_errs, reduced, reduce_errs) = timely::example(|scope| {
+
+        let (mut input, data) = scope.new_collection::<(u64, i64), isize>();
+        let (mut other, divisors) = scope.new_collection::<(u64, i64), isize>();
+
+        // Negative values are errors, reported with their keys.
+        let (oks, errs) = data.map_fallible(|(key, val)| if val >= 0 { Ok((key, val)) } else { Err((key, val)) });
+        // Division by zero is an error, reported with the key and value.
+        let (joined, join_errs) = oks.join_fallible(&divisors, &errs, |&key, &val, &div| val.checked_div(div).map(|x| (key, x)).ok_or((key, val)));
+        // Keys with more than two values are errors, reported with their number of values.
+        let (reduced, reduce_errs) = oks.reduce_fallible(&errs, |&key, input, output| {
+            if input.len() > 2 { return Err((key, input.len() as i64)); }
+            for (val, count) in input.iter() {
+                output.push((**val, *count));
+            }
+            Ok(())
+        });
+
+        input.insert((0, 4));
+        input.insert((0, -1));
+        input.insert((1, 6));
+        input.insert((1, 8));
+        input.insert((1, 10));
+        other.insert((0, 2));
+        other.insert((1, 0));
+
+        input.advance_to(1);
+        other.advance_to(1);
+        // Retracting the records responsible for errors retracts the errors.
+        input.remove((0, -1));
+        input.remove((1, 10));
+        other.remove((1, 0));
+        other.insert((1, 2));
+
+        (
+            oks.inner.capture(),
+            errs.inner.capture(),
+            joined.inner.capture(),
+            join_errs.inner.capture(),
+            reduced.inner.capture(),
+            reduce_errs.inner.capture(),
+        )
+    });
+
+    let oks = oks.extract();
+    let errs = errs.extract();
+    let joined = joined.extract();
+    let join_errs = join_errs.extract();
+    let reduced = reduced.extract();
+    let reduce_errs = reduce_errs.extract();
+
+    assert_eq!(accumulate_to(&oks, 0), vec![((0, 4), 1), ((1, 6), 1), ((1, 8), 1), ((1, 10), 1)]);
+    assert_eq!(accumulate_to(&errs, 0), vec![((0, -1), 1)]);
+    assert_eq!(accumulate_to(&joined, 0), vec![((0, 2), 1)]);
+    assert_eq!(accumulate_to(&join_errs, 0), vec![((0, -1), 1), ((1, 6), 1), ((1, 8), 1), ((1, 10), 1)]);
+    assert_eq!(accumulate_to(&reduced, 0), vec![((0, 4), 1)]);
+    assert_eq!(accumulate_to(&reduce_errs, 0), vec![((0, -1), 1), ((1, 3), 1)]);
+
+    assert_eq!(accumulate_to(&oks, 1), vec![((0, 4), 1), ((1, 6), 1), ((1, 8), 1)]);
+    assert_eq!(accumulate_to(&errs, 1), vec![]);
+    assert_eq!(accumulate_to(&joined, 1), vec![((0, 2), 1), ((1, 3), 1), ((1, 4), 1)]);
+    assert_eq!(accumulate_to(&join_errs, 1), vec![]);
+    assert_eq!(accumulate_to(&reduced, 1), vec![((0, 4), 1), ((1, 6), 1), ((1, 8), 1)]);
+    assert_eq!(accumulate_to(&reduce_errs, 1), vec![]);
+}
+
+#[test]
+fn fallible_errors_chain() {
+
+    let (oks, errs) = timely::example(|scope| {
+
+        let (mut input, data) = scope.new_collection::<(u64, i64), isize>();
+        let (mut other, divisors) = scope.new_collection::<(u64, i64), isize>();
+
+        // Errors from each stage are reported as the stage and the key.
+        let (oks, errs) = data.map_fallible(|(key, val)| if val >= 0 { Ok((key, val)) } else { Err(("map", key)) });
+        let (oks, errs) = oks.join_fallible(&divisors, &errs, |&key, &val, &div| val.checked_div(div).map(|x| (key, x)).ok_or(("join", key)));
+        let (oks, errs) = oks.reduce_fallible(&errs, |&key, input, output| {
+            let sum: i64 = input.iter().map(|(val, count)| **val * (*count as i64)).sum();
+            if sum > 10 { return Err(("reduce", key)); }
+            output.push((sum, 1));
+            Ok(())
+        });
+
+        input.insert((0, -1));
+        input.insert((1, 6));
+        input.insert((2, 8));
+        input.insert((3, 40));
+        other.insert((1, 0));
+        other.insert((2, 2));
+        other.insert((3, 2));
+
+        input.advance_to(1);
+        other.advance_to(1);
+        // Retracting the records responsible for errors retracts the errors at the end of the chain.
+        input.remove((0, -1));
+        other.remove((1, 0));
+        other.insert((1, 3));
+        input.remove((3, 40));
+
+        (oks.inner.capture(), errs.inner.capture())
+    });
+
+    let oks = oks.extract();
+    let errs = errs.extract();
+
+    assert_eq!(accumulate_to(&oks, 0), vec![((2, 4), 1)]);
+    assert_eq!(accumulate_to(&errs, 0), vec![(("join", 1), 1), (("map", 0), 1), (("reduce", 3), 1)]);
+
+    assert_eq!(accumulate_to(&oks, 1), vec![((1, 2), 1), ((2, 4), 1)]);
+    assert_eq!(accumulate_to(&errs, 1), vec![]);
+}
@@ -1,7 +1,7 @@
 use timely::dataflow::operators::{ToStream, Capture, Map};
 use timely::dataflow::operators::capture::Extract;
 use differential_dataflow::AsCollection;
-use differential_dataflow::operators::{Join, Count};
+use differential_dataflow::operators::{Join, OuterJoin, Count};
 
 #[test]
 fn join() {
@@ -98,4 +98,138 @@ fn join_scaling(scale: u64) {
 
     let extracted = data.extract();
     assert_eq!(extracted.len(), 0);
-}
\ No newline at end of file
+}
+
+#[test]
+fn outer_joins() {
+
+    timely::example(|scope| {
+
+        use differential_dataflow::input::Input;
+        use differential_dataflow::operators::Threshold;
+
+        let (mut input1, col1) = scope.new_collection::<(u64, u64), isize>();
+        let (mut input2, col2) = scope.new_collection::<(u64, char), isize>();
+
+        // Reference implementations from `join`, `antijoin`, and `distinct`.
+        let keys1 = col1.map(|(k, _)| k).distinct();
+        let keys2 = col2.map(|(k, _)| k).distinct();
+        let matched = col1.join(&col2);
+        let left = matched.map(|(k, (v1, v2))| (k, (v1, Some(v2))))
+                          .concat(&col1.antijoin(&keys2).map(|(k, v1)| (k, (v1, None))));
+        let right = matched.map(|(k, (v1, v2))| (k, (Some(v1), v2)))
+                           .concat(&col2.antijoin(&keys1).map(|(k, v2)| (k, (None, v2))));
+        let full = matched.map(|(k, (v1, v2))| (k, (Some(v1), Some(v2))))
+                          .concat(&col1.antijoin(&keys2).map(|(k, v1)| (k, (Some(v1), None))))
+                          .concat(&col2.antijoin(&keys1).map(|(k, v2)| (k, (None, Some(v2)))));
+
+        col1.left_join(&col2).assert_eq(&left);
+        col1.right_join(&col2).assert_eq(&right);
+        col1.full_outer_join(&col2).assert_eq(&full);
+
+        for round in 0 .. 10u64 {
+            input1.advance_to(round);
+            input2.advance_to(round);
+            input1.insert((round % 4, round));
+            input2.insert(((round + 1) % 5, (b'a' + round as u8) as char));
+            if round > 2 {
+                input1.remove(((round - 3) % 4, round - 3));
+            }
+            if round > 4 {
+                input2.remove(((round - 4) % 5, (b'a' + (round - 5) as u8) as char));
+            }
+        }
+    });
+}
+
+#[test]
+fn outer_joins_partially_ordered() {
+
+    timely::example(|scope| {
+
+        use differential_dataflow::input::Input;
+        use differential_dataflow::operators::Threshold;
+
+        let (mut input1, col1) = scope.new_collection::<(u64, u64), isize>();
+        let (mut input2, col2) = scope.new_collection::<(u64, char), isize>();
+
+        scope.iterative::<u64, _, _>(|inner| {
+
+            // Records enter at iterations determined by their values, so that updates to a key
+            // in different rounds may occur at incomparable times.
+            let col1 = col1.enter_at(inner, |&(k, v)| (k + v) % 3);
+            let col2 = col2.enter_at(inner, |&(k, v)| (k + v as u64) % 2);
+
+            // Reference implementations from `join`, `antijoin`, and `distinct`.
+            let keys1 = col1.map(|(k, _)| k).distinct();
+            let keys2 = col2.map(|(k, _)| k).distinct();
+            let matched = col1.join(&col2);
+            let left = matched.map(|(k, (v1, v2))| (k, (v1, Some(v2))))
+                              .concat(&col1.antijoin(&keys2).map(|(k, v1)| (k, (v1, None))));
+            let right = matched.map(|(k, (v1, v2))| (k, (Some(v1), v2)))
+                               .concat(&col2.antijoin(&keys1).map(|(k, v2)| (k, (None, v2))));
+            let full = matched.map(|(k, (v1, v2))| (k, (Some(v1), Some(v2))))
+                              .concat(&col1.antijoin(&keys2).map(|(k, v1)| (k, (Some(v1), None))))
+                              .concat(&col2.antijoin(&keys1).map(|(k, v2)| (k, (None, Some(v2)))));
+
+            col1.left_join(&col2).assert_eq(&left);
+            col1.right_join(&col2).assert_eq(&right);
+            col1.full_outer_join(&col2).assert_eq(&full);
+        });
+
+        for round in 0 .. 10u64 {
+            input1.advance_to(round);
+            input2.advance_to(round);
+            input1.insert((round % 4, round));
+            input2.insert(((round + 1) % 5, (b'a' + round as u8) as char));
+            if round > 2 {
+                input1.remove(((round - 3) % 4, round - 3));
+            }
+            if round > 4 {
+                input2.remove(((round - 4) % 5, (b'a' + (round - 5) as u8) as char));
+            }
+        }
+    });
+}
+
+#[test]
+fn skew_join() {
+
+    timely::execute(timely::Config::process(2), |worker| {
+
+        use differential_dataflow::input::Input;
+        use differential_dataflow::operators::SkewJoin;
+
+        let index = worker.index() as u64;
+        let (mut input1, mut input2) = worker.dataflow::<u64, _, _>(|scope| {
+
+            let (input1, col1) = scope.new_collection::<(u64, u64), isize>();
+            let (input2, col2) = scope.new_collection::<(u64, char), isize>();
+
+            col1.skew_join(&col2, 8).assert_eq(&col1.join(&col2));
+
+            (input1, input2)
+        });
+
+        // Key zero becomes heavy and then light again, while other keys remain light.
+        for round in 0 .. 10u64 {
+            input1.advance_to(round);
+            input2.advance_to(round);
+            if round < 5 {
+                for val in 0 .. 5 {
+                    input1.insert((0, round * 10 + val + index * 1000));
+                }
+            }
+            else {
+                for val in 0 .. 5 {
+                    input1.remove((0, (round - 5) * 10 + val + index * 1000));
+                }
+            }
+            input1.insert((round % 3 + 1, round + index * 1000));
+            input2.insert((round % 4, (b'a' + round as u8) as char));
+            if round > 3 {
+                input2.remove(((round - 4) % 4, (b'a' + (round - 4) as u8) as char));
+            }
+        }
+    }).unwrap();
+}
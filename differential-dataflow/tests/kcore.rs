@@ -0,0 +1,120 @@
+use rand::{Rng, SeedableRng, StdRng};
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use timely::dataflow::operators::Capture;
+use timely::dataflow::operators::capture::Extract;
+
+use differential_dataflow::input::Input;
+use differential_dataflow::consolidation::consolidate;
+use differential_dataflow::algorithms::graphs::kcore::{core_numbers, degeneracy, k_core, k_core_from_cores};
+
+type Node = u32;
+type Edge = (Node, Node);
+
+#[test] fn kcore_10_30_50() { test_sizes(10, 30, 50); }
+#[test] fn kcore_50_300_10() { test_sizes(50, 300, 10); }
+
+fn test_sizes(nodes: Node, edges: usize, rounds: usize) {
+
+    let seed: &[_] = &[1, 2, 3, 4];
+    let mut rng1: StdRng = SeedableRng::from_seed(seed);    // rng for edge additions
+    let mut rng2: StdRng = SeedableRng::from_seed(seed);    // rng for edge deletions
+
+    let mut updates = vec![Vec::new()];
+    for _ in 0 .. edges {
+        updates[0].push(((rng1.gen_range(0, nodes), rng1.gen_range(0, nodes)), 1));
+    }
+    for _ in 1 .. rounds {
+        updates.push(vec![
+            ((rng1.gen_range(0, nodes), rng1.gen_range(0, nodes)), 1),
+            ((rng2.gen_range(0, nodes), rng2.gen_range(0, nodes)), -1),
+        ]);
+    }
+
+    let updates_list = updates.clone();
+    let (cores, three_core, two_core, degen) = timely::execute_directly(move |worker| {
+
+        let (mut input, cores, three_core, two_core, degen) = worker.dataflow::<usize, _, _>(|scope| {
+            let (input, edges) = scope.new_collection::<Edge, isize>();
+            let cores = core_numbers(&edges);
+            let three_core = k_core_from_cores(&edges, &cores, 3);
+            let two_core = k_core(&edges, 2);
+            let degen = degeneracy(&cores);
+            (input, cores.inner.capture(), three_core.inner.capture(), two_core.inner.capture(), degen.inner.capture())
+        });
+
+        for (round, changes) in updates_list.iter().enumerate() {
+            input.advance_to(round);
+            for (edge, diff) in changes.iter() {
+                input.update(*edge, *diff);
+            }
+        }
+        input.close();
+        while worker.step() { }
+
+        (cores, three_core, two_core, degen)
+    });
+
+    let cores = cores.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+    let three_core = three_core.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+    let two_core = two_core.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+    let degen = degen.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+
+    let mut graph = HashMap::new();
+    for (round, changes) in updates.iter().enumerate() {
+        for (edge, diff) in changes.iter() {
+            *graph.entry(*edge).or_insert(0) += diff;
+        }
+
+        let expected = core_numbers_sequential(&graph);
+
+        let mut expected_cores = expected.iter().map(|(node, core)| ((*node, *core), 1)).collect::<Vec<_>>();
+        consolidate(&mut expected_cores);
+        let mut actual = cores.iter().filter(|(_, time, _)| *time <= round).map(|(data, _, diff)| (*data, *diff)).collect::<Vec<_>>();
+        consolidate(&mut actual);
+        assert_eq!(actual, expected_cores);
+
+        for (k, k_edges) in [(3, &three_core), (2, &two_core)] {
+            let mut expected_edges = graph.iter().filter(|(&(x, y), _)| expected.get(&x).map(|c| *c >= k).unwrap_or(false) && expected.get(&y).map(|c| *c >= k).unwrap_or(false)).map(|(edge, count)| (*edge, *count)).collect::<Vec<_>>();
+            consolidate(&mut expected_edges);
+            let mut actual = k_edges.iter().filter(|(_, time, _)| *time <= round).map(|(data, _, diff)| (*data, *diff)).collect::<Vec<_>>();
+            consolidate(&mut actual);
+            assert_eq!(actual, expected_edges);
+        }
+
+        let mut expected_degen = expected.values().max().map(|max| vec![(*max, 1)]).unwrap_or_default();
+        consolidate(&mut expected_degen);
+        let mut actual = degen.iter().filter(|(_, time, _)| *time <= round).map(|(data, _, diff)| (*data, *diff)).collect::<Vec<_>>();
+        consolidate(&mut actual);
+        assert_eq!(actual, expected_degen);
+    }
+}
+
+// Core numbers by repeatedly removing a node of least remaining degree.
+fn core_numbers_sequential(graph: &HashMap<Edge, isize>) -> BTreeMap<Node, usize> {
+
+    // Undirected edges with non-zero accumulated multiplicity across both directions.
+    let mut undirected = HashMap::new();
+    for (&(x, y), &count) in graph.iter() {
+        *undirected.entry((x.min(y), x.max(y))).or_insert(0) += count;
+    }
+    let mut neighbors: BTreeMap<Node, BTreeSet<Node>> = BTreeMap::new();
+    for (&(x, y), &count) in undirected.iter() {
+        if count != 0 && x != y {
+            neighbors.entry(x).or_default().insert(y);
+            neighbors.entry(y).or_default().insert(x);
+        }
+    }
+
+    let mut cores = BTreeMap::new();
+    let mut core = 0;
+    while let Some((&node, _)) = neighbors.iter().min_by_key(|(_, adjacent)| adjacent.len()) {
+        core = std::cmp::max(core, neighbors[&node].len());
+        cores.insert(node, core);
+        for other in neighbors.remove(&node).unwrap() {
+            neighbors.get_mut(&other).unwrap().remove(&node);
+        }
+    }
+    cores
+}
@@ -0,0 +1,54 @@
+use std::ops::Bound;
+
+use timely::dataflow::operators::Probe;
+use timely::progress::frontier::AntichainRef;
+
+use differential_dataflow::input::Input;
+use differential_dataflow::operators::arrange::ArrangeByKey;
+use differential_dataflow::trace::TraceReader;
+
+#[test]
+fn lookup_points_and_ranges() {
+
+    timely::execute_directly(|worker| {
+
+        let (mut input, mut trace, probe) = worker.dataflow::<u64, _, _>(|scope| {
+            let (input, data) = scope.new_collection::<(u64, u64), isize>();
+            let arranged = data.arrange_by_key();
+            (input, arranged.trace, arranged.stream.probe())
+        });
+
+        // Each round inserts `(key, round)` for all keys, and retracts the values of two rounds ago.
+        for round in 0 .. 5u64 {
+            input.advance_to(round);
+            for key in 0 .. 10 {
+                input.insert((key, round));
+                if round >= 2 {
+                    input.remove((key, round - 2));
+                }
+            }
+        }
+        input.advance_to(5);
+        input.flush();
+        while probe.less_than(input.time()) { worker.step(); }
+
+        for time in 0 .. 5u64 {
+            let expected = (time.saturating_sub(1) ..= time).map(|val| (val, 1)).collect::<Vec<_>>();
+            assert_eq!(trace.lookup(&3, &time), Some(expected.clone()));
+            let range = trace.lookup_range(Bound::Excluded(&2), Bound::Included(&4), &time).unwrap();
+            let expected_range = (3 .. 5).flat_map(|key| expected.iter().map(move |(val, diff)| ((key, *val), *diff))).collect::<Vec<_>>();
+            assert_eq!(range, expected_range);
+            let snapshot = trace.lookup_range(Bound::Unbounded, Bound::Unbounded, &time).unwrap();
+            assert_eq!(snapshot.len(), 10 * expected.len());
+        }
+
+        // Absent keys have no values, and incomplete times cannot be read.
+        assert_eq!(trace.lookup(&10, &4), Some(Vec::new()));
+        assert_eq!(trace.lookup(&3, &5), None);
+
+        // Times before the logical compaction frontier cannot be read.
+        trace.set_logical_compaction(AntichainRef::new(&[3]));
+        assert_eq!(trace.lookup(&3, &2), None);
+        assert_eq!(trace.lookup(&3, &4), Some(vec![(3, 1), (4, 1)]));
+    });
+}
@@ -0,0 +1,49 @@
+use timely::dataflow::operators::Capture;
+use timely::dataflow::operators::capture::Extract;
+
+use differential_dataflow::input::Input;
+use differential_dataflow::consolidation::consolidate;
+use differential_dataflow::operators::MinMax;
+
+/// Accumulates captured updates at times less or equal to `time`.
+fn accumulate_to<D: Ord+Clone>(extracted: &[(u64, Vec<(D, u64, isize)>)], time: u64) -> Vec<(D, isize)> {
+    let mut updates = extracted.iter().flat_map(|(_, data)| data.iter()).filter(|(_, t, _)| *t <= time).map(|(d, _, r)| (d.clone(), *r)).collect::<Vec<_>>();
+    consolidate(&mut updates);
+    updates
+}
+
+#[test]
+fn min_max_retractions() {
+
+    let (min, max, flat) = timely::example(|scope| {
+        let (mut input, data) = scope.new_collection::<(u64, u64), isize>();
+        let min = data.min_by_key().inner.capture();
+        let max = data.max_by_key_core(&[58, 60, 62]).inner.capture();
+        let flat = data.min_by_key_core(&[]).inner.capture();
+        for key in 0 .. 2 {
+            for val in 0 .. 1000 {
+                input.insert((key, val));
+            }
+        }
+        // Repeatedly retract the least and greatest values of each key.
+        for round in 1 .. 10 {
+            input.advance_to(round);
+            for key in 0 .. 2 {
+                input.remove((key, round - 1));
+                input.remove((key, 1000 - round));
+            }
+        }
+        (min, max, flat)
+    });
+
+    let min = min.extract();
+    let max = max.extract();
+    let flat = flat.extract();
+    for time in 0 .. 10 {
+        let expected_min = (0 .. 2).map(|key| ((key, time), 1)).collect::<Vec<_>>();
+        let expected_max = (0 .. 2).map(|key| ((key, 999 - time), 1)).collect::<Vec<_>>();
+        assert_eq!(accumulate_to(&min, time), expected_min);
+        assert_eq!(accumulate_to(&flat, time), expected_min);
+        assert_eq!(accumulate_to(&max, time), expected_max);
+    }
+}
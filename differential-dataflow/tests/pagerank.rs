@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+
+use timely::dataflow::operators::Capture;
+use timely::dataflow::operators::capture::Extract;
+
+use differential_dataflow::input::Input;
+use differential_dataflow::consolidation::consolidate;
+use differential_dataflow::algorithms::graphs::pagerank::{pagerank, personalized_pagerank, Rank};
+
+type Node = u32;
+type Edge = (Node, Node);
+
+const DAMPING: f64 = 0.85;
+
+// Power iteration until ranks stop changing, resetting to `seeds`, or to all nodes if `None`.
+fn pagerank_sequential(edges: &[Edge], seeds: Option<&[Node]>, iterations: usize) -> HashMap<Node, f64> {
+    let mut degrees = HashMap::new();
+    let mut nodes = Vec::new();
+    for &(src, dst) in edges {
+        *degrees.entry(src).or_insert(0) += 1;
+        nodes.push(src);
+        nodes.push(dst);
+    }
+    nodes.sort();
+    nodes.dedup();
+    let seeds = seeds.map(|seeds| seeds.to_vec()).unwrap_or(nodes);
+
+    let mut ranks: HashMap<Node, f64> = seeds.iter().map(|seed| (*seed, 1.0 - DAMPING)).collect();
+    for _ in 0 .. iterations {
+        let mut next: HashMap<Node, f64> = seeds.iter().map(|seed| (*seed, 1.0 - DAMPING)).collect();
+        for &(src, dst) in edges {
+            if let Some(rank) = ranks.get(&src) {
+                *next.entry(dst).or_insert(0.0) += DAMPING * rank / degrees[&src] as f64;
+            }
+        }
+        ranks = next;
+    }
+    ranks
+}
+
+fn assert_close(actual: &[((Node, Rank), isize)], expected: &HashMap<Node, f64>, epsilon: f64) {
+    assert_eq!(actual.len(), expected.len());
+    for ((node, rank), diff) in actual.iter() {
+        assert_eq!(*diff, 1);
+        assert!((rank.0 - expected[node]).abs() < epsilon, "node {}: {} vs {}", node, rank.0, expected[node]);
+    }
+}
+
+#[test]
+fn pagerank_changing_graph() {
+
+    let rounds: Vec<Vec<(Edge, isize)>> = vec![
+        vec![((0, 1), 1), ((1, 2), 1), ((2, 0), 1), ((2, 3), 1), ((3, 0), 1), ((4, 2), 1)],
+        vec![((2, 3), -1), ((3, 4), 1)],
+        vec![((4, 2), -1), ((1, 3), 1), ((1, 3), 1)],
+    ];
+
+    let (rounds_list, seeds) = (rounds.clone(), vec![0, 4]);
+    let seeds_list = seeds.clone();
+    let (global, personalized, bounded) = timely::execute_directly(move |worker| {
+
+        let (mut edges, mut roots, global, personalized, bounded) = worker.dataflow::<usize, _, _>(|scope| {
+            let (edge_input, edges) = scope.new_collection::<Edge, isize>();
+            let (seed_input, seeds) = scope.new_collection::<Node, isize>();
+            let global = pagerank(&edges, DAMPING, 1e-12, None).inner.capture();
+            let personalized = personalized_pagerank(&edges, &seeds, DAMPING, 1e-12, None).inner.capture();
+            let bounded = pagerank(&edges, DAMPING, 0.0, Some(3)).inner.capture();
+            (edge_input, seed_input, global, personalized, bounded)
+        });
+
+        for seed in seeds_list.iter() {
+            roots.insert(*seed);
+        }
+        for (round, changes) in rounds_list.iter().enumerate() {
+            edges.advance_to(round);
+            for (edge, diff) in changes.iter() {
+                edges.update(*edge, *diff);
+            }
+        }
+        edges.close();
+        roots.close();
+        while worker.step() { }
+
+        (global, personalized, bounded)
+    });
+
+    let global = global.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+    let personalized = personalized.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+    let bounded = bounded.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+
+    let mut graph = HashMap::new();
+    for (round, changes) in rounds.iter().enumerate() {
+        for (edge, diff) in changes.iter() {
+            *graph.entry(*edge).or_insert(0) += diff;
+        }
+        let mut edges = Vec::new();
+        for (edge, count) in graph.iter() {
+            for _ in 0 .. *count { edges.push(*edge); }
+        }
+
+        let contents = |updates: &[((Node, Rank), usize, isize)]| {
+            let mut contents = updates.iter().filter(|(_, time, _)| *time <= round).map(|(data, _, diff)| (*data, *diff)).collect::<Vec<_>>();
+            consolidate(&mut contents);
+            contents
+        };
+
+        assert_close(&contents(&global), &pagerank_sequential(&edges, None, 1000), 1e-9);
+        assert_close(&contents(&personalized), &pagerank_sequential(&edges, Some(&seeds), 1000), 1e-9);
+        assert_close(&contents(&bounded), &pagerank_sequential(&edges, None, 3), 1e-12);
+    }
+}
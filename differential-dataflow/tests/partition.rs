@@ -0,0 +1,85 @@
+use std::ops::Bound;
+
+use differential_dataflow::input::Input;
+use differential_dataflow::operators::{Join, Reduce};
+use differential_dataflow::operators::arrange::{ArrangeByKeyRange, ArrangeBySelfRange, RangePartition};
+
+#[test]
+fn range_partition_parts() {
+
+    let partition = RangePartition::new(vec![30, 10, 20, 10]);
+    assert_eq!(partition.splits(), &[10, 20, 30]);
+    assert_eq!(partition.parts(), 4);
+    assert_eq!((0 .. 40).step_by(5).map(|key| partition.part(&key)).collect::<Vec<_>>(), vec![0, 0, 1, 1, 2, 2, 3, 3]);
+    assert_eq!(partition.parts_overlapping(Bound::Included(&12), Bound::Excluded(&25)), 1 .. 3);
+    assert_eq!(partition.parts_overlapping(Bound::Unbounded, Bound::Included(&5)), 0 .. 1);
+    assert_eq!(partition.parts_overlapping(Bound::Excluded(&30), Bound::Unbounded), 3 .. 4);
+
+    // Quantiles of the sample, with fewer parts when the sample has few distinct keys.
+    assert_eq!(RangePartition::from_sample((0 .. 100).rev().collect(), 4).splits(), &[25, 50, 75]);
+    assert_eq!(RangePartition::from_sample(vec![7; 10], 4).splits(), &[7]);
+    assert_eq!(RangePartition::<u64>::from_sample(Vec::new(), 4).parts(), 1);
+}
+
+#[test]
+fn arrange_by_range() {
+
+    timely::execute(timely::Config::process(2), |worker| {
+
+        let index = worker.index();
+        let (mut input1, mut input2) = worker.dataflow::<u64, _, _>(|scope| {
+
+            let (input1, col1) = scope.new_collection::<(u64, u64), isize>();
+            let (input2, col2) = scope.new_collection::<u64, isize>();
+
+            let partition = RangePartition::new(vec![50]);
+            let arranged1 = col1.arrange_by_key_range(&partition);
+            let arranged2 = col2.arrange_by_self_range(&partition);
+
+            // Each worker holds exactly the keys of its range.
+            let check = partition.clone();
+            arranged1
+                .as_collection(|key, val| (*key, *val))
+                .inspect(move |((key, _), _, _)| assert_eq!(check.part(key), index));
+
+            // Arrangements with the same partition combine without further exchange.
+            arranged1
+                .join_core(&arranged2, |key, val, &()| Some((*key, *val)))
+                .assert_eq(&col1.semijoin(&col2));
+            arranged1
+                .reduce(|_key, input, output| output.push((input.len(), 1)))
+                .assert_eq(&col1.reduce(|_key, input, output| output.push((input.len(), 1))));
+
+            (input1, input2)
+        });
+
+        for round in 0 .. 10u64 {
+            input1.advance_to(round);
+            input2.advance_to(round);
+            for key in 0 .. 10 {
+                input1.insert((key * 10 + round, round + index as u64 * 100));
+                if round > 1 {
+                    input1.remove((key * 10 + round - 2, round - 2 + index as u64 * 100));
+                }
+            }
+            input2.insert(round * 11 + index as u64);
+        }
+
+    }).unwrap();
+}
+
+#[test]
+fn range_partition_worker_samples() {
+
+    timely::execute(timely::Config::process(3), |worker| {
+
+        // Samples differ by worker, and would produce different split points on their own.
+        let index = worker.index() as u64;
+        let sample = (0 .. 30).map(|x| x + 30 * index).collect::<Vec<u64>>();
+        assert_ne!(RangePartition::from_sample(sample.clone(), 3).splits(), &[30, 60]);
+
+        let partition = RangePartition::from_worker_samples(worker, sample, 3);
+        assert_eq!(partition.splits(), &[30, 60]);
+
+    }).unwrap();
+}
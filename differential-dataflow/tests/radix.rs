@@ -0,0 +1,51 @@
+use timely::progress::Antichain;
+
+use differential_dataflow::trace::{Batcher, BatchReader};
+use differential_dataflow::trace::cursor::Cursor;
+use differential_dataflow::trace::implementations::ord_neu::{OrdValBatcher, OrdValRadixBatcher, RcOrdValBuilder};
+use differential_dataflow::trace::implementations::rhh::{HashWrapper, VecBatcher, VecBuilder, VecRadixBatcher};
+
+/// Pseudo-random updates, with repeated keys, values, and times to consolidate.
+fn updates(count: u64) -> Vec<((u64, u64), u64, i64)> {
+    let mut state = 0x2545F4914F6CDD1Du64;
+    (0 .. count).map(|_| {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        let key = (state >> 33) % 1000 + if state & 1 == 0 { 0 } else { 1 << 40 };
+        ((key, (state >> 20) % 3), (state >> 10) % 4, if state & 2 == 0 { 1 } else { -1 })
+    }).collect()
+}
+
+#[test]
+fn radix_chunker_ord() {
+    let mut radix = OrdValRadixBatcher::<u64, u64, u64, i64>::new(None, 0);
+    let mut plain = OrdValBatcher::<u64, u64, u64, i64>::new(None, 0);
+    for _ in 0 .. 4 {
+        radix.push_container(&mut updates(10_000));
+        plain.push_container(&mut updates(10_000));
+    }
+    let radix = radix.seal::<RcOrdValBuilder<_,_,_,_>>(Antichain::new());
+    let plain = plain.seal::<RcOrdValBuilder<_,_,_,_>>(Antichain::new());
+    assert!(radix.len() > 0);
+    assert_eq!(
+        radix.cursor().to_vec(&radix, |k| *k, |v| *v),
+        plain.cursor().to_vec(&plain, |k| *k, |v| *v),
+    );
+}
+
+#[test]
+fn radix_chunker_hashed() {
+    let wrap = |data: Vec<((u64, u64), u64, i64)>| data.into_iter().map(|((k, v), t, r)| ((HashWrapper { inner: k }, v), t, r)).collect::<Vec<_>>();
+    let mut radix = VecRadixBatcher::<HashWrapper<u64>, u64, u64, i64>::new(None, 0);
+    let mut plain = VecBatcher::<HashWrapper<u64>, u64, u64, i64>::new(None, 0);
+    for _ in 0 .. 4 {
+        radix.push_container(&mut wrap(updates(10_000)));
+        plain.push_container(&mut wrap(updates(10_000)));
+    }
+    let radix = radix.seal::<VecBuilder<_,_,_,_>>(Antichain::new());
+    let plain = plain.seal::<VecBuilder<_,_,_,_>>(Antichain::new());
+    assert!(radix.len() > 0);
+    assert_eq!(
+        radix.cursor().to_vec(&radix, |k| k.inner, |v| *v),
+        plain.cursor().to_vec(&plain, |k| k.inner, |v| *v),
+    );
+}
@@ -0,0 +1,68 @@
+use std::ops::Bound;
+
+use differential_dataflow::input::Input;
+use differential_dataflow::operators::{Join, RangeJoin};
+
+/// Compares `range_join` against a filtered cross product, as both inputs change.
+fn range_join_matches<F>(interval: F)
+where
+    F: Fn(&u64)->(Bound<u64>, Bound<u64>)+Clone+Send+Sync+'static,
+{
+    timely::execute(timely::Config::process(2), move |worker| {
+
+        let index = worker.index() as u64;
+        let interval = interval.clone();
+        let (mut points, mut ranges) = worker.dataflow::<u64, _, _>(|scope| {
+
+            let (points_input, points) = scope.new_collection::<(u64, char), isize>();
+            let (ranges_input, ranges) = scope.new_collection::<(u64, u64), isize>();
+
+            let interval2 = interval.clone();
+            let reference = points
+                .map(|x| ((), x))
+                .join(&ranges.map(|x| ((), x)))
+                .filter(move |(_, ((k1, _), (k2, _)))| std::ops::RangeBounds::contains(&interval2(k2), k1))
+                .map(|(_, ((k1, v1), (k2, v2)))| (k1, v1, k2, v2));
+
+            points
+                .range_join(&ranges, interval, |k1, v1, k2, v2| (*k1, *v1, *k2, *v2))
+                .assert_eq(&reference);
+
+            (points_input, ranges_input)
+        });
+
+        // Each worker introduces and retracts its own points and ranges.
+        for round in 0 .. 10u64 {
+            points.advance_to(round);
+            ranges.advance_to(round);
+            for i in 0 .. 5 {
+                let key = (round * 7 + i * 13 + index * 5) % 40;
+                points.insert((key, (b'a' + i as u8) as char));
+                ranges.insert(((round * 11 + i * 3 + index) % 40, round));
+            }
+            if round >= 3 {
+                let past = round - 3;
+                for i in 0 .. 5 {
+                    let key = (past * 7 + i * 13 + index * 5) % 40;
+                    points.remove((key, (b'a' + i as u8) as char));
+                }
+                ranges.remove(((past * 11 + index) % 40, past));
+            }
+        }
+    }).unwrap();
+}
+
+#[test]
+fn range_join_band() {
+    range_join_matches(|k2| (Bound::Included(k2.saturating_sub(3)), Bound::Excluded(k2 + 3)));
+}
+
+#[test]
+fn range_join_less_than() {
+    range_join_matches(|k2| (Bound::Unbounded, Bound::Excluded(*k2)));
+}
+
+#[test]
+fn range_join_greater_than() {
+    range_join_matches(|k2| (Bound::Excluded(*k2), Bound::Unbounded));
+}
@@ -0,0 +1,50 @@
+use timely::dataflow::operators::{Capture, Probe};
+use timely::dataflow::operators::capture::Extract;
+use timely::progress::Antichain;
+use timely::progress::frontier::AntichainRef;
+
+use differential_dataflow::input::Input;
+use differential_dataflow::consolidation::consolidate;
+use differential_dataflow::operators::arrange::ArrangeBySelf;
+use differential_dataflow::trace::TraceReader;
+
+#[test]
+fn retain_history_as_of() {
+
+    let captured = timely::execute_directly(|worker| {
+
+        let (mut input, mut trace, probe) = worker.dataflow::<u64, _, _>(|scope| {
+            let (input, data) = scope.new_collection::<u64, isize>();
+            let arranged = data.arrange_by_self();
+            arranged.retain_history(|time| time.saturating_sub(3));
+            (input, arranged.trace, arranged.stream.probe())
+        });
+
+        // The collection contains only `round` in each round, and our reader follows the frontier.
+        for round in 0 .. 10u64 {
+            input.insert(round);
+            if round > 0 {
+                input.remove(round - 1);
+            }
+            input.advance_to(round + 1);
+            input.flush();
+            while probe.less_than(input.time()) { worker.step(); }
+            trace.set_logical_compaction(AntichainRef::new(&[round + 1]));
+            trace.set_physical_compaction(AntichainRef::new(&[round + 1]));
+        }
+
+        // Query the contents as of each retained time.
+        (7 .. 10u64).map(|as_of| {
+            worker.dataflow(|scope| {
+                let (arranged, _button) = trace.import_frontier_core(scope, "AsOf", Antichain::from_elem(as_of), Antichain::from_elem(as_of + 1));
+                arranged.as_collection(|key, _| *key).inner.capture()
+            })
+        }).collect::<Vec<_>>()
+    });
+
+    for (as_of, captured) in (7 .. 10u64).zip(captured) {
+        let mut contents = captured.extract().into_iter().flat_map(|(_, data)| data).map(|(key, _, diff)| (key, diff)).collect::<Vec<_>>();
+        consolidate(&mut contents);
+        assert_eq!(contents, vec![(as_of, 1)]);
+    }
+}
@@ -0,0 +1,91 @@
+use timely::dataflow::operators::Capture;
+use timely::dataflow::operators::capture::Extract;
+use timely::dataflow::operators::generic::OperatorInfo;
+use timely::progress::Antichain;
+
+use differential_dataflow::input::InputSession;
+use differential_dataflow::operators::arrange::Arrange;
+use differential_dataflow::trace::{Batcher, Trace, TraceReader};
+use differential_dataflow::trace::cursor::Cursor;
+use differential_dataflow::trace::implementations::ord_neu::{OrdKeyBatcher, OrdKeySpine, RcOrdKeyBuilder};
+use differential_dataflow::trace::implementations::rhh::{HashWrapper, VecKeyBatcher, VecKeyBuilder, VecKeySpine};
+
+/// Updates that add and retract keys over rounds, so that merges must cancel some keys.
+fn updates(round: usize) -> Vec<((u64, ()), usize, i64)> {
+    (0 .. 100u64).map(|i| ((i * 7 + round as u64) % 250, ())).map(|kv| (kv, round, if round % 4 == 3 { -1 } else { 1 })).collect()
+}
+
+#[test]
+fn rhh_key_contents() {
+
+    let mut rhh_trace = VecKeySpine::<HashWrapper<u64>, usize, i64>::new(OperatorInfo::new(0, 0, [].into()), None, None);
+    let mut ord_trace = OrdKeySpine::<u64, usize, i64>::new(OperatorInfo::new(0, 0, [].into()), None, None);
+    let mut rhh_batcher = VecKeyBatcher::<HashWrapper<u64>, usize, i64>::new(None, 0);
+    let mut ord_batcher = OrdKeyBatcher::<u64, usize, i64>::new(None, 0);
+
+    for round in 0 .. 20 {
+        let mut wrapped = updates(round).into_iter().map(|((k, ()), t, r)| ((HashWrapper { inner: k }, ()), t, r)).collect();
+        rhh_batcher.push_container(&mut wrapped);
+        ord_batcher.push_container(&mut updates(round));
+        rhh_trace.insert(rhh_batcher.seal::<VecKeyBuilder<_,_,_>>(Antichain::from_elem(round + 1)));
+        ord_trace.insert(ord_batcher.seal::<RcOrdKeyBuilder<_,_,_>>(Antichain::from_elem(round + 1)));
+    }
+
+    let (mut rhh_cursor, rhh_storage) = rhh_trace.cursor();
+    let (mut ord_cursor, ord_storage) = ord_trace.cursor();
+    let mut rhh_contents = rhh_cursor.to_vec(&rhh_storage, |k| k.inner, |v| *v);
+    let mut ord_contents = ord_cursor.to_vec(&ord_storage, |k| *k, |v| *v);
+    for (_, times) in rhh_contents.iter_mut().chain(ord_contents.iter_mut()) {
+        differential_dataflow::consolidation::consolidate(times);
+    }
+    rhh_contents.retain(|(_, times)| !times.is_empty());
+    ord_contents.retain(|(_, times)| !times.is_empty());
+    rhh_contents.sort();
+    assert_eq!(rhh_contents, ord_contents);
+
+    // Each present key can be sought directly, and absent keys are not found.
+    let present = rhh_contents.iter().map(|((k, ()), _)| *k).chain(250 .. 300).collect::<Vec<_>>();
+    for key in present {
+        let wrapped = HashWrapper { inner: key };
+        rhh_cursor.rewind_keys(&rhh_storage);
+        rhh_cursor.seek_key(&rhh_storage, &wrapped);
+        assert_eq!(rhh_cursor.get_key(&rhh_storage) == Some(&wrapped), key < 250, "key {:?}", key);
+    }
+}
+
+#[test]
+fn rhh_key_semijoin() {
+
+    let captured = timely::execute(timely::Config::process(2), move |worker| {
+        let mut data = InputSession::<u64, u64, isize>::new();
+        let mut keys = InputSession::<u64, u64, isize>::new();
+        let captured = worker.dataflow(|scope| {
+            let data = data.to_collection(scope).map(|x| HashWrapper { inner: x });
+            let keys = keys.to_collection(scope).map(|x| HashWrapper { inner: x });
+            let data = data.arrange::<VecKeyBatcher<_,_,_>, VecKeyBuilder<_,_,_>, VecKeySpine<_,_,_>>();
+            let keys = keys.arrange::<VecKeyBatcher<_,_,_>, VecKeyBuilder<_,_,_>, VecKeySpine<_,_,_>>();
+            keys.join_core(&data, |k, &(), &()| Some(k.inner))
+                .consolidate()
+                .inner
+                .capture()
+        });
+        if worker.index() == 0 {
+            for round in 0 .. 4 {
+                data.advance_to(round);
+                keys.advance_to(round);
+                for x in 0 .. 50 { data.insert(x + 10 * round); }
+                keys.insert(3 * round);
+                keys.insert(3 * round + 100);
+            }
+        }
+        data.close();
+        keys.close();
+        while worker.has_dataflows() { worker.step(); }
+        captured
+    }).unwrap().join().into_iter().map(|x| x.unwrap()).collect::<Vec<_>>();
+
+    let mut results = captured.into_iter().flat_map(|c| c.extract()).flat_map(|(_, data)| data).collect::<Vec<_>>();
+    results.sort();
+    // Keys 0, 3, 6, 9 are each present in data from the round they are introduced.
+    assert_eq!(results, vec![(0, 0, 1), (3, 1, 1), (6, 2, 1), (9, 3, 1)]);
+}
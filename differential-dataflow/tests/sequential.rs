@@ -0,0 +1,122 @@
+use rand::{Rng, SeedableRng, StdRng};
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use timely::dataflow::operators::Capture;
+use timely::dataflow::operators::capture::Extract;
+
+use differential_dataflow::input::Input;
+use differential_dataflow::consolidation::consolidate;
+use differential_dataflow::algorithms::graphs::sequential::{color, maximal_independent_set, maximal_matching};
+
+type Node = u32;
+type Edge = (Node, Node);
+
+#[test] fn sequential_10_20_50() { test_sizes(10, 20, 50); }
+#[test] fn sequential_50_200_20() { test_sizes(50, 200, 20); }
+
+fn test_sizes(nodes: Node, edges: usize, rounds: usize) {
+
+    let seed: &[_] = &[1, 2, 3, 4];
+    let mut rng: StdRng = SeedableRng::from_seed(seed);
+
+    // Directed edges, each present at most once, with each later round adding and removing edges.
+    let mut present = BTreeSet::new();
+    let mut updates = vec![Vec::new()];
+    for _ in 0 .. edges {
+        let edge = (rng.gen_range(0, nodes), rng.gen_range(0, nodes));
+        if present.insert(edge) { updates[0].push((edge, 1)); }
+    }
+    let mut graphs = vec![present.clone()];
+    for _ in 1 .. rounds {
+        let mut round = Vec::new();
+        for _ in 0 .. 2 {
+            let edge = (rng.gen_range(0, nodes), rng.gen_range(0, nodes));
+            if present.insert(edge) { round.push((edge, 1)); }
+            if !present.is_empty() {
+                let edge = *present.iter().nth(rng.gen_range(0, present.len())).unwrap();
+                present.remove(&edge);
+                round.push((edge, -1));
+            }
+        }
+        updates.push(round);
+        graphs.push(present.clone());
+    }
+
+    let updates_list = updates.clone();
+    let (colors, independent, matching) = timely::execute_directly(move |worker| {
+
+        let (mut input, colors, independent, matching) = worker.dataflow::<usize, _, _>(|scope| {
+            let (input, edges) = scope.new_collection::<Edge, isize>();
+            let colors = color(&edges).inner.capture();
+            let independent = maximal_independent_set(&edges).inner.capture();
+            let matching = maximal_matching(&edges).inner.capture();
+            (input, colors, independent, matching)
+        });
+
+        for (round, changes) in updates_list.iter().enumerate() {
+            input.advance_to(round);
+            for (edge, diff) in changes.iter() {
+                input.update(*edge, *diff);
+            }
+        }
+        input.close();
+        while worker.step() { }
+
+        (colors, independent, matching)
+    });
+
+    let colors = colors.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+    let independent = independent.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+    let matching = matching.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+
+    for (round, graph) in graphs.iter().enumerate() {
+
+        // Nodes with incident edges, and their neighbors other than themselves.
+        let mut neighbors: BTreeMap<Node, BTreeSet<Node>> = BTreeMap::new();
+        for &(x, y) in graph.iter() {
+            neighbors.entry(x).or_default();
+            neighbors.entry(y).or_default();
+            if x != y {
+                neighbors.get_mut(&x).unwrap().insert(y);
+                neighbors.get_mut(&y).unwrap().insert(x);
+            }
+        }
+
+        // Each node has one color, distinct from its neighbors' and the least unused by lower neighbors.
+        let colors = contents(&colors, round).into_iter().collect::<BTreeMap<_, _>>();
+        assert_eq!(colors.keys().collect::<Vec<_>>(), neighbors.keys().collect::<Vec<_>>());
+        for (node, adjacent) in neighbors.iter() {
+            assert!(adjacent.iter().all(|other| colors[other] != colors[node]));
+            let lower = adjacent.iter().filter(|other| *other < node).map(|other| colors[other]).collect::<BTreeSet<_>>();
+            assert_eq!(colors[node], (1 ..).find(|c| !lower.contains(c)).unwrap());
+        }
+
+        // No two independent nodes are adjacent, and every other node has an independent neighbor.
+        let independent = contents(&independent, round).into_iter().collect::<BTreeSet<_>>();
+        for (node, adjacent) in neighbors.iter() {
+            let covered = adjacent.iter().filter(|other| independent.contains(other)).count();
+            if independent.contains(node) { assert_eq!(covered, 0); }
+            else { assert!(covered > 0); }
+        }
+
+        // Matched edges are edges with no endpoints in common, and every other edge shares an endpoint with one.
+        let matching = contents(&matching, round);
+        let mut matched = BTreeSet::new();
+        for &(x, y) in matching.iter() {
+            assert!(x < y && (graph.contains(&(x, y)) || graph.contains(&(y, x))));
+            assert!(matched.insert(x) && matched.insert(y));
+        }
+        for &(x, y) in graph.iter() {
+            assert!(x == y || matched.contains(&x) || matched.contains(&y));
+        }
+    }
+}
+
+// The records present at `round`, each of which must have multiplicity one.
+fn contents<D: Ord+Clone>(updates: &[(D, usize, isize)], round: usize) -> Vec<D> {
+    let mut contents = updates.iter().filter(|(_, time, _)| *time <= round).map(|(data, _, diff)| (data.clone(), *diff)).collect::<Vec<_>>();
+    consolidate(&mut contents);
+    assert!(contents.iter().all(|(_, diff)| *diff == 1));
+    contents.into_iter().map(|(data, _)| data).collect()
+}
@@ -0,0 +1,130 @@
+use timely::dataflow::operators::{Capture, Probe};
+use timely::dataflow::operators::capture::Extract;
+use timely::progress::Antichain;
+
+use differential_dataflow::input::InputSession;
+use differential_dataflow::operators::arrange::ArrangeByKey;
+use differential_dataflow::operators::arrange::snapshot;
+use differential_dataflow::trace::{BatchReader, TraceReader};
+use differential_dataflow::trace::implementations::ValSpine;
+use differential_dataflow::trace::cursor::Cursor;
+
+fn updates(round: u64) -> impl Iterator<Item=(u64, u64)> {
+    (0 .. 10u64).map(move |i| (i % 3, i + round))
+}
+
+#[test]
+fn snapshot_restore() {
+
+    let directory = std::env::temp_dir().join(format!("dd-snapshot-test-{}", std::process::id()));
+
+    // Arrange the first three rounds of updates, and snapshot the arrangement.
+    let written = directory.clone();
+    timely::execute(timely::Config::thread(), move |worker| {
+        let mut input = InputSession::<u64, (u64, u64), isize>::new();
+        let (trace, probe) = worker.dataflow(|scope| {
+            let arranged = input.to_collection(scope).arrange_by_key();
+            (arranged.trace, arranged.stream.probe())
+        });
+        for round in 0 .. 3 {
+            input.advance_to(round);
+            for update in updates(round) { input.insert(update); }
+        }
+        input.advance_to(3);
+        input.flush();
+        worker.step_while(|| probe.less_than(input.time()));
+
+        let upper = snapshot::write(&trace, &written).unwrap();
+        assert_eq!(upper, Antichain::from_elem(3));
+    }).unwrap();
+
+    // Restore the arrangement, and continue with two more rounds of updates.
+    let restored = directory.clone();
+    let captured = timely::execute(timely::Config::thread(), move |worker| {
+        let batches = snapshot::read(&restored).unwrap();
+        let mut input = InputSession::<u64, (u64, u64), isize>::new();
+        let (mut trace, probe, captured) = worker.dataflow(|scope| {
+            let arranged = snapshot::arrange_by_key_from(&input.to_collection(scope), "Restored", batches);
+            let captured = arranged.as_collection(|k, v| (*k, *v)).inner.capture();
+            (arranged.trace, arranged.stream.probe(), captured)
+        });
+        for round in 3 .. 5 {
+            input.advance_to(round);
+            for update in updates(round) { input.insert(update); }
+        }
+        input.advance_to(5);
+        input.flush();
+        worker.step_while(|| probe.less_than(input.time()));
+
+        let mut expected = std::collections::BTreeMap::<(u64, u64), Vec<(u64, isize)>>::new();
+        for round in 0 .. 5 {
+            for update in updates(round) { expected.entry(update).or_default().push((round, 1)); }
+        }
+        let expected = expected.into_iter().collect::<Vec<_>>();
+
+        let (mut cursor, storage) = trace.cursor();
+        let mut contents = cursor.to_vec(&storage, |k| *k, |v| *v);
+        for (_, times) in contents.iter_mut() { times.sort(); }
+        assert_eq!(contents, expected);
+
+        captured
+    }).unwrap().join().into_iter().map(|x| x.unwrap()).next().unwrap();
+
+    // Restored batches are also presented downstream, as if arranged anew.
+    let mut results = captured.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+    results.sort();
+    let mut expected = (0 .. 5).flat_map(|round| updates(round).map(move |kv| (kv, round, 1))).collect::<Vec<_>>();
+    expected.sort();
+    assert_eq!(results, expected);
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn snapshot_replace() {
+
+    let directory = std::env::temp_dir().join(format!("dd-snapshot-replace-test-{}", std::process::id()));
+
+    let written = directory.clone();
+    timely::execute(timely::Config::thread(), move |worker| {
+        let mut input = InputSession::<u64, (u64, u64), isize>::new();
+        let (trace, probe) = worker.dataflow(|scope| {
+            let arranged = input.to_collection(scope).arrange_by_key();
+            (arranged.trace, arranged.stream.probe())
+        });
+        let batch_files = || {
+            let mut names = std::fs::read_dir(&written).unwrap().map(|entry| entry.unwrap().file_name().into_string().unwrap()).filter(|name| name.starts_with("batch-")).collect::<Vec<_>>();
+            names.sort();
+            names
+        };
+
+        input.advance_to(0);
+        for update in updates(0) { input.insert(update); }
+        input.advance_to(1);
+        input.flush();
+        worker.step_while(|| probe.less_than(input.time()));
+        assert_eq!(snapshot::write(&trace, &written).unwrap(), Antichain::from_elem(1));
+        let first = batch_files();
+        assert!(!first.is_empty());
+
+        // The leftovers of an interrupted write are neither read nor reused.
+        std::fs::write(written.join("batch-7-0.bin"), b"interrupted").unwrap();
+        let batches: Vec<<ValSpine<u64, u64, u64, isize> as TraceReader>::Batch> = snapshot::read(&written).unwrap();
+        assert_eq!(batches.last().map(|batch| batch.upper().clone()), Some(Antichain::from_elem(1)));
+
+        input.advance_to(1);
+        for update in updates(1) { input.insert(update); }
+        input.advance_to(2);
+        input.flush();
+        worker.step_while(|| probe.less_than(input.time()));
+        assert_eq!(snapshot::write(&trace, &written).unwrap(), Antichain::from_elem(2));
+
+        // The new snapshot uses fresh files, and the files of earlier generations are removed.
+        let second = batch_files();
+        assert!(!second.is_empty());
+        assert!(second.iter().all(|name| name.starts_with("batch-8-")));
+        assert!(first.iter().all(|name| !second.contains(name)));
+    }).unwrap();
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}
@@ -0,0 +1,184 @@
+use timely::dataflow::operators::generic::OperatorInfo;
+use timely::progress::Antichain;
+
+use differential_dataflow::trace::implementations::{ValBatcher, ValBuilder, ValSpine};
+use differential_dataflow::trace::implementations::spill::{self, SpillPolicy, SpillValBatcher, SpillValBuilder, SpillValSpine};
+use differential_dataflow::trace::{BatchReader, Trace, TraceReader, Batcher};
+use differential_dataflow::trace::cursor::Cursor;
+
+type SpillTrace = SpillValSpine<u64, u64, usize, i64>;
+type PlainTrace = ValSpine<u64, u64, usize, i64>;
+
+fn updates(round: usize) -> Vec<((u64, u64), usize, i64)> {
+    (0 .. 100u64).map(|i| ((i % 17, i + round as u64), round, if round % 3 == 2 { -1 } else { 1 })).collect()
+}
+
+#[test]
+fn test_spill_contents() {
+
+    let directory = std::env::temp_dir().join(format!("dd-spill-test-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let mut policy = SpillPolicy::new(&directory, 0);
+    policy.chunk_updates = 16;
+    spill::set_policy(Some(policy));
+
+    let mut spill_trace = SpillTrace::new(OperatorInfo::new(0, 0, [].into()), None, None);
+    let mut plain_trace = PlainTrace::new(OperatorInfo::new(0, 0, [].into()), None, None);
+    let mut spill_batcher = SpillValBatcher::<u64, u64, usize, i64>::new(None, 0);
+    let mut plain_batcher = ValBatcher::<u64, u64, usize, i64>::new(None, 0);
+
+    for round in 0 .. 20 {
+        spill_batcher.push_container(&mut updates(round));
+        plain_batcher.push_container(&mut updates(round));
+        spill_trace.insert(spill_batcher.seal::<SpillValBuilder<_,_,_,_>>(Antichain::from_elem(round + 1)));
+        plain_trace.insert(plain_batcher.seal::<ValBuilder<_,_,_,_>>(Antichain::from_elem(round + 1)));
+    }
+
+    assert!(std::fs::read_dir(&directory).unwrap().count() > 0);
+
+    {
+        let (mut spill_cursor, spill_storage) = spill_trace.cursor();
+        let (mut plain_cursor, plain_storage) = plain_trace.cursor();
+        let mut spill_contents = spill_cursor.to_vec(&spill_storage, |k| k.clone(), |v| v.clone());
+        let mut plain_contents = plain_cursor.to_vec(&plain_storage, |k| k.clone(), |v| v.clone());
+        for (_, times) in spill_contents.iter_mut().chain(plain_contents.iter_mut()) {
+            differential_dataflow::consolidation::consolidate(times);
+        }
+        assert_eq!(spill_contents, plain_contents);
+    }
+
+    // Dropping the trace should remove all spilled files.
+    drop(spill_trace);
+    spill::set_policy(None);
+    assert_eq!(std::fs::read_dir(&directory).unwrap().count(), 0);
+    std::fs::remove_dir(&directory).unwrap();
+}
+
+#[test]
+fn test_spill_write_failure() {
+
+    // A directory that does not exist, so that spilling fails and batches remain resident.
+    let directory = std::env::temp_dir().join(format!("dd-spill-missing-{}", std::process::id()));
+    spill::set_policy(Some(SpillPolicy::new(&directory, 0)));
+
+    let mut spill_trace = SpillTrace::new(OperatorInfo::new(0, 0, [].into()), None, None);
+    let mut plain_trace = PlainTrace::new(OperatorInfo::new(0, 0, [].into()), None, None);
+    let mut spill_batcher = SpillValBatcher::<u64, u64, usize, i64>::new(None, 0);
+    let mut plain_batcher = ValBatcher::<u64, u64, usize, i64>::new(None, 0);
+
+    for round in 0 .. 20 {
+        spill_batcher.push_container(&mut updates(round));
+        plain_batcher.push_container(&mut updates(round));
+        spill_trace.insert(spill_batcher.seal::<SpillValBuilder<_,_,_,_>>(Antichain::from_elem(round + 1)));
+        plain_trace.insert(plain_batcher.seal::<ValBuilder<_,_,_,_>>(Antichain::from_elem(round + 1)));
+    }
+
+    spill::set_policy(None);
+    assert!(spill::take_error().is_some());
+
+    let (mut spill_cursor, spill_storage) = spill_trace.cursor();
+    let (mut plain_cursor, plain_storage) = plain_trace.cursor();
+    let mut spill_contents = spill_cursor.to_vec(&spill_storage, |k| k.clone(), |v| v.clone());
+    let mut plain_contents = plain_cursor.to_vec(&plain_storage, |k| k.clone(), |v| v.clone());
+    for (_, times) in spill_contents.iter_mut().chain(plain_contents.iter_mut()) {
+        differential_dataflow::consolidation::consolidate(times);
+    }
+    assert_eq!(spill_contents, plain_contents);
+}
+
+#[test]
+fn test_spill_seek() {
+
+    let directory = std::env::temp_dir().join(format!("dd-spill-seek-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let mut policy = SpillPolicy::new(&directory, 0);
+    policy.chunk_updates = 16;
+    spill::set_policy(Some(policy));
+
+    let mut spill_trace = SpillTrace::new(OperatorInfo::new(0, 0, [].into()), None, None);
+    let mut plain_trace = PlainTrace::new(OperatorInfo::new(0, 0, [].into()), None, None);
+    let mut spill_batcher = SpillValBatcher::<u64, u64, usize, i64>::new(None, 0);
+    let mut plain_batcher = ValBatcher::<u64, u64, usize, i64>::new(None, 0);
+
+    for round in 0 .. 20 {
+        spill_batcher.push_container(&mut updates(round));
+        plain_batcher.push_container(&mut updates(round));
+        spill_trace.insert(spill_batcher.seal::<SpillValBuilder<_,_,_,_>>(Antichain::from_elem(round + 1)));
+        plain_trace.insert(plain_batcher.seal::<ValBuilder<_,_,_,_>>(Antichain::from_elem(round + 1)));
+    }
+
+    // Seeking a key in a batch of several spilled chunks reads only the chunk containing it.
+    let mut spilled = None;
+    spill_trace.map_batches(|batch| if batch.is_spilled() && batch.chunks() > 2 { spilled = Some(batch.clone()); });
+    let batch = spilled.expect("no batch spilled in several chunks");
+    let mut cursor = batch.cursor();
+    cursor.seek_key(&batch, &16);
+    assert_eq!(cursor.get_key(&batch), Some(&16));
+    assert!(!batch.is_resident());
+
+    let (mut spill_cursor, spill_storage) = spill_trace.cursor();
+    let (mut plain_cursor, plain_storage) = plain_trace.cursor();
+    for key in [0, 3, 4, 10, 16, 20] {
+        spill_cursor.seek_key(&spill_storage, &key);
+        plain_cursor.seek_key(&plain_storage, &key);
+        assert_eq!(spill_cursor.get_key(&spill_storage), plain_cursor.get_key(&plain_storage));
+
+        let mut spill_vals = Vec::new();
+        while let Some(val) = spill_cursor.get_val(&spill_storage) {
+            let mut times = Vec::new();
+            spill_cursor.map_times(&spill_storage, |time, diff| times.push((*time, *diff)));
+            differential_dataflow::consolidation::consolidate(&mut times);
+            if !times.is_empty() { spill_vals.push((*val, times)); }
+            spill_cursor.step_val(&spill_storage);
+        }
+        let mut plain_vals = Vec::new();
+        while let Some(val) = plain_cursor.get_val(&plain_storage) {
+            let mut times = Vec::new();
+            plain_cursor.map_times(&plain_storage, |time, diff| times.push((*time, *diff)));
+            differential_dataflow::consolidation::consolidate(&mut times);
+            if !times.is_empty() { plain_vals.push((*val, times)); }
+            plain_cursor.step_val(&plain_storage);
+        }
+        assert_eq!(spill_vals, plain_vals);
+    }
+
+    drop(batch);
+    drop(spill_storage);
+    drop(spill_trace);
+    spill::set_policy(None);
+    std::fs::remove_dir(&directory).unwrap();
+}
+
+#[test]
+fn test_spill_read_failure() {
+
+    let directory = std::env::temp_dir().join(format!("dd-spill-lost-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let mut policy = SpillPolicy::new(&directory, 0);
+    policy.chunk_updates = 16;
+    spill::set_policy(Some(policy));
+
+    let mut spill_trace = SpillTrace::new(OperatorInfo::new(0, 0, [].into()), None, None);
+    let mut spill_batcher = SpillValBatcher::<u64, u64, usize, i64>::new(None, 0);
+
+    for round in 0 .. 20 {
+        spill_batcher.push_container(&mut updates(round));
+        spill_trace.insert(spill_batcher.seal::<SpillValBuilder<_,_,_,_>>(Antichain::from_elem(round + 1)));
+    }
+
+    // Remove the spilled files out from under the trace, so that reading them fails.
+    for entry in std::fs::read_dir(&directory).unwrap() {
+        std::fs::remove_file(entry.unwrap().path()).unwrap();
+    }
+    assert!(spill::take_error().is_none());
+
+    // Reading reports the error rather than panicking.
+    let (mut spill_cursor, spill_storage) = spill_trace.cursor();
+    spill_cursor.to_vec(&spill_storage, |k| k.clone(), |v| v.clone());
+    assert!(spill::take_error().is_some());
+
+    drop(spill_storage);
+    drop(spill_trace);
+    spill::set_policy(None);
+    std::fs::remove_dir(&directory).unwrap();
+}
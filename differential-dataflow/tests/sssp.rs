@@ -0,0 +1,120 @@
+use rand::{Rng, SeedableRng, StdRng};
+
+use std::collections::HashMap;
+
+use timely::dataflow::operators::Capture;
+use timely::dataflow::operators::capture::Extract;
+
+use differential_dataflow::input::Input;
+use differential_dataflow::consolidation::consolidate;
+use differential_dataflow::algorithms::graphs::sssp::sssp;
+use differential_dataflow::algorithms::graphs::bijkstra::bidijkstra_weighted;
+
+type Node = usize;
+type Weight = u64;
+type Edge = (Node, (Node, Weight));
+
+#[test] fn sssp_10_20_100() { test_sizes(10, 20, 100); }
+#[test] fn sssp_100_400_10() { test_sizes(100, 400, 10); }
+
+fn test_sizes(nodes: usize, edges: usize, rounds: usize) {
+
+    let seed: &[_] = &[1, 2, 3, 4];
+    let mut rng: StdRng = SeedableRng::from_seed(seed);
+
+    // Edges present at each round, where each round removes an edge, and increases the weight of another.
+    let mut graph = Vec::new();
+    for _ in 0 .. edges {
+        graph.push((rng.gen_range(0, nodes), (rng.gen_range(0, nodes), rng.gen_range(0, 10))));
+    }
+    let mut updates = vec![graph.iter().map(|edge| (*edge, 1)).collect::<Vec<_>>()];
+    for _ in 1 .. rounds {
+        let mut round = Vec::new();
+        let removed = graph.swap_remove(rng.gen_range(0, graph.len()));
+        round.push((removed, -1));
+        let index = rng.gen_range(0, graph.len());
+        let (src, (dst, weight)) = graph[index];
+        graph[index] = (src, (dst, weight + rng.gen_range(1, 5)));
+        round.push(((src, (dst, weight)), -1));
+        round.push((graph[index], 1));
+        let added = (rng.gen_range(0, nodes), (rng.gen_range(0, nodes), rng.gen_range(0, 10)));
+        graph.push(added);
+        round.push((added, 1));
+        updates.push(round);
+    }
+
+    let goals = (0 .. 10).map(|index| (index % nodes, (index * 7 + 3) % nodes)).collect::<Vec<_>>();
+
+    let (goals_list, updates_list) = (goals.clone(), updates.clone());
+    let (dists, paths) = timely::execute_directly(move |worker| {
+
+        let (mut edges, mut roots, mut queries, dists, paths) = worker.dataflow::<usize, _, _>(|scope| {
+            let (edge_input, edges) = scope.new_collection::<Edge, isize>();
+            let (root_input, roots) = scope.new_collection::<Node, isize>();
+            let (query_input, queries) = scope.new_collection::<(Node, Node), isize>();
+            let dists = sssp(&edges, &roots).inner.capture();
+            let paths = bidijkstra_weighted(&edges, &queries).inner.capture();
+            (edge_input, root_input, query_input, dists, paths)
+        });
+
+        roots.insert(0);
+        for goal in goals_list.iter() {
+            queries.insert(*goal);
+        }
+        for (round, changes) in updates_list.iter().enumerate() {
+            edges.advance_to(round);
+            for (edge, diff) in changes.iter() {
+                edges.update(*edge, *diff);
+            }
+        }
+        edges.close();
+        roots.close();
+        queries.close();
+        while worker.step() { }
+
+        (dists, paths)
+    });
+
+    let dists = dists.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+    let paths = paths.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+
+    // Replay the edge updates and compare the results at each round with sequential computation.
+    let mut edges = HashMap::new();
+    for (round, changes) in updates.iter().enumerate() {
+        for (edge, diff) in changes.iter() {
+            *edges.entry(*edge).or_insert(0) += diff;
+        }
+        edges.retain(|_, count| *count != 0);
+
+        let mut expected = shortest_paths(nodes, &edges, 0).into_iter().enumerate().filter_map(|(node, dist)| dist.map(|dist| ((node, dist), 1))).collect::<Vec<_>>();
+        consolidate(&mut expected);
+        let mut actual = dists.iter().filter(|(_, time, _)| *time <= round).map(|(data, _, diff)| (*data, *diff)).collect::<Vec<_>>();
+        consolidate(&mut actual);
+        assert_eq!(actual, expected);
+
+        let mut expected = goals.iter().filter_map(|&(src, dst)| shortest_paths(nodes, &edges, src)[dst].map(|dist| (((src, dst), dist), 1))).collect::<Vec<_>>();
+        consolidate(&mut expected);
+        let mut actual = paths.iter().filter(|(_, time, _)| *time <= round).map(|(data, _, diff)| (*data, *diff)).collect::<Vec<_>>();
+        consolidate(&mut actual);
+        assert_eq!(actual, expected);
+    }
+}
+
+// Bellman-Ford distances from `root`.
+fn shortest_paths(nodes: usize, edges: &HashMap<Edge, isize>, root: Node) -> Vec<Option<Weight>> {
+    let mut dists = vec![None; nodes];
+    dists[root] = Some(0);
+    let mut changes = true;
+    while changes {
+        changes = false;
+        for &(src, (dst, weight)) in edges.keys() {
+            if let Some(dist) = dists[src] {
+                if dists[dst].map(|d| d > dist + weight).unwrap_or(true) {
+                    dists[dst] = Some(dist + weight);
+                    changes = true;
+                }
+            }
+        }
+    }
+    dists
+}
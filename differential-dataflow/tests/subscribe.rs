@@ -0,0 +1,43 @@
+use differential_dataflow::input::Input;
+
+#[test]
+fn subscribe_times_and_snapshot() {
+
+    timely::execute_directly(|worker| {
+
+        let (mut input, mut subscribe) = worker.dataflow::<u64, _, _>(|scope| {
+            let (input, data) = scope.new_collection::<(u64, char), isize>();
+            (input, data.map(|(key, val)| (key % 2, val)).subscribe())
+        });
+
+        // Several times complete together, and are yielded in order.
+        input.insert((0, 'a'));
+        input.insert((2, 'a'));
+        input.advance_to(1);
+        input.insert((1, 'b'));
+        input.remove((2, 'a'));
+        input.advance_to(2);
+        input.insert((3, 'c'));
+        input.remove((3, 'c'));
+        input.advance_to(3);
+        input.flush();
+        while subscribe.frontier().less_equal(&2) { worker.step(); }
+
+        assert_eq!(subscribe.snapshot(), vec![((0, 'a'), 1), ((1, 'b'), 1)]);
+        assert_eq!(subscribe.by_ref().collect::<Vec<_>>(), vec![
+            (0, vec![((0, 'a'), 2)]),
+            (1, vec![((0, 'a'), -1), ((1, 'b'), 1)]),
+        ]);
+
+        // Updates at incomplete times are not yet yielded.
+        input.remove((0, 'a'));
+        input.flush();
+        worker.step();
+        assert_eq!(subscribe.next(), None);
+
+        input.close();
+        while !subscribe.done() { worker.step(); }
+        assert_eq!(subscribe.snapshot(), vec![((1, 'b'), 1)]);
+        assert_eq!(subscribe.collect::<Vec<_>>(), vec![(3, vec![((0, 'a'), -1)])]);
+    });
+}
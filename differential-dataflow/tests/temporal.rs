@@ -0,0 +1,84 @@
+use timely::dataflow::operators::Capture;
+use timely::dataflow::operators::capture::Extract;
+
+use differential_dataflow::input::Input;
+use differential_dataflow::consolidation::consolidate_updates;
+use differential_dataflow::operators::Temporal;
+
+fn collect<D: Ord, T: Ord, R: differential_dataflow::difference::Semigroup>(extracted: Vec<(T, Vec<(D, T, R)>)>) -> Vec<(D, T, R)> {
+    let mut updates = extracted.into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+    consolidate_updates(&mut updates);
+    updates.sort_by(|x, y| (&x.1, &x.0).cmp(&(&y.1, &y.0)));
+    updates
+}
+
+#[test]
+fn temporal_filter() {
+
+    let captured = timely::example(|scope| {
+        let (mut input, data) = scope.new_collection::<u64, isize>();
+        let captured = data.temporal_filter(|x| (*x, Some(x + 2))).inner.capture();
+        input.insert(1);
+        input.insert(2);
+        input.insert(3);
+        input.advance_to(2);
+        // Retracting before the record is visible cancels it entirely.
+        input.remove(3);
+        input.advance_to(4);
+        // Inserting after the interval has closed produces nothing.
+        input.insert(1);
+        captured
+    });
+
+    assert_eq!(collect(captured.extract()), vec![
+        (1, 1, 1),
+        (2, 2, 1),
+        (1, 3, -1),
+        (2, 4, -1),
+    ]);
+}
+
+#[test]
+fn sliding_window() {
+
+    let captured = timely::example(|scope| {
+        let (mut input, data) = scope.new_collection::<(char, u64), isize>();
+        let captured = data.sliding_window(3, |x| x.1).inner.capture();
+        input.insert(('a', 0));
+        input.insert(('b', 2));
+        input.advance_to(1);
+        input.insert(('c', 1));
+        captured
+    });
+
+    assert_eq!(collect(captured.extract()), vec![
+        (('a', 0), 0, 1),
+        (('c', 1), 1, 1),
+        (('b', 2), 2, 1),
+        (('a', 0), 3, -1),
+        (('c', 1), 4, -1),
+        (('b', 2), 5, -1),
+    ]);
+}
+
+#[test]
+fn tumbling_window() {
+
+    let captured = timely::example(|scope| {
+        let (mut input, data) = scope.new_collection::<(char, u64), isize>();
+        let captured = data.tumbling_window(4, |x| x.1).inner.capture();
+        input.insert(('a', 1));
+        input.insert(('b', 5));
+        input.insert(('c', 6));
+        captured
+    });
+
+    assert_eq!(collect(captured.extract()), vec![
+        ((0, ('a', 1)), 0, 1),
+        ((0, ('a', 1)), 4, -1),
+        ((4, ('b', 5)), 4, 1),
+        ((4, ('c', 6)), 4, 1),
+        ((4, ('b', 5)), 8, -1),
+        ((4, ('c', 6)), 8, -1),
+    ]);
+}
@@ -0,0 +1,77 @@
+use std::collections::BTreeMap;
+
+use timely::dataflow::operators::Capture;
+use timely::dataflow::operators::capture::Extract;
+
+use differential_dataflow::input::Input;
+use differential_dataflow::consolidation::consolidate;
+use differential_dataflow::operators::TopK;
+
+/// Accumulates captured updates into the final contents of the collection.
+fn accumulate<D: Ord, T>(extracted: Vec<(T, Vec<(D, T, isize)>)>) -> Vec<(D, isize)> {
+    let mut updates = extracted.into_iter().flat_map(|(_, data)| data).map(|(d, _, r)| (d, r)).collect::<Vec<_>>();
+    consolidate(&mut updates);
+    updates
+}
+
+/// The values `0 .. 200` for each of three keys, retaining those not divisible by `round + 3`.
+fn contents(round: u64) -> Vec<(u64, u64)> {
+    (0 .. 3).flat_map(|key| (0 .. 200).map(move |val| (key, val))).filter(|(_, val)| val % (round + 3) != 0).collect()
+}
+
+fn reference(data: &[(u64, u64)], k: usize, largest: bool) -> Vec<((u64, u64), isize)> {
+    let mut groups = BTreeMap::<u64, Vec<u64>>::new();
+    for (key, val) in data.iter() {
+        groups.entry(*key).or_default().push(*val);
+    }
+    let mut result = Vec::new();
+    for (key, mut vals) in groups {
+        vals.sort();
+        if largest { vals.reverse(); }
+        result.extend(vals.into_iter().take(k).map(|val| ((key, val), 1)));
+    }
+    result.sort();
+    result
+}
+
+#[test]
+fn top_k_bottom_k() {
+
+    for round in 0 .. 3u64 {
+        let (top, bottom, flat) = timely::example(move |scope| {
+            let (mut input, data) = scope.new_collection::<(u64, u64), isize>();
+            // Small buckets ensure that each stage sees several buckets per key.
+            let top = data.top_k_core(5, |x| *x, &[58, 60, 62]).inner.capture();
+            let bottom = data.bottom_k(5, |x| *x).inner.capture();
+            let flat = data.top_k_core(5, |x| *x, &[]).inner.capture();
+            // Load the initial contents and then transition to the contents of `round`.
+            for datum in contents(0) { input.insert(datum); }
+            input.advance_to(1);
+            for datum in contents(0) { input.remove(datum); }
+            for datum in contents(round) { input.insert(datum); }
+            (top, bottom, flat)
+        });
+
+        let expected_top = reference(&contents(round), 5, true);
+        assert_eq!(accumulate(top.extract()), expected_top);
+        assert_eq!(accumulate(flat.extract()), expected_top);
+        assert_eq!(accumulate(bottom.extract()), reference(&contents(round), 5, false));
+    }
+}
+
+#[test]
+fn top_k_multiplicities() {
+
+    let top = timely::example(|scope| {
+        let (mut input, data) = scope.new_collection::<(u64, u64), isize>();
+        let top = data.top_k(3, |x| *x).inner.capture();
+        input.update((0, 10), 2);
+        input.update((0, 9), 2);
+        input.update((0, 8), 1);
+        input.advance_to(1);
+        input.update((0, 10), -1);
+        top
+    });
+
+    assert_eq!(accumulate(top.extract()), vec![((0, 9), 2), ((0, 10), 1)]);
+}
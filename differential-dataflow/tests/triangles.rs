@@ -0,0 +1,94 @@
+use rand::{Rng, SeedableRng, StdRng};
+
+use std::collections::{BTreeSet, HashMap};
+
+use timely::dataflow::operators::Capture;
+use timely::dataflow::operators::capture::Extract;
+
+use differential_dataflow::input::Input;
+use differential_dataflow::consolidation::consolidate;
+use differential_dataflow::algorithms::graphs::triangles::{triangles, triangle_counts};
+
+type Node = u32;
+type Edge = (Node, Node);
+
+#[test] fn triangles_10_30_50() { test_sizes(10, 30, 50); }
+#[test] fn triangles_50_400_10() { test_sizes(50, 400, 10); }
+
+fn test_sizes(nodes: Node, edges: usize, rounds: usize) {
+
+    let seed: &[_] = &[1, 2, 3, 4];
+    let mut rng1: StdRng = SeedableRng::from_seed(seed);    // rng for edge additions
+    let mut rng2: StdRng = SeedableRng::from_seed(seed);    // rng for edge deletions
+
+    // Edges in either direction, possibly repeated, with each later round adding and removing an edge.
+    let mut updates = vec![Vec::new()];
+    for _ in 0 .. edges {
+        updates[0].push(((rng1.gen_range(0, nodes), rng1.gen_range(0, nodes)), 1));
+    }
+    for _ in 1 .. rounds {
+        updates.push(vec![
+            ((rng1.gen_range(0, nodes), rng1.gen_range(0, nodes)), 1),
+            ((rng2.gen_range(0, nodes), rng2.gen_range(0, nodes)), -1),
+        ]);
+    }
+
+    let updates_list = updates.clone();
+    let (listed, counted) = timely::execute_directly(move |worker| {
+
+        let (mut input, listed, counted) = worker.dataflow::<usize, _, _>(|scope| {
+            let (input, edges) = scope.new_collection::<Edge, isize>();
+            let listed = triangles(&edges);
+            let counted = triangle_counts(&listed);
+            (input, listed.inner.capture(), counted.inner.capture())
+        });
+
+        for (round, changes) in updates_list.iter().enumerate() {
+            input.advance_to(round);
+            for (edge, diff) in changes.iter() {
+                input.update(*edge, *diff);
+            }
+        }
+        input.close();
+        while worker.step() { }
+
+        (listed, counted)
+    });
+
+    let listed = listed.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+    let counted = counted.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+
+    let mut graph = HashMap::new();
+    for (round, changes) in updates.iter().enumerate() {
+        for &((x, y), diff) in changes.iter() {
+            *graph.entry((x.min(y), x.max(y))).or_insert(0) += diff;
+        }
+
+        // Undirected edges with non-zero accumulated multiplicity across both directions.
+        let present = graph.iter().filter(|(&(x, y), &count)| count != 0 && x != y).map(|(edge, _)| *edge).collect::<BTreeSet<_>>();
+
+        let mut expected = Vec::new();
+        let mut expected_counts = HashMap::new();
+        for &(a, b) in present.iter() {
+            for c in (b + 1) .. nodes {
+                if present.contains(&(a, c)) && present.contains(&(b, c)) {
+                    expected.push(((a, b, c), 1));
+                    for node in [a, b, c] {
+                        *expected_counts.entry(node).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+        consolidate(&mut expected);
+        let mut expected_counts = expected_counts.into_iter().map(|(node, count)| ((node, count), 1)).collect::<Vec<_>>();
+        consolidate(&mut expected_counts);
+
+        let mut actual = listed.iter().filter(|(_, time, _)| *time <= round).map(|(data, _, diff)| (*data, *diff)).collect::<Vec<_>>();
+        consolidate(&mut actual);
+        assert_eq!(actual, expected);
+
+        let mut actual_counts = counted.iter().filter(|(_, time, _)| *time <= round).map(|(data, _, diff)| (*data, *diff)).collect::<Vec<_>>();
+        consolidate(&mut actual_counts);
+        assert_eq!(actual_counts, expected_counts);
+    }
+}
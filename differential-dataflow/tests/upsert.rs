@@ -0,0 +1,148 @@
+use timely::dataflow::{InputHandle, ProbeHandle};
+use timely::dataflow::operators::{Capture, Input, Probe};
+use timely::dataflow::operators::capture::Extract;
+use timely::order::{PartialOrder, Product};
+
+use differential_dataflow::consolidation::consolidate;
+use differential_dataflow::operators::arrange::upsert;
+use differential_dataflow::trace::implementations::{ValBuilder, ValSpine};
+
+type Upsert<T> = (u64, Option<u64>, T);
+
+/// Upserts to three keys, with repeated values, removals, and concurrent writes.
+fn upserts<T, F: Fn(u64)->T>(time: F) -> Vec<Upsert<T>> {
+    (0 .. 60u64).map(|i| {
+        let val = if i % 5 == 0 { None } else { Some(i % 7) };
+        (i % 3, val, time(i))
+    }).collect()
+}
+
+/// Accumulates captured updates at times less or equal to `time`.
+fn accumulate_to<T: PartialOrder>(extracted: &[(T, Vec<((u64, u64), T, isize)>)], time: &T) -> Vec<((u64, u64), isize)> {
+    let mut updates = extracted.iter().flat_map(|(_, data)| data.iter()).filter(|(_, t, _)| t.less_equal(time)).map(|(d, _, r)| (*d, *r)).collect::<Vec<_>>();
+    consolidate(&mut updates);
+    updates
+}
+
+/// For each key, the value of the greatest `(time, val)` among upserts at times less or equal to `time`.
+fn reference<T: PartialOrder+Ord>(upserts: &[Upsert<T>], time: &T) -> Vec<((u64, u64), isize)> {
+    let mut result = Vec::new();
+    for key in 0 .. 3 {
+        let latest = upserts.iter().filter(|(k, _, t)| *k == key && t.less_equal(time)).map(|(_, v, t)| (t, v)).max();
+        if let Some((_, Some(val))) = latest {
+            result.push(((key, *val), 1));
+        }
+    }
+    result
+}
+
+#[test]
+fn upsert_lattice_product() {
+
+    type Time = Product<u64, u64>;
+    let upserts = upserts(|i| Product::new((i * 7) % 5, (i * 3) % 4));
+
+    let captured = timely::execute_directly({
+        let upserts = upserts.clone();
+        move |worker| {
+            let mut input = InputHandle::<Time, Upsert<Time>>::new();
+            let captured = worker.dataflow(|scope| {
+                let stream = scope.input_from(&mut input);
+                upsert::arrange_from_upsert_lattice::<_, _, _, ValBuilder<u64, u64, Time, isize>, ValSpine<u64, u64, Time, isize>>(&stream, "UpsertLattice")
+                    .as_collection(|k, v| (*k, *v))
+                    .inner
+                    .capture()
+            });
+            for upsert in upserts {
+                input.send(upsert);
+            }
+            captured
+        }
+    });
+
+    let captured = captured.extract();
+    for outer in 0 .. 6 {
+        for inner in 0 .. 5 {
+            let time = Product::new(outer, inner);
+            assert_eq!(accumulate_to(&captured, &time), reference(&upserts, &time));
+        }
+    }
+}
+
+#[test]
+fn upsert_lattice_product_rounds() {
+
+    type Time = Product<u64, u64>;
+    let upserts = upserts(|i| Product::new((i * 7) % 5, (i * 3) % 4));
+
+    let captured = timely::execute_directly({
+        let upserts = upserts.clone();
+        move |worker| {
+            let mut input = InputHandle::<Time, Upsert<Time>>::new();
+            let mut probe = ProbeHandle::new();
+            let captured = worker.dataflow(|scope| {
+                let stream = scope.input_from(&mut input);
+                upsert::arrange_from_upsert_lattice::<_, _, _, ValBuilder<u64, u64, Time, isize>, ValSpine<u64, u64, Time, isize>>(&stream, "UpsertLattice")
+                    .as_collection(|k, v| (*k, *v))
+                    .inner
+                    .probe_with(&mut probe)
+                    .capture()
+            });
+            // Upserts arrive in rounds, so that later upserts dominate those already processed.
+            for round in 0 .. 5 {
+                input.advance_to(Product::new(round, 0));
+                for upsert in upserts.iter().filter(|(_, _, time)| time.outer == round) {
+                    input.send(upsert.clone());
+                }
+                input.advance_to(Product::new(round + 1, 0));
+                while probe.less_than(input.time()) { worker.step(); }
+            }
+            captured
+        }
+    });
+
+    let captured = captured.extract();
+    for outer in 0 .. 6 {
+        for inner in 0 .. 5 {
+            let time = Product::new(outer, inner);
+            assert_eq!(accumulate_to(&captured, &time), reference(&upserts, &time));
+        }
+    }
+}
+
+#[test]
+fn upsert_lattice_total() {
+
+    let upserts = upserts(|i| (i * 7) % 20);
+
+    let (lattice, total) = timely::execute_directly({
+        let upserts = upserts.clone();
+        move |worker| {
+            let mut input = InputHandle::<u64, Upsert<u64>>::new();
+            let captured = worker.dataflow(|scope| {
+                let stream = scope.input_from(&mut input);
+                let lattice = upsert::arrange_from_upsert_lattice::<_, _, _, ValBuilder<u64, u64, u64, isize>, ValSpine<u64, u64, u64, isize>>(&stream, "UpsertLattice")
+                    .as_collection(|k, v| (*k, *v))
+                    .inner
+                    .capture();
+                let total = upsert::arrange_from_upsert::<_, ValBuilder<u64, u64, u64, isize>, ValSpine<u64, u64, u64, isize>>(&stream, "Upsert")
+                    .as_collection(|k, v| (*k, *v))
+                    .inner
+                    .capture();
+                (lattice, total)
+            });
+            for upsert in upserts {
+                input.send(upsert);
+            }
+            captured
+        }
+    });
+
+    let lattice = lattice.extract();
+    let total = total.extract();
+    for time in 0 .. 21 {
+        let expected = reference(&upserts, &time);
+        assert_eq!(accumulate_to(&lattice, &time), expected);
+        assert_eq!(accumulate_to(&total, &time), expected);
+    }
+}